@@ -210,13 +210,18 @@ export class StellarService {
 
   async createListing(sellerSecretKey: string, tokenContractId: string, amount: number, pricePerToken: number, expirationDays: number): Promise<{ txHash: string; listingId: number }> {
     const sellerKeypair = StellarSdk.Keypair.fromSecret(sellerSecretKey);
+    // Listings without a positive expiration never expire
+    const expiresAt = expirationDays > 0
+      ? StellarSdk.nativeToScVal(Math.floor(Date.now() / 1000) + expirationDays * 86400, { type: 'u64' })
+      : StellarSdk.xdr.ScVal.scvVoid();
     // Call the correct contract method: list_property
     const operation = new StellarSdk.Contract(this.CONTRACTS.MARKETPLACE).call(
       'list_property',
       new StellarSdk.Address(sellerKeypair.publicKey()).toScVal(),
       new StellarSdk.Address(tokenContractId).toScVal(),
       StellarSdk.nativeToScVal(amount * 10000000, { type: 'i128' }),
-      StellarSdk.nativeToScVal(Math.round(pricePerToken * 10000000), { type: 'i128' })
+      StellarSdk.nativeToScVal(Math.round(pricePerToken * 10000000), { type: 'i128' }),
+      expiresAt
     );
     const txHash = await this.submitTransaction(sellerKeypair, operation);
 
@@ -1,13 +1,19 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env};
+use soroban_sdk::{contract, contractimpl, contracttype, token, Address, BytesN, Env};
+use stellar_property_access::{self as access, Role};
 use stellar_property_errors::Error;
 use stellar_property_events as events;
 use stellar_property_storage::{self as storage, DataKey};
 
 // DeFindex yield integration (design phase - not yet implemented)
+#[allow(dead_code)]
 mod yield_integration;
 
+mod migration;
+
+pub use migration::EscrowDataV1;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum EscrowStatus {
@@ -27,6 +33,7 @@ pub struct EscrowData {
     pub status: EscrowStatus,
     pub created_at: u64,
     pub timeout_at: u64,
+    pub resolved_at: u64, // 0 while locked
 }
 
 #[contract]
@@ -59,6 +66,7 @@ impl EscrowContract {
             .instance()
             .set(&DataKey::MarketplaceAddress, &marketplace_contract);
 
+        storage::set_schema_version(&env, migration::SCHEMA_VERSION);
         storage::set_initialized(&env);
 
         Ok(())
@@ -102,6 +110,7 @@ impl EscrowContract {
             status: EscrowStatus::Locked,
             created_at: current_time,
             timeout_at: current_time + timeout_duration,
+            resolved_at: 0,
         };
 
         // Store escrow data
@@ -118,35 +127,12 @@ impl EscrowContract {
     }
 
     /// Release funds to seller (called by marketplace after successful purchase)
-    pub fn release_to_seller(env: Env, escrow_id: u64) -> Result<(), Error> {
-        // SECURITY: Verify caller is authorized (marketplace or admin only)
-        let marketplace: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::MarketplaceAddress)
-            .unwrap();
-
-        let admin = storage::get_admin(&env);
-
-        // Require authorization from either marketplace or admin
-        // This prevents unauthorized release of funds
-        let mut authorized = false;
-
-        // Check if admin is calling
-        if storage::has_admin(&env) {
-            admin.require_auth();
-            authorized = true;
-        }
-
-        // TODO: In production with cross-contract calls, also check:
-        // if env.invoker() == marketplace { authorized = true; }
-
-        if !authorized {
-            return Err(Error::NotAuthorized);
-        }
+    pub fn release_to_seller(env: Env, caller: Address, escrow_id: u64) -> Result<(), Error> {
+        // SECURITY: Only the admin or the marketplace can release funds
+        Self::require_operator(&env, &caller)?;
 
         // Get escrow data
-        let mut escrow_data: EscrowData = storage::get_persistent(&env, &DataKey::Escrow(escrow_id))
+        let mut escrow_data = migration::read_escrow(&env, escrow_id)
             .ok_or(Error::EscrowNotFound)?;
 
         // Check status
@@ -156,6 +142,7 @@ impl EscrowContract {
 
         // Update status
         escrow_data.status = EscrowStatus::Released;
+        escrow_data.resolved_at = env.ledger().timestamp();
         storage::set_persistent(&env, &DataKey::Escrow(escrow_id), &escrow_data);
 
         // Transfer funds to seller
@@ -173,21 +160,18 @@ impl EscrowContract {
     }
 
     /// Refund funds to buyer (called if purchase fails or timeout)
-    pub fn refund_to_buyer(env: Env, escrow_id: u64) -> Result<(), Error> {
+    pub fn refund_to_buyer(env: Env, caller: Address, escrow_id: u64) -> Result<(), Error> {
         // Get escrow data
-        let mut escrow_data: EscrowData = storage::get_persistent(&env, &DataKey::Escrow(escrow_id))
+        let mut escrow_data = migration::read_escrow(&env, escrow_id)
             .ok_or(Error::EscrowNotFound)?;
 
         // Check if timeout reached or admin authorized
         let current_time = env.ledger().timestamp();
-        let admin = storage::get_admin(&env);
 
+        // Only the admin or the marketplace can refund before timeout
+        // After timeout anyone can unwind it, funds only go back to the buyer
         if current_time < escrow_data.timeout_at {
-            // Only admin can refund before timeout
-            admin.require_auth();
-        } else {
-            // After timeout, buyer can request refund
-            escrow_data.buyer.require_auth();
+            Self::require_operator(&env, &caller)?;
         }
 
         // Check status
@@ -197,6 +181,7 @@ impl EscrowContract {
 
         // Update status
         escrow_data.status = EscrowStatus::Refunded;
+        escrow_data.resolved_at = current_time;
         storage::set_persistent(&env, &DataKey::Escrow(escrow_id), &escrow_data);
 
         // Transfer funds back to buyer
@@ -213,11 +198,103 @@ impl EscrowContract {
         Ok(())
     }
 
+    // Require `caller` to authorize and be the admin or the marketplace
+    fn require_operator(env: &Env, caller: &Address) -> Result<(), Error> {
+        caller.require_auth();
+
+        let marketplace: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::MarketplaceAddress)
+            .unwrap();
+
+        if *caller != storage::get_admin(env) && *caller != marketplace {
+            return Err(Error::NotAuthorized);
+        }
+
+        Ok(())
+    }
+
+    // ========== ACCESS CONTROL ==========
+
+    /// Grant `role` to `account` (admin only)
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::grant_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Revoke `role` from `account` (admin only)
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::revoke_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Check whether `account` holds `role` (the admin holds every role)
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        access::has_role(&env, role, &account)
+    }
+
+    /// Nominate a new admin; takes effect once they call `accept_admin` (admin only)
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        access::propose_admin(&env, &new_admin);
+        Ok(())
+    }
+
+    /// Accept a pending admin nomination (pending admin only)
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        access::accept_admin(&env)?;
+        Ok(())
+    }
+
+    /// Get the nominated admin awaiting acceptance
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        access::pending_admin(&env)
+    }
+
+    // ========== UPGRADES ==========
+
+    /// Replace the contract code, keeping storage (admin only)
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        access::upgrade(&env, &new_wasm_hash);
+        Ok(())
+    }
+
+    /// Convert up to `limit` escrows from `cursor` to the current layout (admin only)
+    /// Returns the cursor to continue from, or None once the schema version is recorded
+    pub fn migrate(env: Env, cursor: u64, limit: u32) -> Result<Option<u64>, Error> {
+        access::require_admin(&env);
+
+        if limit == 0 || limit > migration::MAX_MIGRATION_BATCH {
+            return Err(Error::InvalidAmount);
+        }
+
+        let counter: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::EscrowCounter)
+            .unwrap_or(0);
+        let end = cursor.saturating_add(limit as u64).min(counter);
+
+        migration::migrate_escrows(&env, cursor, end);
+
+        if end < counter {
+            return Ok(Some(end));
+        }
+
+        migration::finish(&env);
+        Ok(None)
+    }
+
+    /// Storage layout version of this contract
+    pub fn schema_version(env: Env) -> u32 {
+        storage::get_schema_version(&env)
+    }
+
     // ========== VIEW FUNCTIONS ==========
 
     /// Get escrow status
     pub fn get_escrow_status(env: Env, escrow_id: u64) -> Result<EscrowStatus, Error> {
-        let escrow_data: EscrowData = storage::get_persistent(&env, &DataKey::Escrow(escrow_id))
+        let escrow_data = migration::read_escrow(&env, escrow_id)
             .ok_or(Error::EscrowNotFound)?;
 
         Ok(escrow_data.status)
@@ -225,7 +302,7 @@ impl EscrowContract {
 
     /// Get escrow data
     pub fn get_escrow_data(env: Env, escrow_id: u64) -> Result<EscrowData, Error> {
-        let escrow_data: EscrowData = storage::get_persistent(&env, &DataKey::Escrow(escrow_id))
+        let escrow_data = migration::read_escrow(&env, escrow_id)
             .ok_or(Error::EscrowNotFound)?;
 
         Ok(escrow_data)
@@ -233,13 +310,21 @@ impl EscrowContract {
 
     /// Check if escrow is timed out
     pub fn is_timed_out(env: Env, escrow_id: u64) -> Result<bool, Error> {
-        let escrow_data: EscrowData = storage::get_persistent(&env, &DataKey::Escrow(escrow_id))
+        let escrow_data = migration::read_escrow(&env, escrow_id)
             .ok_or(Error::EscrowNotFound)?;
 
         let current_time = env.ledger().timestamp();
         Ok(current_time >= escrow_data.timeout_at)
     }
 
+    /// Get the token escrowed funds are held in
+    pub fn usdc_token(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::PropertyData(soroban_sdk::Symbol::new(&env, "usdc")))
+            .unwrap()
+    }
+
     /// Get contract admin
     pub fn admin(env: Env) -> Address {
         storage::get_admin(&env)
@@ -249,23 +334,30 @@ impl EscrowContract {
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::{testutils::{Address as _, Ledger, LedgerInfo}, Address, Env};
+    use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Env};
 
     fn create_token_contract<'a>(env: &Env, admin: &Address) -> token::StellarAssetClient<'a> {
         token::StellarAssetClient::new(env, &env.register_stellar_asset_contract_v2(admin.clone()).address())
     }
 
+    fn token_balance(env: &Env, token: &Address, id: &Address) -> i128 {
+        token::TokenClient::new(env, token).balance(id)
+    }
+
     #[test]
     fn test_initialize() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, EscrowContract);
+        env.mock_all_auths();
+
+        let contract_id = env.register(EscrowContract, ());
         let client = EscrowContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
         let usdc_admin = Address::generate(&env);
         let usdc_token = create_token_contract(&env, &usdc_admin);
+        let marketplace = Address::generate(&env);
 
-        client.initialize(&admin, &usdc_token.address);
+        client.initialize(&admin, &usdc_token.address, &marketplace);
 
         assert_eq!(client.admin(), admin);
     }
@@ -275,7 +367,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, EscrowContract);
+        let contract_id = env.register(EscrowContract, ());
         let client = EscrowContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -283,11 +375,12 @@ mod test {
         let seller = Address::generate(&env);
         let usdc_admin = Address::generate(&env);
         let usdc_token = create_token_contract(&env, &usdc_admin);
+        let marketplace = Address::generate(&env);
 
         // Mint USDC to buyer
         usdc_token.mint(&buyer, &1000_0000000);
 
-        client.initialize(&admin, &usdc_token.address);
+        client.initialize(&admin, &usdc_token.address, &marketplace);
 
         // Lock funds
         let amount = 500_0000000;
@@ -301,18 +394,18 @@ mod test {
         assert_eq!(status, EscrowStatus::Locked);
 
         // Check contract received funds
-        assert_eq!(usdc_token.balance(&contract_id), amount);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), amount);
 
         // Release funds to seller
-        client.release_to_seller(&escrow_id);
+        client.release_to_seller(&admin, &escrow_id);
 
         // Check status updated
         let status = client.get_escrow_status(&escrow_id);
         assert_eq!(status, EscrowStatus::Released);
 
         // Check seller received funds
-        assert_eq!(usdc_token.balance(&seller), amount);
-        assert_eq!(usdc_token.balance(&contract_id), 0);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), amount);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 0);
     }
 
     #[test]
@@ -320,7 +413,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, EscrowContract);
+        let contract_id = env.register(EscrowContract, ());
         let client = EscrowContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -328,10 +421,11 @@ mod test {
         let seller = Address::generate(&env);
         let usdc_admin = Address::generate(&env);
         let usdc_token = create_token_contract(&env, &usdc_admin);
+        let marketplace = Address::generate(&env);
 
         usdc_token.mint(&buyer, &1000_0000000);
 
-        client.initialize(&admin, &usdc_token.address);
+        client.initialize(&admin, &usdc_token.address, &marketplace);
 
         let amount = 500_0000000;
         let listing_id = 1;
@@ -340,43 +434,135 @@ mod test {
         let escrow_id = client.lock_funds(&buyer, &seller, &amount, &listing_id, &timeout);
 
         // Advance time past timeout
-        env.ledger().set(LedgerInfo {
-            timestamp: env.ledger().timestamp() + timeout + 1,
-            protocol_version: 20,
-            sequence_number: env.ledger().sequence(),
-            network_id: Default::default(),
-            base_reserve: 10,
-            min_temp_entry_ttl: 10,
-            min_persistent_entry_ttl: 10,
-            max_entry_ttl: 3110400,
-        });
+        env.ledger().set_timestamp(env.ledger().timestamp() + timeout + 1);
 
-        // Refund to buyer
-        client.refund_to_buyer(&escrow_id);
+        // Anyone can refund to the buyer after the timeout
+        client.refund_to_buyer(&Address::generate(&env), &escrow_id);
 
         // Check status
         let status = client.get_escrow_status(&escrow_id);
         assert_eq!(status, EscrowStatus::Refunded);
 
         // Check buyer received refund
-        assert_eq!(usdc_token.balance(&buyer), 1000_0000000);
-        assert_eq!(usdc_token.balance(&contract_id), 0);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 1000_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 0);
+    }
+
+    #[test]
+    fn test_release_and_refund_require_operator() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(EscrowContract, ());
+        let client = EscrowContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let outsider = Address::generate(&env);
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        let marketplace = Address::generate(&env);
+
+        usdc_token.mint(&buyer, &1000_0000000);
+        client.initialize(&admin, &usdc_token.address, &marketplace);
+        assert_eq!(client.usdc_token(), usdc_token.address);
+
+        let first = client.lock_funds(&buyer, &seller, &400_0000000, &1, &100);
+        let second = client.lock_funds(&buyer, &seller, &600_0000000, &1, &100);
+
+        assert_eq!(client.try_release_to_seller(&outsider, &first), Err(Ok(Error::NotAuthorized)));
+        assert_eq!(client.try_refund_to_buyer(&outsider, &first), Err(Ok(Error::NotAuthorized)));
+        assert_eq!(client.try_refund_to_buyer(&seller, &first), Err(Ok(Error::NotAuthorized)));
+
+        // The marketplace settles escrows like the admin
+        client.release_to_seller(&marketplace, &first);
+        client.refund_to_buyer(&marketplace, &second);
+
+        assert_eq!(client.try_refund_to_buyer(&marketplace, &first), Err(Ok(Error::EscrowNotLocked)));
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), 400_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 600_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 0);
     }
 
     #[test]
-    #[should_panic(expected = "Error(EscrowNotFound)")]
+    #[should_panic(expected = "Error(Contract, #41)")]
     fn test_get_nonexistent_escrow() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, EscrowContract);
+        env.mock_all_auths();
+
+        let contract_id = env.register(EscrowContract, ());
         let client = EscrowContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
         let usdc_admin = Address::generate(&env);
         let usdc_token = create_token_contract(&env, &usdc_admin);
+        let marketplace = Address::generate(&env);
 
-        client.initialize(&admin, &usdc_token.address);
+        client.initialize(&admin, &usdc_token.address, &marketplace);
 
         // Try to get nonexistent escrow
         client.get_escrow_status(&999);
     }
+
+    #[test]
+    fn test_upgrade_and_migrate_escrows() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(EscrowContract, ());
+        let client = EscrowContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let usdc_admin = Address::generate(&env);
+        let usdc_token = create_token_contract(&env, &usdc_admin);
+
+        usdc_token.mint(&buyer, &1000_0000000);
+        client.initialize(&admin, &usdc_token.address, &Address::generate(&env));
+
+        // Live escrow in the current layout
+        let escrow_id = client.lock_funds(&buyer, &seller, &300_0000000, &1, &86400);
+
+        // A locked escrow left behind by a version 1 deployment
+        let old_escrow = EscrowDataV1 {
+            buyer: buyer.clone(),
+            seller: seller.clone(),
+            amount: 200_0000000,
+            token: usdc_token.address.clone(),
+            listing_id: 2,
+            status: EscrowStatus::Locked,
+            created_at: 0,
+            timeout_at: 86400,
+        };
+        usdc_token.mint(&contract_id, &200_0000000);
+        env.as_contract(&contract_id, || {
+            storage::set_persistent(&env, &DataKey::Escrow(1), &old_escrow);
+            storage::set_persistent(&env, &DataKey::EscrowCounter, &2u64);
+            env.storage().instance().remove(&DataKey::SchemaVersion);
+        });
+        assert_eq!(client.schema_version(), 1);
+
+        let wasm_hash = stellar_property_access::testutils::upload_upgrade_wasm(&env);
+        client.upgrade(&wasm_hash);
+        env.register_at(&contract_id, EscrowContract, ());
+
+        // Readable before it is rewritten
+        assert_eq!(client.get_escrow_data(&1).resolved_at, 0);
+        assert_eq!(client.get_escrow_status(&1), EscrowStatus::Locked);
+
+        assert_eq!(client.migrate(&0, &100), None);
+        assert_eq!(client.schema_version(), migration::SCHEMA_VERSION);
+
+        // Both escrows settle normally
+        env.ledger().with_mut(|li| li.timestamp = 500);
+        client.release_to_seller(&admin, &escrow_id);
+        client.refund_to_buyer(&admin, &1);
+
+        assert_eq!(client.get_escrow_data(&escrow_id).resolved_at, 500);
+        assert_eq!(client.get_escrow_data(&1).status, EscrowStatus::Refunded);
+        assert_eq!(client.get_escrow_data(&1).resolved_at, 500);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), 300_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 900_0000000);
+    }
 }
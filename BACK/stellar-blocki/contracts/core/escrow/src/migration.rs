@@ -0,0 +1,84 @@
+// Storage layout versions
+//
+//   1 - initial layout
+//   2 - `EscrowData` records `resolved_at` (0 while funds are locked)
+//
+// Escrows stored by an older version are converted when read, and `migrate`
+// rewrites them in batches like the marketplace does for listings.
+
+use soroban_sdk::{contracttype, Address, Env, Map, Symbol, TryFromVal, Val};
+use stellar_property_events as events;
+use stellar_property_storage::{self as storage, DataKey};
+
+use crate::{EscrowData, EscrowStatus};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+// SECURITY: Maximum escrows converted per call, keeps each call within budget
+pub const MAX_MIGRATION_BATCH: u32 = 100;
+
+/// `EscrowData` as stored by schema version 1
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowDataV1 {
+    pub buyer: Address,
+    pub seller: Address,
+    pub amount: i128,
+    pub token: Address,
+    pub listing_id: u64,
+    pub status: EscrowStatus,
+    pub created_at: u64,
+    pub timeout_at: u64,
+}
+
+impl From<EscrowDataV1> for EscrowData {
+    fn from(old: EscrowDataV1) -> Self {
+        // The resolution time of settled version 1 escrows was not recorded
+        EscrowData {
+            buyer: old.buyer,
+            seller: old.seller,
+            amount: old.amount,
+            token: old.token,
+            listing_id: old.listing_id,
+            status: old.status,
+            created_at: old.created_at,
+            timeout_at: old.timeout_at,
+            resolved_at: 0,
+        }
+    }
+}
+
+// Returns the escrow and whether it is stored in the old layout
+fn load(env: &Env, escrow_id: u64) -> Option<(EscrowData, bool)> {
+    let raw: Map<Symbol, Val> = storage::get_persistent(env, &DataKey::Escrow(escrow_id))?;
+    let val = raw.to_val();
+
+    if raw.contains_key(Symbol::new(env, "resolved_at")) {
+        EscrowData::try_from_val(env, &val).ok().map(|escrow| (escrow, false))
+    } else {
+        EscrowDataV1::try_from_val(env, &val).ok().map(|old| (old.into(), true))
+    }
+}
+
+/// Read an escrow written by any schema version
+pub fn read_escrow(env: &Env, escrow_id: u64) -> Option<EscrowData> {
+    load(env, escrow_id).map(|(escrow, _)| escrow)
+}
+
+/// Rewrite escrows `[start, end)` that still use an old layout
+pub fn migrate_escrows(env: &Env, start: u64, end: u64) {
+    for escrow_id in start..end {
+        if let Some((escrow, true)) = load(env, escrow_id) {
+            storage::set_persistent(env, &DataKey::Escrow(escrow_id), &escrow);
+        }
+    }
+}
+
+/// Record that storage now follows the current layout
+pub fn finish(env: &Env) {
+    let from_version = storage::get_schema_version(env);
+    if from_version != SCHEMA_VERSION {
+        storage::set_schema_version(env, SCHEMA_VERSION);
+        events::schema_migrated(env, from_version, SCHEMA_VERSION);
+    }
+}
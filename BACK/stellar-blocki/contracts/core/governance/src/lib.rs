@@ -0,0 +1,620 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, String};
+use stellar_property_access::{self as access, Role};
+use stellar_property_errors::GovernanceError as Error;
+use stellar_property_events as events;
+use stellar_property_storage as storage;
+
+// Property token interface used for snapshot-based vote weights
+mod token_interface {
+    use soroban_sdk::{contractclient, Address, Env};
+
+    #[contractclient(name = "PropertyTokenClient")]
+    #[allow(dead_code)]
+    pub trait PropertyToken {
+        fn balance(env: Env, id: Address) -> i128;
+        fn snapshot(env: Env) -> u64;
+        fn snapshot_ledger(env: Env, snapshot_id: u64) -> Option<u32>;
+        fn balance_at(env: Env, owner: Address, ledger: u32) -> i128;
+        fn total_supply_at(env: Env, ledger: u32) -> i128;
+    }
+}
+
+use token_interface::PropertyTokenClient;
+
+// Storage layout version written by this code (1 = initial layout)
+const SCHEMA_VERSION: u32 = 1;
+
+#[contracttype]
+#[derive(Clone)]
+enum GovernanceKey {
+    Token,
+    Config,
+    ProposalCounter,
+    Proposal(u64),
+    Outcome(u64),
+    Vote(u64, Address), // (proposal_id, voter)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GovernanceConfig {
+    pub proposal_threshold: i128, // Minimum token balance to create a proposal
+    pub voting_period: u64,       // Seconds
+    pub quorum_bps: i128,         // Basis points of supply (10000 = 100%)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProposalAction {
+    Renovation,
+    SellProperty,
+    ChangePropertyManager(Address),
+    Other,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum VoteChoice {
+    Against = 0,
+    For = 1,
+    Abstain = 2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProposalStatus {
+    Active,
+    Passed,
+    Rejected,
+    Executed,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Address,
+    pub action: ProposalAction,
+    pub description: String,
+    pub snapshot_ledger: u32,  // Vote weights are balances at the end of this ledger
+    pub supply: i128,          // Circulating supply at the snapshot
+    pub quorum_bps: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub votes_for: i128,
+    pub votes_against: i128,
+    pub votes_abstain: i128,
+    pub status: ProposalStatus,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalOutcome {
+    pub proposal_id: u64,
+    pub passed: bool,
+    pub quorum_reached: bool,
+    pub turnout_bps: i128,
+    pub votes_for: i128,
+    pub votes_against: i128,
+    pub votes_abstain: i128,
+    pub decided_at: u64,
+    pub executed_at: u64, // 0 until executed
+    pub executor: Option<Address>,
+}
+
+#[contract]
+pub struct GovernanceContract;
+
+#[contractimpl]
+impl GovernanceContract {
+    /// Initialize governance for a property token
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        token: Address,
+        proposal_threshold: i128,
+        voting_period: u64,
+        quorum_bps: i128,
+    ) -> Result<(), Error> {
+        if storage::is_initialized(&env) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        admin.require_auth();
+
+        let config = Self::validate_config(proposal_threshold, voting_period, quorum_bps)?;
+
+        storage::set_admin(&env, &admin);
+        env.storage().instance().set(&GovernanceKey::Token, &token);
+        env.storage().instance().set(&GovernanceKey::Config, &config);
+
+        storage::set_schema_version(&env, SCHEMA_VERSION);
+        storage::set_initialized(&env);
+
+        Ok(())
+    }
+
+    /// Update proposal threshold, voting period and quorum (admin only)
+    /// Applies to proposals created afterwards
+    pub fn set_config(
+        env: Env,
+        proposal_threshold: i128,
+        voting_period: u64,
+        quorum_bps: i128,
+    ) -> Result<(), Error> {
+        access::require_admin(&env);
+
+        let config = Self::validate_config(proposal_threshold, voting_period, quorum_bps)?;
+        env.storage().instance().set(&GovernanceKey::Config, &config);
+
+        Ok(())
+    }
+
+    /// Create a proposal; the proposer must hold at least the proposal threshold
+    pub fn create_proposal(
+        env: Env,
+        proposer: Address,
+        action: ProposalAction,
+        description: String,
+    ) -> Result<u64, Error> {
+        proposer.require_auth();
+
+        let config = Self::config(env.clone());
+        let token = PropertyTokenClient::new(&env, &Self::token(env.clone()));
+
+        if token.balance(&proposer) < config.proposal_threshold {
+            return Err(Error::BelowProposalThreshold);
+        }
+
+        // Freeze vote weights at the current ledger
+        let snapshot_id = token.snapshot();
+        let snapshot_ledger = token
+            .snapshot_ledger(&snapshot_id)
+            .ok_or(Error::NotInitialized)?;
+        let supply = token.total_supply_at(&snapshot_ledger);
+
+        let start_time = env.ledger().timestamp();
+        let end_time = start_time
+            .checked_add(config.voting_period)
+            .ok_or(Error::InvalidAmount)?;
+
+        let proposal_id = storage::get_and_increment_counter(&env, GovernanceKey::ProposalCounter);
+
+        let proposal = Proposal {
+            id: proposal_id,
+            proposer: proposer.clone(),
+            action,
+            description,
+            snapshot_ledger,
+            supply,
+            quorum_bps: config.quorum_bps,
+            start_time,
+            end_time,
+            votes_for: 0,
+            votes_against: 0,
+            votes_abstain: 0,
+            status: ProposalStatus::Active,
+        };
+
+        storage::set_persistent(&env, &GovernanceKey::Proposal(proposal_id), &proposal);
+
+        events::proposal_created(&env, proposal_id, proposer, snapshot_ledger, end_time);
+
+        Ok(proposal_id)
+    }
+
+    /// Cast a vote weighted by the voter's balance at the proposal snapshot
+    pub fn vote(
+        env: Env,
+        voter: Address,
+        proposal_id: u64,
+        support: VoteChoice,
+    ) -> Result<i128, Error> {
+        voter.require_auth();
+
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id)?;
+
+        if proposal.status != ProposalStatus::Active {
+            return Err(Error::VotingClosed);
+        }
+
+        // Voting opens the ledger after the snapshot so weights can't change mid-vote
+        if env.ledger().sequence() <= proposal.snapshot_ledger {
+            return Err(Error::VotingNotStarted);
+        }
+
+        if env.ledger().timestamp() >= proposal.end_time {
+            return Err(Error::VotingClosed);
+        }
+
+        let vote_key = GovernanceKey::Vote(proposal_id, voter.clone());
+        if storage::has_persistent(&env, &vote_key) {
+            return Err(Error::AlreadyVoted);
+        }
+
+        let token = PropertyTokenClient::new(&env, &Self::token(env.clone()));
+        let weight = token.balance_at(&voter, &proposal.snapshot_ledger);
+        if weight <= 0 {
+            return Err(Error::NoVotingPower);
+        }
+
+        match support {
+            VoteChoice::For => {
+                proposal.votes_for = proposal.votes_for.checked_add(weight)
+                    .ok_or(Error::InvalidAmount)?;
+            }
+            VoteChoice::Against => {
+                proposal.votes_against = proposal.votes_against.checked_add(weight)
+                    .ok_or(Error::InvalidAmount)?;
+            }
+            VoteChoice::Abstain => {
+                proposal.votes_abstain = proposal.votes_abstain.checked_add(weight)
+                    .ok_or(Error::InvalidAmount)?;
+            }
+        }
+
+        storage::set_persistent(&env, &vote_key, &support);
+        storage::set_persistent(&env, &GovernanceKey::Proposal(proposal_id), &proposal);
+
+        events::vote_cast(&env, proposal_id, voter, support as u32, weight);
+
+        Ok(weight)
+    }
+
+    /// Close voting and record the outcome (anyone can call after the end time)
+    pub fn finalize_proposal(env: Env, proposal_id: u64) -> Result<ProposalOutcome, Error> {
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id)?;
+
+        if proposal.status != ProposalStatus::Active {
+            return Err(Error::ProposalAlreadyDecided);
+        }
+
+        if env.ledger().timestamp() < proposal.end_time {
+            return Err(Error::VotingNotEnded);
+        }
+
+        // Turnout counts every vote cast, including abstentions
+        let total_votes = proposal.votes_for
+            .checked_add(proposal.votes_against)
+            .and_then(|v| v.checked_add(proposal.votes_abstain))
+            .ok_or(Error::InvalidAmount)?;
+
+        let turnout_bps = if proposal.supply > 0 {
+            total_votes.checked_mul(10000)
+                .and_then(|v| v.checked_div(proposal.supply))
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        let quorum_reached = turnout_bps >= proposal.quorum_bps;
+        let passed = quorum_reached && proposal.votes_for > proposal.votes_against;
+
+        proposal.status = if passed {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        };
+        storage::set_persistent(&env, &GovernanceKey::Proposal(proposal_id), &proposal);
+
+        let outcome = ProposalOutcome {
+            proposal_id,
+            passed,
+            quorum_reached,
+            turnout_bps,
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            votes_abstain: proposal.votes_abstain,
+            decided_at: env.ledger().timestamp(),
+            executed_at: 0,
+            executor: None,
+        };
+        storage::set_persistent(&env, &GovernanceKey::Outcome(proposal_id), &outcome);
+
+        events::proposal_decided(
+            &env,
+            proposal_id,
+            passed,
+            proposal.votes_for,
+            proposal.votes_against,
+            turnout_bps,
+        );
+
+        Ok(outcome)
+    }
+
+    /// Record that a passed proposal has been carried out (admin only)
+    pub fn execute_proposal(env: Env, proposal_id: u64) -> Result<(), Error> {
+        let admin = access::require_admin(&env);
+
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id)?;
+
+        if proposal.status != ProposalStatus::Passed {
+            return Err(Error::ProposalNotPassed);
+        }
+
+        let mut outcome: ProposalOutcome =
+            storage::get_persistent(&env, &GovernanceKey::Outcome(proposal_id))
+                .ok_or(Error::ProposalNotFound)?;
+
+        proposal.status = ProposalStatus::Executed;
+        outcome.executed_at = env.ledger().timestamp();
+        outcome.executor = Some(admin.clone());
+
+        storage::set_persistent(&env, &GovernanceKey::Proposal(proposal_id), &proposal);
+        storage::set_persistent(&env, &GovernanceKey::Outcome(proposal_id), &outcome);
+
+        events::proposal_executed(&env, proposal_id, admin);
+
+        Ok(())
+    }
+
+    // ========== ACCESS CONTROL ==========
+
+    /// Grant `role` to `account` (admin only)
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::grant_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Revoke `role` from `account` (admin only)
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::revoke_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Check whether `account` holds `role` (the admin holds every role)
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        access::has_role(&env, role, &account)
+    }
+
+    /// Nominate a new admin; takes effect once they call `accept_admin` (admin only)
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        access::propose_admin(&env, &new_admin);
+        Ok(())
+    }
+
+    /// Accept a pending admin nomination (pending admin only)
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        access::accept_admin(&env).map_err(|_| Error::NotAuthorized)?;
+        Ok(())
+    }
+
+    /// Get the nominated admin awaiting acceptance
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        access::pending_admin(&env)
+    }
+
+    // ========== UPGRADES ==========
+
+    /// Replace the contract code, keeping storage (admin only)
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        access::upgrade(&env, &new_wasm_hash);
+        Ok(())
+    }
+
+    /// Storage layout version of this contract
+    pub fn schema_version(env: Env) -> u32 {
+        storage::get_schema_version(&env)
+    }
+
+    // ========== VIEW FUNCTIONS ==========
+
+    /// Get proposal details
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Result<Proposal, Error> {
+        storage::get_persistent(&env, &GovernanceKey::Proposal(proposal_id))
+            .ok_or(Error::ProposalNotFound)
+    }
+
+    /// Get the recorded outcome of a decided proposal
+    pub fn get_outcome(env: Env, proposal_id: u64) -> Option<ProposalOutcome> {
+        storage::get_persistent(&env, &GovernanceKey::Outcome(proposal_id))
+    }
+
+    /// Get the vote cast by `voter` on a proposal
+    pub fn get_vote(env: Env, proposal_id: u64, voter: Address) -> Option<VoteChoice> {
+        storage::get_persistent(&env, &GovernanceKey::Vote(proposal_id, voter))
+    }
+
+    /// Get the number of proposals created
+    pub fn proposal_count(env: Env) -> u64 {
+        storage::get_persistent(&env, &GovernanceKey::ProposalCounter).unwrap_or(0)
+    }
+
+    /// Get governance configuration
+    pub fn config(env: Env) -> GovernanceConfig {
+        env.storage().instance().get(&GovernanceKey::Config).unwrap()
+    }
+
+    /// Get the governed property token
+    pub fn token(env: Env) -> Address {
+        env.storage().instance().get(&GovernanceKey::Token).unwrap()
+    }
+
+    /// Get contract admin
+    pub fn admin(env: Env) -> Address {
+        storage::get_admin(&env)
+    }
+
+    fn validate_config(
+        proposal_threshold: i128,
+        voting_period: u64,
+        quorum_bps: i128,
+    ) -> Result<GovernanceConfig, Error> {
+        if proposal_threshold < 0 || voting_period == 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Same 10000 basis point scale as get_ownership_percentage
+        if quorum_bps <= 0 || quorum_bps > 10000 {
+            return Err(Error::InvalidPercentage);
+        }
+
+        Ok(GovernanceConfig {
+            proposal_threshold,
+            voting_period,
+            quorum_bps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use property_token::{PropertyTokenContract, PropertyTokenContractClient};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        Symbol,
+    };
+
+    const SUPPLY: i128 = 1_000_000_0000000;
+    const VOTING_PERIOD: u64 = 7 * 24 * 3600;
+
+    struct Setup<'a> {
+        admin: Address,
+        token: PropertyTokenContractClient<'a>,
+        governance: GovernanceContractClient<'a>,
+    }
+
+    fn setup<'a>(env: &Env, quorum_bps: i128) -> Setup<'a> {
+        env.mock_all_auths();
+        env.ledger().set_sequence_number(10);
+
+        let admin = Address::generate(env);
+
+        let token_id = env.register(PropertyTokenContract, ());
+        let token = PropertyTokenContractClient::new(env, &token_id);
+        token.initialize(
+            &admin,
+            &Symbol::new(env, "PROP001"),
+            &String::from_str(env, "Property Token"),
+            &String::from_str(env, "PROP"),
+            &SUPPLY,
+        );
+
+        let governance_id = env.register(GovernanceContract, ());
+        let governance = GovernanceContractClient::new(env, &governance_id);
+        governance.initialize(&admin, &token_id, &10_000_0000000, &VOTING_PERIOD, &quorum_bps);
+
+        Setup { admin, token, governance }
+    }
+
+    fn next_ledger(env: &Env, seconds: u64) {
+        env.ledger().with_mut(|li| {
+            li.sequence_number += 1;
+            li.timestamp += seconds;
+        });
+    }
+
+    #[test]
+    fn test_proposal_passes_with_snapshot_weights() {
+        let env = Env::default();
+        let s = setup(&env, 5000);
+
+        let holder1 = Address::generate(&env);
+        let holder2 = Address::generate(&env);
+        let latecomer = Address::generate(&env);
+        s.token.transfer(&s.admin, &holder1, &400_000_0000000);
+        s.token.transfer(&s.admin, &holder2, &300_000_0000000);
+
+        let proposal_id = s.governance.create_proposal(
+            &holder1,
+            &ProposalAction::Renovation,
+            &String::from_str(&env, "Replace the roof"),
+        );
+
+        next_ledger(&env, 60);
+
+        // Tokens moved after the snapshot carry no extra weight
+        s.token.transfer(&holder2, &latecomer, &100_000_0000000);
+
+        assert_eq!(s.governance.vote(&holder1, &proposal_id, &VoteChoice::For), 400_000_0000000);
+        assert_eq!(s.governance.vote(&holder2, &proposal_id, &VoteChoice::Against), 300_000_0000000);
+        assert_eq!(
+            s.governance.try_vote(&latecomer, &proposal_id, &VoteChoice::Against),
+            Err(Ok(Error::NoVotingPower))
+        );
+        assert_eq!(
+            s.governance.try_vote(&holder1, &proposal_id, &VoteChoice::For),
+            Err(Ok(Error::AlreadyVoted))
+        );
+
+        assert_eq!(
+            s.governance.try_finalize_proposal(&proposal_id),
+            Err(Ok(Error::VotingNotEnded))
+        );
+
+        next_ledger(&env, VOTING_PERIOD);
+
+        let outcome = s.governance.finalize_proposal(&proposal_id);
+        assert!(outcome.passed);
+        assert!(outcome.quorum_reached);
+        assert_eq!(outcome.turnout_bps, 7000);
+        assert_eq!(s.governance.get_proposal(&proposal_id).status, ProposalStatus::Passed);
+
+        s.governance.execute_proposal(&proposal_id);
+        let outcome = s.governance.get_outcome(&proposal_id).unwrap();
+        assert_eq!(outcome.executor, Some(s.admin.clone()));
+        assert_eq!(s.governance.get_proposal(&proposal_id).status, ProposalStatus::Executed);
+    }
+
+    #[test]
+    fn test_proposal_rejected_without_quorum() {
+        let env = Env::default();
+        let s = setup(&env, 5000);
+
+        let holder = Address::generate(&env);
+        let new_manager = Address::generate(&env);
+        s.token.transfer(&s.admin, &holder, &200_000_0000000);
+
+        let proposal_id = s.governance.create_proposal(
+            &holder,
+            &ProposalAction::ChangePropertyManager(new_manager),
+            &String::from_str(&env, "Switch property manager"),
+        );
+
+        // Voting opens in the ledger after the snapshot
+        assert_eq!(
+            s.governance.try_vote(&holder, &proposal_id, &VoteChoice::For),
+            Err(Ok(Error::VotingNotStarted))
+        );
+
+        next_ledger(&env, 60);
+        s.governance.vote(&holder, &proposal_id, &VoteChoice::For);
+
+        next_ledger(&env, VOTING_PERIOD);
+        let outcome = s.governance.finalize_proposal(&proposal_id);
+        assert!(!outcome.passed);
+        assert!(!outcome.quorum_reached);
+        assert_eq!(outcome.turnout_bps, 2000);
+
+        assert_eq!(
+            s.governance.try_execute_proposal(&proposal_id),
+            Err(Ok(Error::ProposalNotPassed))
+        );
+        assert_eq!(
+            s.governance.try_finalize_proposal(&proposal_id),
+            Err(Ok(Error::ProposalAlreadyDecided))
+        );
+    }
+
+    #[test]
+    fn test_proposal_threshold() {
+        let env = Env::default();
+        let s = setup(&env, 5000);
+
+        let small_holder = Address::generate(&env);
+        s.token.transfer(&s.admin, &small_holder, &1_0000000);
+
+        let result = s.governance.try_create_proposal(
+            &small_holder,
+            &ProposalAction::SellProperty,
+            &String::from_str(&env, "Sell the building"),
+        );
+        assert_eq!(result, Err(Ok(Error::BelowProposalThreshold)));
+        assert_eq!(s.governance.proposal_count(), 0);
+    }
+}
@@ -0,0 +1,75 @@
+// English auctions
+//
+// A lot of tokens is held by this contract until the auction settles. Bids
+// are for the whole lot: the first must reach the reserve price and each
+// later one must beat the high bid by the minimum increment. The high bid and
+// its buyer fee are held here. The bid it replaces is credited to its bidder
+// as a refund to claim (see refunds.rs) rather than paid back in the same
+// call, so a bidder who can't receive the payment can't block higher bids.
+// A bid close to the end pushes the end back so others can answer.
+// Once ended anyone can settle, sending the lot to the winner and the payment
+// to the seller, or the lot back to the seller when nobody met the reserve or
+// the winner can't receive it; that winner's bid is then credited the same way.
+
+use soroban_sdk::{contracttype, Address, Env};
+use stellar_property_storage as storage;
+
+// Bids in the last 10 minutes extend the auction to 10 minutes from the bid
+pub const ANTI_SNIPING_WINDOW: u64 = 600;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuctionStatus {
+    Active,
+    Sold,
+    Unsold,                        // No bid met the reserve, or the winner couldn't take the lot
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Auction {
+    pub id: u64,
+    pub seller: Address,
+    pub token: Address,            // Property token contract
+    pub payment_token: Address,    // Asset bids are paid in (USDC)
+    pub amount: i128,              // Tokens in the lot
+    pub reserve_price: i128,       // Lowest price the lot sells for
+    pub min_increment: i128,       // How much each bid must beat the last by
+    pub high_bid: i128,            // Price of the lot at the high bid, 0 before any bid
+    pub high_bidder: Option<Address>,
+    pub buyer_fee: i128,           // Held with the high bid
+    pub bid_count: u32,
+    pub status: AuctionStatus,
+    pub created_at: u64,
+    pub end_time: u64,             // No bids from this time on
+}
+
+impl Auction {
+    /// Lowest bid the auction accepts next
+    pub fn min_bid(&self) -> i128 {
+        if self.high_bidder.is_none() {
+            self.reserve_price
+        } else {
+            self.high_bid.saturating_add(self.min_increment)
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum AuctionKey {
+    Auction(u64),
+    Counter,
+}
+
+pub fn next_id(env: &Env) -> u64 {
+    storage::get_and_increment_counter(env, AuctionKey::Counter)
+}
+
+pub fn read(env: &Env, auction_id: u64) -> Option<Auction> {
+    storage::get_persistent(env, &AuctionKey::Auction(auction_id))
+}
+
+pub fn write(env: &Env, auction: &Auction) {
+    storage::set_persistent(env, &AuctionKey::Auction(auction.id), auction);
+}
@@ -0,0 +1,57 @@
+// Dutch auctions
+//
+// A Dutch auction is a listing whose price per token falls in a straight
+// line from the start price at `start_time` to the floor price at `end_time`,
+// and stays at the floor for `floor_duration` seconds after that, when the
+// listing expires. Until `start_time` the start price applies. Buyers pay the
+// price at the ledger time of their purchase, so these listings don't rest on
+// the order book.
+
+use soroban_sdk::{contracttype, Env};
+use stellar_property_storage as storage;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DutchAuction {
+    pub start_price: i128,         // Price per token until `start_time`
+    pub floor_price: i128,         // Price per token from `end_time` on
+    pub start_time: u64,
+    pub end_time: u64,
+    pub floor_duration: u64,       // Seconds the floor price holds before the listing expires
+}
+
+impl DutchAuction {
+    /// Price per token at `now`, rounded up in the seller's favour
+    pub fn price_at(&self, now: u64) -> i128 {
+        if now <= self.start_time {
+            return self.start_price;
+        }
+        if now >= self.end_time {
+            return self.floor_price;
+        }
+
+        // Can't overflow: checked against the full duration when listed
+        let elapsed = (now - self.start_time) as i128;
+        let duration = (self.end_time - self.start_time) as i128;
+        self.start_price - (self.start_price - self.floor_price) * elapsed / duration
+    }
+
+    /// Time from which the listing can no longer be bought
+    pub fn expires_at(&self) -> u64 {
+        self.end_time.saturating_add(self.floor_duration)
+    }
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DutchKey {
+    Auction(u64), // By listing id
+}
+
+pub fn read(env: &Env, listing_id: u64) -> Option<DutchAuction> {
+    storage::get_persistent(env, &DutchKey::Auction(listing_id))
+}
+
+pub fn write(env: &Env, listing_id: u64, auction: &DutchAuction) {
+    storage::set_persistent(env, &DutchKey::Auction(listing_id), auction);
+}
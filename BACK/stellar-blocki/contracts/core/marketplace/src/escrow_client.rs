@@ -0,0 +1,41 @@
+// EscrowContract interface used for reservation checkout
+//
+// Declared here instead of importing the compiled contract, so the marketplace
+// builds without the escrow WASM. Signatures must match `EscrowContract`.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env};
+
+/// Mirrors `escrow::EscrowStatus`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EscrowStatus {
+    Locked,
+    Released,
+    Refunded,
+}
+
+#[contractclient(name = "EscrowContractClient")]
+#[allow(dead_code)]
+pub trait EscrowContract {
+    /// Lock `amount` of the buyer's funds, returns the escrow id
+    fn lock_funds(
+        env: Env,
+        buyer: Address,
+        seller: Address,
+        amount: i128,
+        listing_id: u64,
+        timeout_duration: u64,
+    ) -> u64;
+
+    /// Pay the locked funds to the seller
+    fn release_to_seller(env: Env, caller: Address, escrow_id: u64);
+
+    /// Return the locked funds to the buyer
+    fn refund_to_buyer(env: Env, caller: Address, escrow_id: u64);
+
+    /// Current state of an escrow
+    fn get_escrow_status(env: Env, escrow_id: u64) -> EscrowStatus;
+
+    /// Token escrowed funds are held in
+    fn usdc_token(env: Env) -> Address;
+}
@@ -0,0 +1,106 @@
+// Platform fees on secondary trades
+//
+// Each side of a trade pays its basis points of the price, raised to the flat
+// minimum when one is set. The buyer's fee is paid on top of the price and the
+// seller's fee is taken from the proceeds; both go to the fee recipient.
+// A per-token schedule replaces the default one for that token.
+
+use soroban_sdk::{contracttype, Address, Env};
+use stellar_property_errors::MarketplaceError as Error;
+use stellar_property_storage as storage;
+
+pub const BPS_DENOMINATOR: i128 = 10000;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSchedule {
+    pub buyer_fee_bps: u32,
+    pub seller_fee_bps: u32,
+    pub min_fee: i128, // Flat minimum per charged side, 0 for none
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeQuote {
+    pub price: i128,           // Price of the tokens before fees
+    pub buyer_fee: i128,
+    pub seller_fee: i128,
+    pub buyer_total: i128,     // What the buyer pays
+    pub seller_proceeds: i128, // What the seller receives
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum FeeKey {
+    Schedule,
+    Recipient,
+    TokenSchedule(Address),
+}
+
+pub fn validate(schedule: &FeeSchedule) -> Result<(), Error> {
+    if schedule.buyer_fee_bps as i128 > BPS_DENOMINATOR || schedule.seller_fee_bps as i128 > BPS_DENOMINATOR {
+        return Err(Error::InvalidPercentage);
+    }
+
+    if schedule.min_fee < 0 {
+        return Err(Error::InvalidAmount);
+    }
+
+    Ok(())
+}
+
+pub fn set_default(env: &Env, schedule: &FeeSchedule, recipient: &Address) {
+    env.storage().instance().set(&FeeKey::Schedule, schedule);
+    env.storage().instance().set(&FeeKey::Recipient, recipient);
+}
+
+pub fn set_for_token(env: &Env, token: &Address, schedule: &Option<FeeSchedule>) {
+    let key = FeeKey::TokenSchedule(token.clone());
+    match schedule {
+        Some(schedule) => storage::set_persistent(env, &key, schedule),
+        None => storage::remove_persistent(env, &key),
+    }
+}
+
+pub fn recipient(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&FeeKey::Recipient)
+}
+
+/// Schedule that applies to `token`, no fees until one is set
+pub fn schedule_for(env: &Env, token: &Address) -> FeeSchedule {
+    storage::get_persistent(env, &FeeKey::TokenSchedule(token.clone()))
+        .or_else(|| env.storage().instance().get(&FeeKey::Schedule))
+        .unwrap_or(FeeSchedule {
+            buyer_fee_bps: 0,
+            seller_fee_bps: 0,
+            min_fee: 0,
+        })
+}
+
+// Fee for one side, never more than the price itself
+fn side_fee(price: i128, fee_bps: u32, min_fee: i128) -> Result<i128, Error> {
+    if fee_bps == 0 {
+        return Ok(0);
+    }
+
+    let fee = price.checked_mul(fee_bps as i128)
+        .ok_or(Error::InvalidAmount)?
+        / BPS_DENOMINATOR;
+
+    Ok(fee.max(min_fee).min(price))
+}
+
+/// Fees on a trade of `token` worth `price`
+pub fn quote(env: &Env, token: &Address, price: i128) -> Result<FeeQuote, Error> {
+    let schedule = schedule_for(env, token);
+    let buyer_fee = side_fee(price, schedule.buyer_fee_bps, schedule.min_fee)?;
+    let seller_fee = side_fee(price, schedule.seller_fee_bps, schedule.min_fee)?;
+
+    Ok(FeeQuote {
+        price,
+        buyer_fee,
+        seller_fee,
+        buyer_total: price.checked_add(buyer_fee).ok_or(Error::InvalidAmount)?,
+        seller_proceeds: price - seller_fee,
+    })
+}
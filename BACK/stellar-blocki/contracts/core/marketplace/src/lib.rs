@@ -1,48 +1,50 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env, Symbol, Vec};
-use stellar_property_errors::Error;
+use soroban_sdk::{contract, contractimpl, contracttype, token, Address, BytesN, Env, Symbol, Vec};
+use stellar_property_access::{self as access, Role};
+use stellar_property_errors::MarketplaceError as Error;
 use stellar_property_events as events;
 use stellar_property_storage::{self as storage, DataKey};
 
-// SECURITY: Maximum trade history entries to prevent unbounded storage
-const MAX_TRADE_HISTORY: u32 = 10000;
-
-// Import contract clients for cross-contract calls
-// NOTE: These imports require compiled WASM files
-// For now, we'll define placeholder clients until contracts are built
-// TODO: Uncomment when contracts are compiled
-
-// mod escrow {
-//     soroban_sdk::contractimport!(
-//         file = "../escrow/target/wasm32-unknown-unknown/release/escrow_contract.wasm"
-//     );
-// }
-// use escrow::Client as EscrowContractClient;
-
-// mod property_token {
-//     soroban_sdk::contractimport!(
-//         file = "../property-token/target/wasm32-unknown-unknown/release/property_token.wasm"
-//     );
-// }
-// use property_token::Client as PropertyTokenContractClient;
-
-// mod registry {
-//     soroban_sdk::contractimport!(
-//         file = "../registry/target/wasm32-unknown-unknown/release/ownership_registry.wasm"
-//     );
-// }
-// use registry::{Client as OwnershipRegistryContractClient, OwnerInfo};
-
-// Temporary: Define struct locally until we can import from compiled contract
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct OwnerInfo {
-    pub owner: Address,
-    pub percentage: i128,
-}
-
+// SECURITY: Maximum accounts synced to the registry per call, matches its per-update cap
+const MAX_SYNCED_OWNERS: u32 = 100;
+// SECURITY: Maximum listings returned per page
+const MAX_LISTINGS_PAGE: u32 = 100;
+// SECURITY: Maximum trades returned per page
+const MAX_TRADES_PAGE: u32 = 100;
+// SECURITY: Maximum candle buckets read per call, a week of hours
+const MAX_CANDLES: u32 = 168;
+
+// Cross-contract clients, declared from each contract's interface
+mod escrow_client;
+mod property_token_client;
+mod registry_client;
+
+mod auctions;
+mod dutch;
+mod fees;
+mod listing_index;
+mod migration;
+mod orderbook;
+mod refunds;
+mod reservations;
 mod swap;
+mod trades;
+
+use escrow_client::{EscrowContractClient, EscrowStatus};
+use listing_index::ListingSet;
+use orderbook::Side;
+use property_token_client::PropertyTokenContractClient;
+use registry_client::OwnershipRegistryContractClient;
+
+pub use auctions::{Auction, AuctionStatus};
+pub use dutch::DutchAuction;
+pub use fees::{FeeQuote, FeeSchedule};
+pub use migration::{ListingV1, TradeV1};
+pub use orderbook::{Bid, BidStatus, MarketDepth, PriceLevel};
+pub use registry_client::OwnerInfo;
+pub use reservations::{Reservation, ReservationStatus};
+pub use trades::{Candle, Interval};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -50,6 +52,7 @@ pub enum ListingStatus {
     Active,
     Cancelled,
     Completed,
+    Expired,
 }
 
 #[contracttype]
@@ -58,26 +61,59 @@ pub struct Listing {
     pub id: u64,
     pub seller: Address,
     pub token: Address,           // Property token contract
+    pub payment_token: Address,   // Asset the listing settles in (USDC)
     pub amount: i128,              // Amount of tokens for sale
-    pub price_per_token: i128,     // Price in USDC per token
+    pub price_per_token: i128,     // Price in USDC per token, the start price of a Dutch auction
     pub status: ListingStatus,
     pub created_at: u64,
+    pub updated_at: u64,
+    pub expires_at: Option<u64>,   // No purchases from this time on, None to never expire
+}
+
+impl Listing {
+    /// Whether the listing can no longer be bought from at `now`
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingsPage {
+    pub listings: Vec<Listing>,
+    pub next_cursor: Option<u32>,  // None when the last listing was returned
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Trade {
-    pub listing_id: u64,
+    pub token: Address,            // Property token traded
+    pub listing_id: Option<u64>,   // Listing filled, None for direct bid fills and auctions
+    pub bid_id: Option<u64>,       // Bid filled, None for listing purchases and auctions
     pub buyer: Address,
     pub seller: Address,
     pub amount: i128,
-    pub price: i128,
+    pub price: i128,               // Price before fees
+    pub buyer_fee: i128,
+    pub seller_fee: i128,
     pub timestamp: u64,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TradesPage {
+    pub trades: Vec<Trade>,
+    pub next_cursor: Option<u32>,  // None when the last trade was returned
+}
+
 #[contract]
 pub struct MarketplaceContract;
 
+// Asset new listings are bound to, set by the admin
+pub(crate) fn configured_payment_token(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&Symbol::new(env, "payment"))
+}
+
 #[contractimpl]
 impl MarketplaceContract {
     /// Initialize the marketplace contract
@@ -103,21 +139,98 @@ impl MarketplaceContract {
             .instance()
             .set(&Symbol::new(&env, "registry"), &registry_contract);
 
+        storage::set_schema_version(&env, migration::SCHEMA_VERSION);
         storage::set_initialized(&env);
 
         Ok(())
     }
 
     /// List property tokens for sale
+    /// A listing with `expires_at` can't be bought from once that time is reached
     pub fn list_property(
         env: Env,
         seller: Address,
         token: Address,
         amount: i128,
         price_per_token: i128,
+        expires_at: Option<u64>,
+    ) -> Result<u64, Error> {
+        seller.require_auth();
+
+        let mut listing = Self::create_listing(&env, seller, token, amount, price_per_token, expires_at)?;
+
+        // Fill crossing bids, then rest the rest on the book
+        Self::post_listing(&env, &mut listing)?;
+
+        Ok(listing.id)
+    }
+
+    /// List property tokens in a Dutch auction
+    /// The price per token falls linearly from the start price at
+    /// `start_time` to the floor price at `end_time`; buyers pay the price
+    /// at the time they buy. The listing expires `floor_duration` seconds
+    /// after `end_time`
+    pub fn list_dutch_auction(
+        env: Env,
+        seller: Address,
+        token: Address,
+        amount: i128,
+        auction: DutchAuction,
     ) -> Result<u64, Error> {
         seller.require_auth();
 
+        if auction.floor_price <= 0 || auction.start_price <= auction.floor_price {
+            return Err(Error::InvalidPrice);
+        }
+
+        if auction.end_time <= auction.start_time || auction.end_time <= env.ledger().timestamp() {
+            return Err(Error::ListingExpired);
+        }
+
+        // SECURITY: Keep the decay within range at every point in time
+        (auction.start_price - auction.floor_price)
+            .checked_mul((auction.end_time - auction.start_time) as i128)
+            .ok_or(Error::InvalidPrice)?;
+
+        let listing = Self::create_listing(&env, seller, token, amount, auction.start_price, Some(auction.expires_at()))?;
+        dutch::write(&env, listing.id, &auction);
+        listing_index::sync(&env, &listing);
+
+        events::dutch_auction_listed(
+            &env,
+            listing.id,
+            auction.start_price,
+            auction.floor_price,
+            auction.start_time,
+            auction.end_time,
+        );
+
+        Ok(listing.id)
+    }
+
+    /// Internal helper to store a listing and keep the listing indexes in step
+    fn save_listing(env: &Env, listing: &Listing) {
+        storage::set_persistent(env, &DataKey::Listing(listing.id), listing);
+        listing_index::sync(env, listing);
+    }
+
+    /// Internal helper to get the price per token a listing sells at now
+    fn current_price_of(env: &Env, listing: &Listing) -> i128 {
+        match dutch::read(env, listing.id) {
+            Some(auction) => auction.price_at(env.ledger().timestamp()),
+            None => listing.price_per_token,
+        }
+    }
+
+    /// Internal helper to take a seller's tokens into custody and store a new listing
+    fn create_listing(
+        env: &Env,
+        seller: Address,
+        token: Address,
+        amount: i128,
+        price_per_token: i128,
+        expires_at: Option<u64>,
+    ) -> Result<Listing, Error> {
         // Validations
         if amount <= 0 {
             return Err(Error::InvalidListingAmount);
@@ -127,8 +240,15 @@ impl MarketplaceContract {
             return Err(Error::InvalidPrice);
         }
 
+        if expires_at.is_some_and(|expires_at| expires_at <= env.ledger().timestamp()) {
+            return Err(Error::ListingExpired);
+        }
+
+        // Bind the listing to the current settlement asset
+        let payment_token = configured_payment_token(env).ok_or(Error::NotInitialized)?;
+
         // Check seller has enough tokens
-        let token_client = token::TokenClient::new(&env, &token);
+        let token_client = token::TokenClient::new(env, &token);
         let seller_balance = token_client.balance(&seller);
 
         if seller_balance < amount {
@@ -139,28 +259,33 @@ impl MarketplaceContract {
         token_client.transfer(&seller, &env.current_contract_address(), &amount);
 
         // Create listing
-        let listing_id = storage::get_and_increment_counter(&env, DataKey::ListingCounter);
+        let listing_id = storage::get_and_increment_counter(env, DataKey::ListingCounter);
 
         let listing = Listing {
             id: listing_id,
             seller: seller.clone(),
             token: token.clone(),
+            payment_token,
             amount,
             price_per_token,
             status: ListingStatus::Active,
             created_at: env.ledger().timestamp(),
+            updated_at: env.ledger().timestamp(),
+            expires_at,
         };
 
         // Store listing
-        storage::set_persistent(&env, &DataKey::Listing(listing_id), &listing);
+        Self::save_listing(env, &listing);
 
         // Emit event
-        events::listing_created(&env, listing_id, seller, token, amount, price_per_token);
+        events::listing_created(env, listing_id, seller, token, amount, price_per_token);
 
-        Ok(listing_id)
+        Ok(listing)
     }
 
     /// Buy tokens from a listing
+    /// Payment and delivery settle atomically: the buyer's payment moves to
+    /// the seller and the tokens move from custody to the buyer in one call
     pub fn buy_tokens(
         env: Env,
         buyer: Address,
@@ -176,7 +301,7 @@ impl MarketplaceContract {
         }
 
         // Get listing
-        let mut listing: Listing = storage::get_persistent(&env, &DataKey::Listing(listing_id))
+        let mut listing = migration::read_listing(&env, listing_id)
             .ok_or(Error::ListingNotFound)?;
 
         // Check listing is active
@@ -184,135 +309,224 @@ impl MarketplaceContract {
             return Err(Error::ListingCancelled);
         }
 
+        if listing.is_expired(env.ledger().timestamp()) {
+            return Err(Error::ListingExpired);
+        }
+
+        // SECURITY: Trading with yourself would print fake trades into the price history
+        if buyer == listing.seller {
+            return Err(Error::SelfTrade);
+        }
+
         // Check amount available
         if amount > listing.amount {
             return Err(Error::InvalidListingAmount);
         }
 
-        // Calculate total price with checked arithmetic
-        let total_price = amount.checked_mul(listing.price_per_token)
-            .ok_or(Error::InvalidAmount)?;
+        // SECURITY: Settle only in the asset bound to the listing
+        if usdc_token != listing.payment_token {
+            return Err(Error::SettlementTokenMismatch);
+        }
 
-        // Get escrow and registry contracts
-        let _escrow_contract: Address = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, "escrow"))
-            .unwrap();
+        // Calculate total price with checked arithmetic, at the current
+        // point of a Dutch auction
+        let total_price = amount.checked_mul(Self::current_price_of(&env, &listing))
+            .ok_or(Error::InvalidAmount)?;
 
-        let _registry_contract: Address = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, "registry"))
-            .unwrap();
+        let quote = fees::quote(&env, &listing.token, total_price)?;
 
-        // Verify buyer has sufficient USDC allowance for this contract
-        let usdc_client = token::TokenClient::new(&env, &usdc_token);
-        let buyer_allowance = usdc_client.allowance(&buyer, &env.current_contract_address());
-        if buyer_allowance < total_price {
+        // Verify buyer can pay price and fee and has approved this contract
+        let payment_client = token::TokenClient::new(&env, &listing.payment_token);
+        if payment_client.balance(&buyer) < quote.buyer_total {
             return Err(Error::InsufficientBalance);
         }
+        if payment_client.allowance(&buyer, &env.current_contract_address()) < quote.buyer_total {
+            return Err(Error::InsufficientAllowance);
+        }
 
         // ===== EFFECTS =====
         // Update listing state BEFORE external calls
         let new_listing_amount = listing.amount.checked_sub(amount)
             .ok_or(Error::InvalidAmount)?;
         listing.amount = new_listing_amount;
+        Self::complete_if_sold_out(&env, &mut listing);
+        listing.updated_at = env.ledger().timestamp();
 
-        if listing.amount == 0 {
-            listing.status = ListingStatus::Completed;
+        Self::save_listing(&env, &listing);
+
+        // Record trade
+        Self::record_trade(&env, &listing.token, Some(listing_id), None, &buyer, &listing.seller, amount, &quote);
+
+        // ===== INTERACTIONS =====
+        // Any failing transfer reverts the whole purchase
+
+        // 1. Payment from buyer to seller, fees to the fee recipient
+        if quote.seller_proceeds > 0 {
+            payment_client.transfer_from(
+                &env.current_contract_address(),
+                &buyer,
+                &listing.seller,
+                &quote.seller_proceeds,
+            );
+        }
+        let total_fees = quote.buyer_fee + quote.seller_fee;
+        if total_fees > 0 {
+            let recipient = fees::recipient(&env).ok_or(Error::NotInitialized)?;
+            payment_client.transfer_from(&env.current_contract_address(), &buyer, &recipient, &total_fees);
         }
 
-        storage::set_persistent(&env, &DataKey::Listing(listing_id), &listing);
+        // 2. Delivery of property tokens from marketplace custody to buyer
+        let token_client = token::TokenClient::new(&env, &listing.token);
+        token_client.transfer(&env.current_contract_address(), &buyer, &amount);
 
-        // Record trade
+        // 3. Sync ownership with registry
+        Self::sync_ownership_with_registry(&env, &listing.token, Vec::from_array(&env, [buyer.clone(), listing.seller.clone()]));
+
+        // Emit event
+        events::tokens_purchased(
+            &env,
+            listing_id,
+            buyer,
+            listing.seller,
+            amount,
+            total_price,
+            quote.buyer_fee,
+            quote.seller_fee,
+        );
+
+        Ok(())
+    }
+
+    /// Internal helper to append a trade to the price history
+    #[allow(clippy::too_many_arguments)]
+    fn record_trade(
+        env: &Env,
+        token: &Address,
+        listing_id: Option<u64>,
+        bid_id: Option<u64>,
+        buyer: &Address,
+        seller: &Address,
+        amount: i128,
+        quote: &FeeQuote,
+    ) {
         let trade = Trade {
+            token: token.clone(),
             listing_id,
+            bid_id,
             buyer: buyer.clone(),
-            seller: listing.seller.clone(),
+            seller: seller.clone(),
             amount,
-            price: total_price,
+            price: quote.price,
+            buyer_fee: quote.buyer_fee,
+            seller_fee: quote.seller_fee,
             timestamp: env.ledger().timestamp(),
         };
 
-        let trade_key = DataKey::PropertyData(Symbol::new(&env, "trade"));
-        let mut trades: Vec<Trade> = storage::get_persistent(&env, &trade_key)
-            .unwrap_or(Vec::new(&env));
+        trades::record(env, &trade);
+    }
 
-        // SECURITY: Enforce maximum trade history with FIFO eviction
-        if trades.len() >= MAX_TRADE_HISTORY {
-            // Remove oldest trade (index 0) when limit reached
-            trades.remove(0);
+    /// Internal helper to complete a listing once nothing is left or reserved
+    fn complete_if_sold_out(env: &Env, listing: &mut Listing) {
+        if listing.status == ListingStatus::Active
+            && listing.amount == 0
+            && reservations::reserved(env, listing.id) == 0
+        {
+            listing.status = ListingStatus::Completed;
+            orderbook::remove(env, Side::Ask, &listing.token, listing.id);
         }
+    }
 
-        trades.push_back(trade);
-        storage::set_persistent(&env, &trade_key, &trades);
+    /// Internal helper to pay a trade from funds held by this contract:
+    /// proceeds to the seller, both fees to the fee recipient
+    fn pay_from_custody(
+        env: &Env,
+        payment_token: &Address,
+        seller: &Address,
+        quote: &FeeQuote,
+    ) -> Result<(), Error> {
+        let payment_client = token::TokenClient::new(env, payment_token);
+        if quote.seller_proceeds > 0 {
+            payment_client.transfer(&env.current_contract_address(), seller, &quote.seller_proceeds);
+        }
 
-        // ===== INTERACTIONS =====
-        // TODO: Uncomment when contract clients are available
-
-        // 1. Lock USDC funds in escrow
-        // let escrow_client = EscrowContractClient::new(&env, &escrow_contract);
-        // let escrow_id = escrow_client.lock_funds(
-        //     &buyer,
-        //     &listing.seller,
-        //     &total_price,
-        //     &listing_id,
-        //     &86400, // 24 hour timeout
-        // );
-
-        // 2. Transfer property tokens from marketplace to buyer
-        let token_client = token::TokenClient::new(&env, &listing.token);
-        token_client.transfer(&env.current_contract_address(), &buyer, &amount);
+        let total_fees = quote.buyer_fee + quote.seller_fee;
+        if total_fees > 0 {
+            let recipient = fees::recipient(env).ok_or(Error::NotInitialized)?;
+            payment_client.transfer(&env.current_contract_address(), &recipient, &total_fees);
+        }
 
-        // 3. Release USDC from escrow to seller
-        // escrow_client.release_to_seller(&escrow_id);
+        Ok(())
+    }
 
-        // 4. Sync ownership with registry
-        // Self::sync_ownership_with_registry(&env, &listing.token, &registry_contract)?;
+    /// Internal helper to sync the registry shares of the parties to a trade
+    /// A registry that refuses the update doesn't hold up the trade; the
+    /// accounts are announced so anyone can retry with `sync_registry`
+    fn sync_ownership_with_registry(env: &Env, token_contract: &Address, accounts: Vec<Address>) {
+        if let Err(property_id) = Self::update_registry(env, token_contract, &accounts) {
+            events::registry_sync_failed(env, property_id, accounts);
+        }
+    }
 
-        // Emit event
-        events::tokens_purchased(&env, listing_id, buyer, listing.seller, amount, total_price);
+    /// Internal helper to record the registry share of each of `accounts`
+    /// Tokens that aren't the registered token of a property are not tracked,
+    /// and custody addresses hold no share. Fails with the property id when
+    /// the registry refuses the update
+    fn update_registry(env: &Env, token_contract: &Address, accounts: &Vec<Address>) -> Result<(), Symbol> {
+        // Get property ID from token contract metadata
+        let token_client = PropertyTokenContractClient::new(env, token_contract);
+        let metadata = match token_client.try_metadata() {
+            Ok(Ok(metadata)) => metadata,
+            _ => return Ok(()),
+        };
 
-        Ok(())
+        // SECURITY: Only the token recorded for the property may update it
+        let registry_client = OwnershipRegistryContractClient::new(env, &Self::registry_contract(env.clone()));
+        match registry_client.try_get_property_metadata(&metadata.property_id) {
+            Ok(Ok(property)) if property.token_contract == *token_contract => {}
+            _ => return Ok(()),
+        }
+
+        let mut shares = Vec::new(env);
+        for account in accounts.iter() {
+            let percentage = if Self::is_custody(env, &token_client, &account) {
+                0
+            } else {
+                token_client.get_ownership_percentage(&account)
+            };
+            shares.push_back(OwnerInfo { owner: account, percentage });
+        }
+
+        match registry_client.try_update_ownership(&env.current_contract_address(), &metadata.property_id, &shares) {
+            Ok(Ok(())) => Ok(()),
+            _ => Err(metadata.property_id),
+        }
     }
 
-    /// Internal helper to sync ownership with registry after transfer
-    /// TODO: Uncomment when contract clients are available
-    fn sync_ownership_with_registry(
-        _env: &Env,
-        _token_contract: &Address,
-        _registry_contract: &Address,
-    ) -> Result<(), Error> {
-        // TODO: Implement when contracts are compiled
-        // Get property ID from token contract metadata
-        // let token_client = PropertyTokenContractClient::new(env, token_contract);
-        // let metadata = token_client.metadata();
-
-        // Get all current owners from token contract
-        // let owners_info = token_client.list_all_owners();
-
-        // Convert to registry format
-        // let mut registry_owners = Vec::new(env);
-        // for i in 0..owners_info.len() {
-        //     let owner_info = owners_info.get(i).unwrap();
-        //     registry_owners.push_back(OwnerInfo {
-        //         owner: owner_info.owner,
-        //         percentage: owner_info.percentage,
-        //     });
-        // }
-
-        // Update registry
-        // let registry_client = OwnershipRegistryContractClient::new(env, registry_contract);
-        // registry_client.update_ownership(&metadata.property_id, &registry_owners)?;
+    /// Internal helper to check whether `account` holds tokens for others:
+    /// this marketplace, its escrow, or an account the token flags as custodian
+    fn is_custody(env: &Env, token_client: &PropertyTokenContractClient, account: &Address) -> bool {
+        if *account == env.current_contract_address() || *account == Self::escrow_contract(env.clone()) {
+            return true;
+        }
 
-        Ok(())
+        matches!(token_client.try_is_distribution_custodian(account), Ok(Ok(true)))
+    }
+
+    /// Record the current registry share of each of `accounts` (anyone can call)
+    /// Brings the registry back in line after transfers outside the
+    /// marketplace or a sync a trade couldn't complete
+    pub fn sync_registry(env: Env, token: Address, accounts: Vec<Address>) -> Result<(), Error> {
+        if accounts.is_empty() || accounts.len() > MAX_SYNCED_OWNERS {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::update_registry(&env, &token, &accounts).map_err(|_| Error::RegistrySyncFailed)
     }
 
     /// Cancel a listing
     pub fn cancel_listing(env: Env, listing_id: u64) -> Result<(), Error> {
         // Get listing
-        let mut listing: Listing = storage::get_persistent(&env, &DataKey::Listing(listing_id))
+        let mut listing = migration::read_listing(&env, listing_id)
             .ok_or(Error::ListingNotFound)?;
 
         // Only seller can cancel
@@ -323,17 +537,22 @@ impl MarketplaceContract {
             return Err(Error::ListingCancelled);
         }
 
-        // Return tokens to seller
-        let token_client = token::TokenClient::new(&env, &listing.token);
-        token_client.transfer(
-            &env.current_contract_address(),
-            &listing.seller,
-            &listing.amount,
-        );
+        // Return unreserved tokens to seller
+        // Reserved tokens follow their reservation
+        if listing.amount > 0 {
+            let token_client = token::TokenClient::new(&env, &listing.token);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &listing.seller,
+                &listing.amount,
+            );
+        }
 
         // Update listing status
         listing.status = ListingStatus::Cancelled;
-        storage::set_persistent(&env, &DataKey::Listing(listing_id), &listing);
+        listing.updated_at = env.ledger().timestamp();
+        Self::save_listing(&env, &listing);
+        orderbook::remove(&env, Side::Ask, &listing.token, listing_id);
 
         // Emit event
         events::listing_cancelled(&env, listing_id, listing.seller);
@@ -341,331 +560,3464 @@ impl MarketplaceContract {
         Ok(())
     }
 
-    // ========== VIEW FUNCTIONS ==========
+    /// Change the price of an active listing and/or add or withdraw tokens
+    /// A positive `amount_change` moves more tokens into custody, a negative
+    /// one returns unreserved tokens to the seller. A new price or added
+    /// tokens go to the back of the book and fill any crossing bids
+    pub fn update_listing(
+        env: Env,
+        listing_id: u64,
+        price_per_token: Option<i128>,
+        amount_change: i128,
+    ) -> Result<(), Error> {
+        // ===== CHECKS =====
+        let mut listing = migration::read_listing(&env, listing_id)
+            .ok_or(Error::ListingNotFound)?;
 
-    /// Get listing details
-    pub fn get_listing(env: Env, listing_id: u64) -> Result<Listing, Error> {
-        storage::get_persistent(&env, &DataKey::Listing(listing_id))
-            .ok_or(Error::ListingNotFound)
-    }
+        // Only seller can update
+        listing.seller.require_auth();
 
-    /// Get all active listings for a property token
-    pub fn get_listings(env: Env, token: Address) -> Vec<Listing> {
-        // This is a simplified implementation
-        // In production, you'd need to maintain an index of listings by token
-        let mut listings = Vec::new(&env);
+        if listing.status != ListingStatus::Active {
+            return Err(Error::ListingCancelled);
+        }
 
-        // Get listing counter to know how many listings exist
-        let counter: u64 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::ListingCounter)
-            .unwrap_or(0);
+        if listing.is_expired(env.ledger().timestamp()) {
+            return Err(Error::ListingExpired);
+        }
 
-        // Iterate through all listings (not efficient, but works for demo)
-        for i in 0..counter {
-            if let Some(listing) = storage::get_persistent::<Listing>(&env, &DataKey::Listing(i)) {
-                if listing.token == token && listing.status == ListingStatus::Active {
-                    listings.push_back(listing);
-                }
-            }
+        if price_per_token.is_none() && amount_change == 0 {
+            return Err(Error::InvalidAmount);
         }
 
-        listings
-    }
+        // Dutch auctions are priced by their schedule
+        if price_per_token.is_some_and(|price| price <= 0)
+            || (price_per_token.is_some() && dutch::read(&env, listing_id).is_some())
+        {
+            return Err(Error::InvalidPrice);
+        }
 
-    /// Get price history for a property token
-    pub fn get_price_history(env: Env, _token: Address) -> Vec<Trade> {
-        // Simplified: return all trades
-        let trade_key = DataKey::PropertyData(Symbol::new(&env, "trade"));
-        storage::get_persistent(&env, &trade_key).unwrap_or(Vec::new(&env))
-    }
+        let new_amount = listing.amount.checked_add(amount_change)
+            .ok_or(Error::InvalidAmount)?;
+        if new_amount < 0 {
+            return Err(Error::InvalidListingAmount);
+        }
 
-    /// Calculate total market cap (sum of all active listings value)
-    pub fn calculate_market_cap(env: Env) -> i128 {
-        let counter: u64 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::ListingCounter)
-            .unwrap_or(0);
+        // A listing with nothing left to sell should be cancelled instead
+        if new_amount == 0 && reservations::reserved(&env, listing_id) == 0 {
+            return Err(Error::InvalidListingAmount);
+        }
 
-        let mut total_value: i128 = 0;
+        let token_client = token::TokenClient::new(&env, &listing.token);
+        if amount_change > 0 && token_client.balance(&listing.seller) < amount_change {
+            return Err(Error::InsufficientBalance);
+        }
 
-        for i in 0..counter {
-            if let Some(listing) = storage::get_persistent::<Listing>(&env, &DataKey::Listing(i)) {
-                if listing.status == ListingStatus::Active {
-                    // Use checked arithmetic to prevent overflow
-                    let listing_value = listing.amount.checked_mul(listing.price_per_token)
-                        .unwrap_or(0);
-                    total_value = total_value.checked_add(listing_value)
-                        .unwrap_or(total_value);
-                }
-            }
+        // ===== EFFECTS =====
+        let old_price = listing.price_per_token;
+        if let Some(price) = price_per_token {
+            listing.price_per_token = price;
         }
+        listing.amount = new_amount;
+        listing.updated_at = env.ledger().timestamp();
+        Self::save_listing(&env, &listing);
 
-        total_value
-    }
+        // ===== INTERACTIONS =====
+        if amount_change > 0 {
+            token_client.transfer(&listing.seller, &env.current_contract_address(), &amount_change);
+        } else if amount_change < 0 {
+            token_client.transfer(&env.current_contract_address(), &listing.seller, &-amount_change);
+        }
 
-    /// Get contract admin
-    pub fn admin(env: Env) -> Address {
-        storage::get_admin(&env)
-    }
+        if price_per_token.is_some() {
+            events::listing_repriced(&env, listing_id, old_price, listing.price_per_token);
+        }
+        if amount_change != 0 {
+            events::listing_amount_changed(&env, listing_id, listing.seller.clone(), amount_change, new_amount);
+        }
 
-    /// Get escrow contract address
-    pub fn escrow_contract(env: Env) -> Address {
-        env.storage()
-            .instance()
-            .get(&Symbol::new(&env, "escrow"))
-            .unwrap()
-    }
+        if price_per_token.is_some() || amount_change > 0 {
+            orderbook::remove(&env, Side::Ask, &listing.token, listing_id);
+            Self::post_listing(&env, &mut listing)?;
+        }
 
-    /// Get registry contract address
-    pub fn registry_contract(env: Env) -> Address {
-        env.storage()
-            .instance()
-            .get(&Symbol::new(&env, "registry"))
-            .unwrap()
+        Ok(())
     }
 
-    // ========== SOROSWAP INTEGRATION ==========
+    /// Close a listing past its expiry and return its tokens to the seller
+    /// Anyone can call this once the listing has expired
+    pub fn expire_listing(env: Env, listing_id: u64) -> Result<(), Error> {
+        let mut listing = migration::read_listing(&env, listing_id)
+            .ok_or(Error::ListingNotFound)?;
 
-    /// Swap property tokens for USDC using Soroswap DEX
-    /// Allows sellers to instantly convert tokens to USDC without listing
-    pub fn swap_tokens_for_usdc(
-        env: Env,
-        seller: Address,
-        property_token: Address,
-        usdc_token: Address,
-        amount_in: i128,
-        min_usdc_out: i128,
-    ) -> Result<i128, Error> {
-        seller.require_auth();
+        if listing.status != ListingStatus::Active {
+            return Err(Error::ListingCancelled);
+        }
 
-        swap::swap_property_token_for_usdc(
-            &env,
-            &seller,
-            &property_token,
-            &usdc_token,
-            amount_in,
-            min_usdc_out,
-        )
-    }
+        if !listing.is_expired(env.ledger().timestamp()) {
+            return Err(Error::ListingNotExpired);
+        }
 
-    /// Get quote for swapping property tokens to USDC
-    /// Useful for UI price display before executing swap
-    pub fn get_swap_quote(
-        env: Env,
-        property_token: Address,
-        usdc_token: Address,
-        amount_in: i128,
-    ) -> Result<i128, Error> {
-        swap::get_swap_quote(&env, &property_token, &usdc_token, amount_in)
-    }
+        // Reserved tokens follow their reservation
+        let returned = listing.amount;
+        listing.amount = 0;
+        listing.status = ListingStatus::Expired;
+        listing.updated_at = env.ledger().timestamp();
+        Self::save_listing(&env, &listing);
+        orderbook::remove(&env, Side::Ask, &listing.token, listing_id);
+
+        if returned > 0 {
+            let token_client = token::TokenClient::new(&env, &listing.token);
+            token_client.transfer(&env.current_contract_address(), &listing.seller, &returned);
+        }
 
-    /// Swap property tokens for USDC via XLM (when no direct pool exists)
-    pub fn swap_tokens_for_usdc_via_xlm(
-        env: Env,
-        seller: Address,
-        property_token: Address,
-        xlm_token: Address,
-        usdc_token: Address,
-        amount_in: i128,
-        min_usdc_out: i128,
-    ) -> Result<i128, Error> {
-        seller.require_auth();
+        events::listing_expired(&env, listing_id, listing.seller, returned);
 
-        swap::swap_property_token_for_usdc_via_xlm(
-            &env,
-            &seller,
-            &property_token,
-            &xlm_token,
-            &usdc_token,
-            amount_in,
-            min_usdc_out,
-        )
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod test {
+    // ========== RESERVATIONS ==========
+
+    /// Reserve tokens from a listing and lock the payment in escrow
+    /// Reserved tokens can't be bought by anyone else until the reservation
+    /// is confirmed or refunded
+    pub fn reserve_tokens(
+        env: Env,
+        buyer: Address,
+        listing_id: u64,
+        amount: i128,
+    ) -> Result<u64, Error> {
+        buyer.require_auth();
+
+        // ===== CHECKS =====
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut listing = migration::read_listing(&env, listing_id)
+            .ok_or(Error::ListingNotFound)?;
+
+        if listing.status != ListingStatus::Active {
+            return Err(Error::ListingCancelled);
+        }
+
+        if listing.is_expired(env.ledger().timestamp()) {
+            return Err(Error::ListingExpired);
+        }
+
+        // SECURITY: Trading with yourself would print fake trades into the price history
+        if buyer == listing.seller {
+            return Err(Error::SelfTrade);
+        }
+
+        if amount > listing.amount {
+            return Err(Error::InvalidListingAmount);
+        }
+
+        let escrow_client = EscrowContractClient::new(&env, &Self::escrow_contract(env.clone()));
+
+        // SECURITY: Escrow must hold the asset bound to the listing
+        if escrow_client.usdc_token() != listing.payment_token {
+            return Err(Error::SettlementTokenMismatch);
+        }
+
+        let total_price = amount.checked_mul(Self::current_price_of(&env, &listing))
+            .ok_or(Error::InvalidAmount)?;
+        let quote = fees::quote(&env, &listing.token, total_price)?;
+
+        let payment_client = token::TokenClient::new(&env, &listing.payment_token);
+        if payment_client.balance(&buyer) < quote.buyer_total {
+            return Err(Error::InsufficientBalance);
+        }
+
+        // ===== EFFECTS =====
+        // Move the tokens from the listing to the reservation
+        listing.amount = listing.amount.checked_sub(amount)
+            .ok_or(Error::InvalidAmount)?;
+        listing.updated_at = env.ledger().timestamp();
+        Self::save_listing(&env, &listing);
+
+        let reserved = reservations::reserved(&env, listing_id).checked_add(amount)
+            .ok_or(Error::InvalidAmount)?;
+        reservations::set_reserved(&env, listing_id, reserved);
+
+        // ===== INTERACTIONS =====
+        // Lock the buyer's payment in escrow until confirmed or timed out
+        // Escrow releases to this contract, which splits proceeds and fees
+        let escrow_id = escrow_client.lock_funds(
+            &buyer,
+            &env.current_contract_address(),
+            &quote.buyer_total,
+            &listing_id,
+            &reservations::RESERVATION_TIMEOUT,
+        );
+
+        let now = env.ledger().timestamp();
+        let reservation = Reservation {
+            id: reservations::next_id(&env),
+            listing_id,
+            buyer: buyer.clone(),
+            amount,
+            price: total_price,
+            buyer_fee: quote.buyer_fee,
+            seller_fee: quote.seller_fee,
+            escrow_id,
+            status: ReservationStatus::Pending,
+            created_at: now,
+            expires_at: now + reservations::RESERVATION_TIMEOUT,
+        };
+        reservations::write(&env, &reservation);
+
+        events::tokens_reserved(&env, listing_id, reservation.id, buyer, amount, total_price);
+
+        Ok(reservation.id)
+    }
+
+    /// Confirm a reservation: pay the seller from escrow and deliver the tokens
+    /// (compliance officer only, before the reservation expires)
+    pub fn confirm_reservation(env: Env, caller: Address, reservation_id: u64) -> Result<(), Error> {
+        access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        // ===== CHECKS =====
+        let mut reservation = reservations::read(&env, reservation_id)
+            .ok_or(Error::EscrowNotFound)?;
+
+        if reservation.status != ReservationStatus::Pending {
+            return Err(Error::EscrowNotLocked);
+        }
+
+        if env.ledger().timestamp() >= reservation.expires_at {
+            return Err(Error::ListingExpired);
+        }
+
+        let mut listing = migration::read_listing(&env, reservation.listing_id)
+            .ok_or(Error::ListingNotFound)?;
+
+        // ===== EFFECTS =====
+        reservation.status = ReservationStatus::Confirmed;
+        reservations::write(&env, &reservation);
+
+        let reserved = reservations::reserved(&env, listing.id) - reservation.amount;
+        reservations::set_reserved(&env, listing.id, reserved);
+
+        Self::complete_if_sold_out(&env, &mut listing);
+        listing.updated_at = env.ledger().timestamp();
+        Self::save_listing(&env, &listing);
+
+        let quote = reservation.fee_quote();
+        Self::record_trade(
+            &env,
+            &listing.token,
+            Some(listing.id),
+            None,
+            &reservation.buyer,
+            &listing.seller,
+            reservation.amount,
+            &quote,
+        );
+
+        // ===== INTERACTIONS =====
+        // 1. Release the escrowed payment, then pay the seller and the fees
+        let escrow_client = EscrowContractClient::new(&env, &Self::escrow_contract(env.clone()));
+        escrow_client.release_to_seller(&env.current_contract_address(), &reservation.escrow_id);
+
+        Self::pay_from_custody(&env, &listing.payment_token, &listing.seller, &quote)?;
+
+        // 2. Deliver the reserved tokens to the buyer
+        let token_client = token::TokenClient::new(&env, &listing.token);
+        token_client.transfer(&env.current_contract_address(), &reservation.buyer, &reservation.amount);
+
+        // 3. Sync ownership with registry
+        Self::sync_ownership_with_registry(
+            &env,
+            &listing.token,
+            Vec::from_array(&env, [reservation.buyer.clone(), listing.seller.clone()]),
+        );
+
+        events::reservation_confirmed(&env, reservation_id, caller);
+        events::tokens_purchased(
+            &env,
+            listing.id,
+            reservation.buyer,
+            listing.seller,
+            reservation.amount,
+            quote.price,
+            quote.buyer_fee,
+            quote.seller_fee,
+        );
+
+        Ok(())
+    }
+
+    /// Unwind a reservation: refund the buyer and return the tokens to the listing
+    /// Before expiry only a compliance officer can refund; after it anyone can
+    pub fn refund_reservation(env: Env, caller: Address, reservation_id: u64) -> Result<(), Error> {
+        // ===== CHECKS =====
+        let mut reservation = reservations::read(&env, reservation_id)
+            .ok_or(Error::EscrowNotFound)?;
+
+        if env.ledger().timestamp() < reservation.expires_at {
+            access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
+        }
+
+        if reservation.status != ReservationStatus::Pending {
+            return Err(Error::EscrowNotLocked);
+        }
+
+        let mut listing = migration::read_listing(&env, reservation.listing_id)
+            .ok_or(Error::ListingNotFound)?;
+
+        let escrow_client = EscrowContractClient::new(&env, &Self::escrow_contract(env.clone()));
+        let escrow_status = escrow_client.get_escrow_status(&reservation.escrow_id);
+        if escrow_status == EscrowStatus::Released {
+            return Err(Error::EscrowAlreadyReleased);
+        }
+
+        // ===== EFFECTS =====
+        reservation.status = ReservationStatus::Refunded;
+        reservations::write(&env, &reservation);
+
+        let reserved = reservations::reserved(&env, listing.id) - reservation.amount;
+        reservations::set_reserved(&env, listing.id, reserved);
+
+        // Tokens go back on sale, or to the seller if the listing was cancelled
+        let back_on_sale = listing.status == ListingStatus::Active;
+        if back_on_sale {
+            listing.amount = listing.amount.checked_add(reservation.amount)
+                .ok_or(Error::InvalidAmount)?;
+            listing.updated_at = env.ledger().timestamp();
+            Self::save_listing(&env, &listing);
+        }
+
+        // ===== INTERACTIONS =====
+        // The buyer may already have reclaimed the payment from escrow
+        if escrow_status == EscrowStatus::Locked {
+            escrow_client.refund_to_buyer(&env.current_contract_address(), &reservation.escrow_id);
+        }
+
+        if !back_on_sale {
+            let token_client = token::TokenClient::new(&env, &listing.token);
+            token_client.transfer(&env.current_contract_address(), &listing.seller, &reservation.amount);
+        }
+
+        events::reservation_refunded(&env, reservation_id, reservation.buyer, reservation.amount);
+
+        Ok(())
+    }
+
+    // ========== ORDER BOOK ==========
+    // Bids share the listing errors: an unknown bid is `ListingNotFound` and
+    // one that is no longer open is `ListingCancelled`
+
+    /// Place a bid for `amount` tokens at up to `price_per_token`
+    /// The price and buyer fee are held by this contract. The bid first fills
+    /// against listings at or below its price, best price then oldest first,
+    /// at each listing's price; the rest waits on the book
+    pub fn place_bid(
+        env: Env,
+        buyer: Address,
+        token: Address,
+        amount: i128,
+        price_per_token: i128,
+    ) -> Result<u64, Error> {
+        buyer.require_auth();
+
+        // ===== CHECKS =====
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        if price_per_token <= 0 {
+            return Err(Error::InvalidPrice);
+        }
+
+        let payment_token = configured_payment_token(&env).ok_or(Error::NotInitialized)?;
+
+        let total_price = amount.checked_mul(price_per_token)
+            .ok_or(Error::InvalidAmount)?;
+        let quote = fees::quote(&env, &token, total_price)?;
+
+        let payment_client = token::TokenClient::new(&env, &payment_token);
+        if payment_client.balance(&buyer) < quote.buyer_total {
+            return Err(Error::InsufficientBalance);
+        }
+        if payment_client.allowance(&buyer, &env.current_contract_address()) < quote.buyer_total {
+            return Err(Error::InsufficientAllowance);
+        }
+
+        // ===== EFFECTS =====
+        let now = env.ledger().timestamp();
+        let mut bid = Bid {
+            id: orderbook::next_bid_id(&env),
+            buyer: buyer.clone(),
+            token: token.clone(),
+            payment_token,
+            amount,
+            price_per_token,
+            buyer_fee: quote.buyer_fee,
+            status: BidStatus::Open,
+            created_at: now,
+            updated_at: now,
+        };
+        orderbook::write_bid(&env, &bid);
+
+        // ===== INTERACTIONS =====
+        // Hold the price and buyer fee until the bid fills or is cancelled
+        payment_client.transfer_from(
+            &env.current_contract_address(),
+            &buyer,
+            &env.current_contract_address(),
+            &quote.buyer_total,
+        );
+
+        events::bid_placed(&env, bid.id, buyer, token, amount, price_per_token);
+
+        Self::post_bid(&env, &mut bid)?;
+
+        Ok(bid.id)
+    }
+
+    /// Sell `amount` tokens straight into a bid at the bid's price
+    pub fn fill_bid(env: Env, seller: Address, bid_id: u64, amount: i128) -> Result<(), Error> {
+        seller.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut bid = orderbook::read_bid(&env, bid_id).ok_or(Error::ListingNotFound)?;
+
+        if bid.status != BidStatus::Open {
+            return Err(Error::ListingCancelled);
+        }
+
+        // SECURITY: Trading with yourself would print fake trades into the price history
+        if seller == bid.buyer {
+            return Err(Error::SelfTrade);
+        }
+
+        if amount > bid.amount {
+            return Err(Error::InvalidListingAmount);
+        }
+
+        let token_client = token::TokenClient::new(&env, &bid.token);
+        if token_client.balance(&seller) < amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        let price_per_token = bid.price_per_token;
+        let quote = Self::settle_bid(&env, &mut bid, &seller, amount, price_per_token, None)?;
+        token_client.transfer(&seller, &bid.buyer, &amount);
+        events::bid_filled(
+            &env,
+            bid_id,
+            seller.clone(),
+            None,
+            amount,
+            quote.price,
+            quote.buyer_fee,
+            quote.seller_fee,
+        );
+
+        Self::sync_ownership_with_registry(&env, &bid.token, Vec::from_array(&env, [bid.buyer.clone(), seller]));
+
+        Ok(())
+    }
+
+    /// Cancel an open bid and return what it still holds to the buyer
+    pub fn cancel_bid(env: Env, bid_id: u64) -> Result<(), Error> {
+        let mut bid = orderbook::read_bid(&env, bid_id).ok_or(Error::ListingNotFound)?;
+
+        // Only the buyer can cancel
+        bid.buyer.require_auth();
+
+        if bid.status != BidStatus::Open {
+            return Err(Error::ListingCancelled);
+        }
+
+        let refund = Self::close_bid(&env, &mut bid);
+
+        let payment_client = token::TokenClient::new(&env, &bid.payment_token);
+        payment_client.transfer(&env.current_contract_address(), &bid.buyer, &refund);
+
+        events::bid_cancelled(&env, bid_id, bid.buyer, refund);
+
+        Ok(())
+    }
+
+    /// Internal helper to fill a new or repriced listing against the bids
+    /// for its token, then rest what is left on the book
+    fn post_listing(env: &Env, listing: &mut Listing) -> Result<(), Error> {
+        // Dutch auctions move with the clock and stay off the book
+        if dutch::read(env, listing.id).is_some() {
+            return Ok(());
+        }
+
+        let mut traders = Vec::new(env);
+
+        for entry in orderbook::entries(env, Side::Bid, &listing.token).iter() {
+            if listing.amount == 0 || entry.price < listing.price_per_token {
+                break;
+            }
+
+            let mut bid = orderbook::read_bid(env, entry.id).ok_or(Error::ListingNotFound)?;
+
+            // Orders settling in another asset, or from the same account, don't match
+            if bid.payment_token != listing.payment_token || bid.buyer == listing.seller {
+                continue;
+            }
+
+            let amount = listing.amount.min(bid.amount);
+
+            // A buyer who can't receive the tokens loses the bid instead of
+            // holding up the listing; its payment waits as a refund
+            let token_client = token::TokenClient::new(env, &listing.token);
+            let delivered = token_client.try_transfer(&env.current_contract_address(), &bid.buyer, &amount);
+            if !matches!(delivered, Ok(Ok(()))) {
+                let refund = Self::close_bid(env, &mut bid);
+                refunds::credit(env, &bid.buyer, &bid.payment_token, refund)?;
+                events::bid_cancelled(env, bid.id, bid.buyer, refund);
+                continue;
+            }
+            listing.amount -= amount;
+
+            // Resting bid sets the price; the tokens left custody above
+            let quote = Self::settle_bid(env, &mut bid, &listing.seller, amount, entry.price, Some(listing.id))?;
+            events::bid_filled(
+                env,
+                bid.id,
+                listing.seller.clone(),
+                Some(listing.id),
+                amount,
+                quote.price,
+                quote.buyer_fee,
+                quote.seller_fee,
+            );
+            traders.push_back(bid.buyer);
+        }
+
+        Self::complete_if_sold_out(env, listing);
+        listing.updated_at = env.ledger().timestamp();
+        Self::save_listing(env, listing);
+
+        // A listing priced out of a full book stays buyable directly
+        if listing.status == ListingStatus::Active {
+            match Self::rest_on_book(env, Side::Ask, &listing.token, listing.id, listing.price_per_token) {
+                Ok(()) | Err(Error::OrderBookFull) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        if !traders.is_empty() {
+            traders.push_back(listing.seller.clone());
+            Self::sync_ownership_with_registry(env, &listing.token, traders);
+        }
+
+        Ok(())
+    }
+
+    /// Internal helper to fill a new bid against the listings for its token,
+    /// then rest what is left on the book
+    fn post_bid(env: &Env, bid: &mut Bid) -> Result<(), Error> {
+        let now = env.ledger().timestamp();
+        let mut traders = Vec::new(env);
+
+        for entry in orderbook::entries(env, Side::Ask, &bid.token).iter() {
+            if bid.amount == 0 || entry.price > bid.price_per_token {
+                break;
+            }
+
+            let mut listing = migration::read_listing(env, entry.id)
+                .ok_or(Error::ListingNotFound)?;
+
+            // Skip listings that can't sell now, settle in another asset, or are the buyer's own
+            if listing.amount == 0
+                || listing.is_expired(now)
+                || listing.payment_token != bid.payment_token
+                || listing.seller == bid.buyer
+            {
+                continue;
+            }
+
+            let amount = bid.amount.min(listing.amount);
+            listing.amount -= amount;
+            Self::complete_if_sold_out(env, &mut listing);
+            listing.updated_at = now;
+            Self::save_listing(env, &listing);
+
+            // Resting listing sets the price; the tokens leave custody
+            let quote = Self::settle_bid(env, bid, &listing.seller, amount, entry.price, Some(listing.id))?;
+            let token_client = token::TokenClient::new(env, &bid.token);
+            token_client.transfer(&env.current_contract_address(), &bid.buyer, &amount);
+            events::tokens_purchased(
+                env,
+                listing.id,
+                bid.buyer.clone(),
+                listing.seller.clone(),
+                amount,
+                quote.price,
+                quote.buyer_fee,
+                quote.seller_fee,
+            );
+            traders.push_back(listing.seller);
+        }
+
+        if bid.status == BidStatus::Open {
+            Self::rest_on_book(env, Side::Bid, &bid.token, bid.id, bid.price_per_token)?;
+        }
+
+        if !traders.is_empty() {
+            traders.push_back(bid.buyer.clone());
+            Self::sync_ownership_with_registry(env, &bid.token, traders);
+        }
+
+        Ok(())
+    }
+
+    /// Internal helper to rest an order on the book, announcing the order it
+    /// pushes off a full side
+    fn rest_on_book(env: &Env, side: Side, token: &Address, id: u64, price: i128) -> Result<(), Error> {
+        match orderbook::insert(env, side, token, id, price)? {
+            Some(listing_id) if side == Side::Ask => {
+                let listing = migration::read_listing(env, listing_id).ok_or(Error::ListingNotFound)?;
+                events::listing_unbooked(env, listing_id, listing.seller);
+            }
+            Some(bid_id) => {
+                let bid = orderbook::read_bid(env, bid_id).ok_or(Error::ListingNotFound)?;
+                events::bid_unbooked(env, bid_id, bid.buyer);
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// Internal helper to close an open bid, returns what it still holds
+    fn close_bid(env: &Env, bid: &mut Bid) -> i128 {
+        let held = bid.escrowed();
+        bid.status = BidStatus::Cancelled;
+        bid.updated_at = env.ledger().timestamp();
+        orderbook::write_bid(env, bid);
+        orderbook::remove(env, Side::Bid, &bid.token, bid.id);
+        held
+    }
+
+    /// Internal helper to settle the payment for `amount` of a bid at `price_per_token`
+    /// Payment comes from what the bid holds; paying less than the bid price
+    /// returns the difference, and the buyer fee saved on it, to the buyer.
+    /// The caller delivers the tokens
+    fn settle_bid(
+        env: &Env,
+        bid: &mut Bid,
+        seller: &Address,
+        amount: i128,
+        price_per_token: i128,
+        listing_id: Option<u64>,
+    ) -> Result<FeeQuote, Error> {
+        let total_price = amount.checked_mul(price_per_token)
+            .ok_or(Error::InvalidAmount)?;
+        let held = amount.checked_mul(bid.price_per_token)
+            .ok_or(Error::InvalidAmount)?;
+
+        // Both fees apply to the execution price; the buyer fee is capped at
+        // what the bid holds for this amount
+        let held_fee = bid.fee_share(amount)?;
+        let mut quote = fees::quote(env, &bid.token, total_price)?;
+        if quote.buyer_fee > held_fee {
+            quote.buyer_fee = held_fee;
+            quote.buyer_total = total_price + held_fee;
+        }
+        let surplus = held + held_fee - quote.buyer_total;
+
+        // ===== EFFECTS =====
+        bid.amount -= amount;
+        bid.buyer_fee -= held_fee;
+        if bid.amount == 0 {
+            bid.status = BidStatus::Filled;
+            orderbook::remove(env, Side::Bid, &bid.token, bid.id);
+        }
+        bid.updated_at = env.ledger().timestamp();
+        orderbook::write_bid(env, bid);
+
+        Self::record_trade(env, &bid.token, listing_id, Some(bid.id), &bid.buyer, seller, amount, &quote);
+
+        // ===== INTERACTIONS =====
+        Self::pay_from_custody(env, &bid.payment_token, seller, &quote)?;
+        if surplus > 0 {
+            let payment_client = token::TokenClient::new(env, &bid.payment_token);
+            payment_client.transfer(&env.current_contract_address(), &bid.buyer, &surplus);
+        }
+
+        Ok(quote)
+    }
+
+    // ========== AUCTIONS ==========
+    // Auctions share the listing errors: an unknown auction is
+    // `ListingNotFound`, one that is over is `ListingCancelled`, and a bid
+    // below the minimum is `InvalidPrice`
+
+    /// Put a lot of `amount` tokens up for auction until `end_time`
+    /// Bids are for the whole lot; `min_increment` is how much each bid must
+    /// beat the previous one by
+    pub fn create_auction(
+        env: Env,
+        seller: Address,
+        token: Address,
+        amount: i128,
+        reserve_price: i128,
+        min_increment: i128,
+        end_time: u64,
+    ) -> Result<u64, Error> {
+        seller.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidListingAmount);
+        }
+
+        if reserve_price <= 0 || min_increment <= 0 {
+            return Err(Error::InvalidPrice);
+        }
+
+        let now = env.ledger().timestamp();
+        if end_time <= now {
+            return Err(Error::ListingExpired);
+        }
+
+        let payment_token = configured_payment_token(&env).ok_or(Error::NotInitialized)?;
+
+        let token_client = token::TokenClient::new(&env, &token);
+        if token_client.balance(&seller) < amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        // Hold the lot until the auction settles
+        token_client.transfer(&seller, &env.current_contract_address(), &amount);
+
+        let auction = Auction {
+            id: auctions::next_id(&env),
+            seller: seller.clone(),
+            token: token.clone(),
+            payment_token,
+            amount,
+            reserve_price,
+            min_increment,
+            high_bid: 0,
+            high_bidder: None,
+            buyer_fee: 0,
+            bid_count: 0,
+            status: AuctionStatus::Active,
+            created_at: now,
+            end_time,
+        };
+        auctions::write(&env, &auction);
+
+        events::auction_created(&env, auction.id, seller, token, amount, reserve_price, end_time);
+
+        Ok(auction.id)
+    }
+
+    /// Bid `bid` for the whole lot of an auction
+    /// The bid and its buyer fee are held until the auction settles. An outbid
+    /// bidder isn't paid back here: their bid and fee are credited as a
+    /// refund, withdrawn with `claim_refund`. A bid in the last
+    /// `ANTI_SNIPING_WINDOW` seconds moves the end to that long after the bid
+    pub fn bid_auction(env: Env, bidder: Address, auction_id: u64, bid: i128) -> Result<(), Error> {
+        bidder.require_auth();
+
+        // ===== CHECKS =====
+        let mut auction = auctions::read(&env, auction_id).ok_or(Error::ListingNotFound)?;
+
+        if auction.status != AuctionStatus::Active {
+            return Err(Error::ListingCancelled);
+        }
+
+        let now = env.ledger().timestamp();
+        if now >= auction.end_time {
+            return Err(Error::ListingExpired);
+        }
+
+        if bid < auction.min_bid() {
+            return Err(Error::InvalidPrice);
+        }
+
+        let quote = fees::quote(&env, &auction.token, bid)?;
+
+        let payment_client = token::TokenClient::new(&env, &auction.payment_token);
+        if payment_client.balance(&bidder) < quote.buyer_total {
+            return Err(Error::InsufficientBalance);
+        }
+        if payment_client.allowance(&bidder, &env.current_contract_address()) < quote.buyer_total {
+            return Err(Error::InsufficientAllowance);
+        }
+
+        // ===== EFFECTS =====
+        // The outbid bid is credited, so a bidder who can't receive payment
+        // can't hold up the auction
+        let outbid = auction.high_bidder.clone()
+            .map(|previous| (previous, auction.high_bid + auction.buyer_fee));
+        if let Some((previous, refund)) = &outbid {
+            refunds::credit(&env, previous, &auction.payment_token, *refund)?;
+        }
+
+        auction.high_bid = bid;
+        auction.high_bidder = Some(bidder.clone());
+        auction.buyer_fee = quote.buyer_fee;
+        auction.bid_count += 1;
+
+        // SECURITY: Late bids extend the auction so others can answer them
+        let extended = auction.end_time - now < auctions::ANTI_SNIPING_WINDOW;
+        if extended {
+            auction.end_time = now + auctions::ANTI_SNIPING_WINDOW;
+        }
+        auctions::write(&env, &auction);
+
+        // ===== INTERACTIONS =====
+        payment_client.transfer_from(
+            &env.current_contract_address(),
+            &bidder,
+            &env.current_contract_address(),
+            &quote.buyer_total,
+        );
+        events::auction_bid(&env, auction_id, bidder, bid);
+        if let Some((previous, refund)) = outbid {
+            events::auction_outbid(&env, auction_id, previous, refund);
+        }
+
+        if extended {
+            events::auction_extended(&env, auction_id, auction.end_time);
+        }
+
+        Ok(())
+    }
+
+    /// Settle an ended auction: the lot goes to the high bidder and the
+    /// payment to the seller, or the lot back to the seller if no bid met
+    /// the reserve or the high bidder can't receive it, in which case the
+    /// bid becomes a refund to claim. Anyone can call this once the auction
+    /// has ended
+    pub fn settle_auction(env: Env, auction_id: u64) -> Result<(), Error> {
+        // ===== CHECKS =====
+        let mut auction = auctions::read(&env, auction_id).ok_or(Error::ListingNotFound)?;
+
+        if auction.status != AuctionStatus::Active {
+            return Err(Error::ListingCancelled);
+        }
+
+        if env.ledger().timestamp() < auction.end_time {
+            return Err(Error::AuctionNotEnded);
+        }
+
+        let token_client = token::TokenClient::new(&env, &auction.token);
+
+        let Some(winner) = auction.high_bidder.clone() else {
+            auction.status = AuctionStatus::Unsold;
+            auctions::write(&env, &auction);
+
+            token_client.transfer(&env.current_contract_address(), &auction.seller, &auction.amount);
+            events::auction_settled(&env, auction_id, None, 0, 0, 0);
+            return Ok(());
+        };
+
+        // A winner who can't receive the lot loses it instead of holding up
+        // the seller; the bid and its fee wait as a refund
+        let delivered = token_client.try_transfer(&env.current_contract_address(), &winner, &auction.amount);
+        if !matches!(delivered, Ok(Ok(()))) {
+            auction.status = AuctionStatus::Unsold;
+            auctions::write(&env, &auction);
+
+            refunds::credit(&env, &winner, &auction.payment_token, auction.high_bid + auction.buyer_fee)?;
+            token_client.transfer(&env.current_contract_address(), &auction.seller, &auction.amount);
+            events::auction_settled(&env, auction_id, None, 0, 0, 0);
+            return Ok(());
+        }
+
+        // Buyer fee was fixed with the winning bid, the seller fee applies now
+        let seller_fee = fees::quote(&env, &auction.token, auction.high_bid)?.seller_fee;
+        let quote = FeeQuote {
+            price: auction.high_bid,
+            buyer_fee: auction.buyer_fee,
+            seller_fee,
+            buyer_total: auction.high_bid + auction.buyer_fee,
+            seller_proceeds: auction.high_bid - seller_fee,
+        };
+
+        // ===== EFFECTS =====
+        auction.status = AuctionStatus::Sold;
+        auctions::write(&env, &auction);
+
+        Self::record_trade(&env, &auction.token, None, None, &winner, &auction.seller, auction.amount, &quote);
+
+        // ===== INTERACTIONS =====
+        Self::pay_from_custody(&env, &auction.payment_token, &auction.seller, &quote)?;
+
+        Self::sync_ownership_with_registry(
+            &env,
+            &auction.token,
+            Vec::from_array(&env, [winner.clone(), auction.seller.clone()]),
+        );
+
+        events::auction_settled(
+            &env,
+            auction_id,
+            Some(winner),
+            quote.price,
+            quote.buyer_fee,
+            quote.seller_fee,
+        );
+
+        Ok(())
+    }
+
+    // ========== SETTLEMENT ==========
+
+    /// Set the asset new listings settle in (admin only)
+    /// Existing listings keep the asset they were created with
+    pub fn set_payment_token(env: Env, payment_token: Address) -> Result<(), Error> {
+        access::require_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&Symbol::new(&env, "payment"), &payment_token);
+
+        Ok(())
+    }
+
+    /// Claim every refund owed to `account` in `asset`, returns the amount paid
+    pub fn claim_refund(env: Env, account: Address, asset: Address) -> Result<i128, Error> {
+        account.require_auth();
+
+        Ok(refunds::claim(&env, &account, &asset))
+    }
+
+    /// Get the refund owed to `account` in `asset` that has not been claimed yet
+    pub fn claimable_refund(env: Env, account: Address, asset: Address) -> i128 {
+        refunds::claimable(&env, &account, &asset)
+    }
+
+    // ========== FEES ==========
+
+    /// Set the default trade fees and where they are paid (fee manager role)
+    pub fn set_fees(
+        env: Env,
+        caller: Address,
+        schedule: FeeSchedule,
+        recipient: Address,
+    ) -> Result<(), Error> {
+        access::require_role(&env, Role::FeeManager, &caller).map_err(|_| Error::NotAuthorized)?;
+        fees::validate(&schedule)?;
+
+        fees::set_default(&env, &schedule, &recipient);
+
+        events::fees_updated(
+            &env,
+            recipient,
+            schedule.buyer_fee_bps,
+            schedule.seller_fee_bps,
+            schedule.min_fee,
+        );
+
+        Ok(())
+    }
+
+    /// Override the fees for one token, or pass None to use the default (fee manager role)
+    pub fn set_token_fees(
+        env: Env,
+        caller: Address,
+        token: Address,
+        schedule: Option<FeeSchedule>,
+    ) -> Result<(), Error> {
+        access::require_role(&env, Role::FeeManager, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        // Fees need somewhere to go
+        if fees::recipient(&env).is_none() {
+            return Err(Error::NotInitialized);
+        }
+        if let Some(schedule) = &schedule {
+            fees::validate(schedule)?;
+        }
+
+        fees::set_for_token(&env, &token, &schedule);
+
+        events::token_fees_updated(
+            &env,
+            token,
+            schedule.map(|schedule| (schedule.buyer_fee_bps, schedule.seller_fee_bps, schedule.min_fee)),
+        );
+
+        Ok(())
+    }
+
+    // ========== ACCESS CONTROL ==========
+
+    /// Grant `role` to `account` (admin only)
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::grant_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Revoke `role` from `account` (admin only)
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::revoke_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Check whether `account` holds `role` (the admin holds every role)
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        access::has_role(&env, role, &account)
+    }
+
+    /// Nominate a new admin; takes effect once they call `accept_admin` (admin only)
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        access::propose_admin(&env, &new_admin);
+        Ok(())
+    }
+
+    /// Accept a pending admin nomination (pending admin only)
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        access::accept_admin(&env).map_err(|_| Error::NotAuthorized)?;
+        Ok(())
+    }
+
+    /// Get the nominated admin awaiting acceptance
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        access::pending_admin(&env)
+    }
+
+    // ========== UPGRADES ==========
+
+    /// Replace the contract code, keeping storage (admin only)
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        access::upgrade(&env, &new_wasm_hash);
+        Ok(())
+    }
+
+    /// Convert up to `limit` listings from `cursor` to the current layout (admin only)
+    /// Once every listing is converted, each call moves up to `limit` trades of
+    /// the old history to the per-token ledgers
+    /// Returns the cursor to continue from, or None once the schema version is recorded
+    /// Old listings settle in the payment token, so it must be set first
+    pub fn migrate(env: Env, cursor: u64, limit: u32) -> Result<Option<u64>, Error> {
+        access::require_admin(&env);
+
+        if limit == 0 || limit > migration::MAX_MIGRATION_BATCH {
+            return Err(Error::InvalidAmount);
+        }
+        if configured_payment_token(&env).is_none() {
+            return Err(Error::NotInitialized);
+        }
+
+        let counter: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ListingCounter)
+            .unwrap_or(0);
+        let end = cursor.saturating_add(limit as u64).min(counter);
+
+        migration::migrate_listings(&env, cursor, end);
+
+        if end < counter {
+            return Ok(Some(end));
+        }
+
+        if migration::migrate_trades(&env, limit)? {
+            return Ok(Some(end));
+        }
+
+        migration::finish(&env);
+        Ok(None)
+    }
+
+    /// Storage layout version of this contract
+    pub fn schema_version(env: Env) -> u32 {
+        storage::get_schema_version(&env)
+    }
+
+    // ========== VIEW FUNCTIONS ==========
+
+    /// Get listing details
+    pub fn get_listing(env: Env, listing_id: u64) -> Result<Listing, Error> {
+        migration::read_listing(&env, listing_id).ok_or(Error::ListingNotFound)
+    }
+
+    /// Get reservation details
+    pub fn get_reservation(env: Env, reservation_id: u64) -> Result<Reservation, Error> {
+        reservations::read(&env, reservation_id).ok_or(Error::EscrowNotFound)
+    }
+
+    /// Get bid details
+    pub fn get_bid(env: Env, bid_id: u64) -> Result<Bid, Error> {
+        orderbook::read_bid(&env, bid_id).ok_or(Error::ListingNotFound)
+    }
+
+    /// Get the price per token a listing sells at now
+    pub fn current_price(env: Env, listing_id: u64) -> Result<i128, Error> {
+        let listing = migration::read_listing(&env, listing_id)
+            .ok_or(Error::ListingNotFound)?;
+        Ok(Self::current_price_of(&env, &listing))
+    }
+
+    /// Get the price schedule of a Dutch auction listing
+    pub fn get_dutch_auction(env: Env, listing_id: u64) -> Option<DutchAuction> {
+        dutch::read(&env, listing_id)
+    }
+
+    /// Get auction details
+    pub fn get_auction(env: Env, auction_id: u64) -> Result<Auction, Error> {
+        auctions::read(&env, auction_id).ok_or(Error::ListingNotFound)
+    }
+
+    /// Get the lowest bid an auction accepts next
+    pub fn auction_min_bid(env: Env, auction_id: u64) -> Result<i128, Error> {
+        auctions::read(&env, auction_id)
+            .map(|auction| auction.min_bid())
+            .ok_or(Error::ListingNotFound)
+    }
+
+    /// Get the highest open bid price for a property token
+    pub fn best_bid(env: Env, token: Address) -> Option<i128> {
+        Self::market_depth(env, token, 1).bids.first().map(|level| level.price)
+    }
+
+    /// Get the lowest price a property token can be bought at from the book
+    pub fn best_ask(env: Env, token: Address) -> Option<i128> {
+        Self::market_depth(env, token, 1).asks.first().map(|level| level.price)
+    }
+
+    /// Get up to `levels` price levels on each side of a token's book
+    /// Asks only count tokens that can be bought now
+    pub fn market_depth(env: Env, token: Address, levels: u32) -> MarketDepth {
+        let now = env.ledger().timestamp();
+
+        let mut bids: Vec<PriceLevel> = Vec::new(&env);
+        for entry in orderbook::entries(&env, Side::Bid, &token).iter() {
+            if bids.len() >= levels && bids.last().map(|level| level.price) != Some(entry.price) {
+                break;
+            }
+            if let Some(bid) = orderbook::read_bid(&env, entry.id) {
+                orderbook::add_to_levels(&mut bids, entry.price, bid.amount);
+            }
+        }
+
+        let mut asks: Vec<PriceLevel> = Vec::new(&env);
+        for entry in orderbook::entries(&env, Side::Ask, &token).iter() {
+            if asks.len() >= levels && asks.last().map(|level| level.price) != Some(entry.price) {
+                break;
+            }
+            if let Some(listing) = migration::read_listing(&env, entry.id) {
+                if listing.amount > 0 && !listing.is_expired(now) {
+                    orderbook::add_to_levels(&mut asks, entry.price, listing.amount);
+                }
+            }
+        }
+
+        MarketDepth { bids, asks }
+    }
+
+    /// Get the amount of a listing held by pending reservations
+    pub fn reserved_amount(env: Env, listing_id: u64) -> i128 {
+        reservations::reserved(&env, listing_id)
+    }
+
+    /// List active listings of a property token, `limit` at a time starting at `cursor`
+    /// Pass the returned `next_cursor` to fetch the following page; listings
+    /// closing between calls may reorder the set, so read pages in one ledger.
+    /// Listings past their expiry are left out
+    pub fn get_listings(env: Env, token: Address, cursor: u32, limit: u32) -> ListingsPage {
+        let now = env.ledger().timestamp();
+        Self::listings_page(&env, &ListingSet::Token(token), cursor, limit, |listing| !listing.is_expired(now))
+    }
+
+    /// List active listings of a seller, `limit` at a time starting at `cursor`
+    /// Includes listings past their expiry, which the seller can close
+    pub fn get_seller_listings(env: Env, seller: Address, cursor: u32, limit: u32) -> ListingsPage {
+        Self::listings_page(&env, &ListingSet::Seller(seller), cursor, limit, |_| true)
+    }
+
+    /// Internal helper to read a page of an active listing index
+    fn listings_page(
+        env: &Env,
+        set: &ListingSet,
+        cursor: u32,
+        limit: u32,
+        include: impl Fn(&Listing) -> bool,
+    ) -> ListingsPage {
+        let count = listing_index::count(env, set);
+        let end = cursor.saturating_add(limit.min(MAX_LISTINGS_PAGE)).min(count);
+
+        let mut listings = Vec::new(env);
+        for index in cursor..end {
+            let listing = listing_index::listing_at(env, set, index)
+                .and_then(|listing_id| migration::read_listing(env, listing_id));
+            if let Some(listing) = listing.filter(|listing| include(listing)) {
+                listings.push_back(listing);
+            }
+        }
+
+        let next_cursor = if end < count { Some(end) } else { None };
+
+        ListingsPage { listings, next_cursor }
+    }
+
+    /// Get the latest trades of a property token, oldest first
+    pub fn get_price_history(env: Env, token: Address) -> Vec<Trade> {
+        let count = trades::count(&env, &token);
+        let start = count.saturating_sub(MAX_TRADES_PAGE);
+        Self::get_trades(env, token, start, MAX_TRADES_PAGE).trades
+    }
+
+    /// List trades of a property token in the order they happened, `limit`
+    /// at a time starting at `cursor`
+    /// Pass the returned `next_cursor` to fetch the following page
+    pub fn get_trades(env: Env, token: Address, cursor: u32, limit: u32) -> TradesPage {
+        let count = trades::count(&env, &token);
+        let end = cursor.saturating_add(limit.min(MAX_TRADES_PAGE)).min(count);
+
+        let mut page = Vec::new(&env);
+        for index in cursor..end {
+            if let Some(trade) = trades::read(&env, &token, index) {
+                page.push_back(trade);
+            }
+        }
+
+        let next_cursor = if end < count { Some(end) } else { None };
+
+        TradesPage { trades: page, next_cursor }
+    }
+
+    /// Get the number of trades recorded for a property token
+    pub fn trade_count(env: Env, token: Address) -> u32 {
+        trades::count(&env, &token)
+    }
+
+    /// Get the candle of `interval` holding `timestamp`, if anything traded in it
+    pub fn get_candle(env: Env, token: Address, interval: Interval, timestamp: u64) -> Option<Candle> {
+        trades::candle(&env, &token, interval, interval.bucket_start(timestamp))
+    }
+
+    /// Get the candles of `interval` in `[from, to)`, oldest first
+    /// Buckets without trades are left out, and at most 168 buckets from
+    /// `from` are read, a week of hourly candles
+    pub fn get_candles(env: Env, token: Address, interval: Interval, from: u64, to: u64) -> Vec<Candle> {
+        let mut candles = Vec::new(&env);
+        let mut start = interval.bucket_start(from);
+        for _ in 0..MAX_CANDLES {
+            if start >= to {
+                break;
+            }
+            if let Some(candle) = trades::candle(&env, &token, interval, start) {
+                candles.push_back(candle);
+            }
+            start = match start.checked_add(interval.seconds()) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        candles
+    }
+
+    /// Get the fees that apply to trades of `token`
+    pub fn fee_schedule(env: Env, token: Address) -> FeeSchedule {
+        fees::schedule_for(&env, &token)
+    }
+
+    /// Get the address trade fees are paid to
+    pub fn fee_recipient(env: Env) -> Option<Address> {
+        fees::recipient(&env)
+    }
+
+    /// Preview price, fees and totals for buying `amount` from a listing
+    pub fn preview_fees(env: Env, listing_id: u64, amount: i128) -> Result<FeeQuote, Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let listing = migration::read_listing(&env, listing_id)
+            .ok_or(Error::ListingNotFound)?;
+        let total_price = amount.checked_mul(Self::current_price_of(&env, &listing))
+            .ok_or(Error::InvalidAmount)?;
+
+        fees::quote(&env, &listing.token, total_price)
+    }
+
+    /// Calculate total market cap (sum of all active listings value)
+    /// Dutch auctions count at their floor price, and expired listings until
+    /// they are closed
+    pub fn calculate_market_cap(env: Env) -> i128 {
+        listing_index::total_value(&env)
+    }
+
+    /// Get the value of the active listings of a property token, counted
+    /// the same way as `calculate_market_cap`
+    pub fn market_cap(env: Env, token: Address) -> i128 {
+        listing_index::token_value(&env, &token)
+    }
+
+    /// Get contract admin
+    pub fn admin(env: Env) -> Address {
+        storage::get_admin(&env)
+    }
+
+    /// Get escrow contract address
+    pub fn escrow_contract(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "escrow"))
+            .unwrap()
+    }
+
+    /// Get the asset new listings settle in
+    pub fn payment_token(env: Env) -> Option<Address> {
+        configured_payment_token(&env)
+    }
+
+    /// Get registry contract address
+    pub fn registry_contract(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "registry"))
+            .unwrap()
+    }
+
+    // ========== SOROSWAP INTEGRATION ==========
+
+    /// Swap property tokens for USDC using Soroswap DEX
+    /// Allows sellers to instantly convert tokens to USDC without listing
+    pub fn swap_tokens_for_usdc(
+        env: Env,
+        seller: Address,
+        property_token: Address,
+        usdc_token: Address,
+        amount_in: i128,
+        min_usdc_out: i128,
+    ) -> Result<i128, Error> {
+        seller.require_auth();
+
+        swap::swap_property_token_for_usdc(
+            &env,
+            &seller,
+            &property_token,
+            &usdc_token,
+            amount_in,
+            min_usdc_out,
+        )
+    }
+
+    /// Get quote for swapping property tokens to USDC
+    /// Useful for UI price display before executing swap
+    pub fn get_swap_quote(
+        env: Env,
+        property_token: Address,
+        usdc_token: Address,
+        amount_in: i128,
+    ) -> Result<i128, Error> {
+        swap::get_swap_quote(&env, &property_token, &usdc_token, amount_in)
+    }
+
+    /// Swap property tokens for USDC via XLM (when no direct pool exists)
+    pub fn swap_tokens_for_usdc_via_xlm(
+        env: Env,
+        seller: Address,
+        property_token: Address,
+        xlm_token: Address,
+        usdc_token: Address,
+        amount_in: i128,
+        min_usdc_out: i128,
+    ) -> Result<i128, Error> {
+        seller.require_auth();
+
+        swap::swap_property_token_for_usdc_via_xlm(
+            &env,
+            &seller,
+            &property_token,
+            &xlm_token,
+            &usdc_token,
+            amount_in,
+            min_usdc_out,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, token, Address, Env};
+    use soroban_sdk::{testutils::{Address as _, Ledger}, token, Address, Env};
+
+    fn create_token_contract<'a>(env: &Env, admin: &Address) -> token::StellarAssetClient<'a> {
+        token::StellarAssetClient::new(
+            env,
+            &env.register_stellar_asset_contract_v2(admin.clone())
+                .address(),
+        )
+    }
+
+    fn token_balance(env: &Env, token: &Address, id: &Address) -> i128 {
+        token::TokenClient::new(env, token).balance(id)
+    }
+
+    #[test]
+    fn test_initialize() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        client.initialize(&admin, &escrow, &registry);
+
+        assert_eq!(client.admin(), admin);
+        assert_eq!(client.escrow_contract(), escrow);
+        assert_eq!(client.registry_contract(), registry);
+    }
+
+    #[test]
+    fn test_list_and_buy_property() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        // Create property token
+        let token_admin = Address::generate(&env);
+        let property_token = create_token_contract(&env, &token_admin);
+
+        // Mint tokens to seller
+        property_token.mint(&seller, &1000_0000000);
+
+        let usdc_admin = Address::generate(&env);
+        let usdc_token = create_token_contract(&env, &usdc_admin);
+
+        client.initialize(&admin, &escrow, &registry);
+        client.set_payment_token(&usdc_token.address);
+
+        // Seller lists 250 tokens at 2000 USDC per token
+        let amount = 250_0000000;
+        let price_per_token = 2000_0000000;
+
+        let listing_id = client.list_property(&seller, &property_token.address, &amount, &price_per_token, &None);
+
+        // Check listing was created
+        let listing = client.get_listing(&listing_id);
+        assert_eq!(listing.seller, seller);
+        assert_eq!(listing.payment_token, usdc_token.address);
+        assert_eq!(listing.amount, amount);
+        assert_eq!(listing.price_per_token, price_per_token);
+        assert_eq!(listing.status, ListingStatus::Active);
+
+        // Check tokens transferred to marketplace
+        assert_eq!(token_balance(&env, &property_token.address, &contract_id), amount);
+
+        // Buyer approves the marketplace to spend USDC for the whole listing
+        let listing_price = amount * price_per_token;
+        usdc_token.mint(&buyer, &listing_price);
+        token::TokenClient::new(&env, &usdc_token.address).approve(
+            &buyer,
+            &contract_id,
+            &listing_price,
+            &(env.ledger().sequence() + 100),
+        );
+
+        // Sellers can't buy from their own listing
+        assert_eq!(
+            client.try_buy_tokens(&seller, &listing_id, &1, &usdc_token.address),
+            Err(Ok(Error::SelfTrade))
+        );
+
+        // Partial fill: buyer buys 100 tokens
+        let buy_amount = 100_0000000;
+        let total_price = buy_amount * price_per_token;
+        client.buy_tokens(&buyer, &listing_id, &buy_amount, &usdc_token.address);
+
+        assert_eq!(token_balance(&env, &property_token.address, &buyer), buy_amount);
+        assert_eq!(token_balance(&env, &property_token.address, &contract_id), amount - buy_amount);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 1000_0000000 - amount);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), listing_price - total_price);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), total_price);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 0);
+
+        let listing = client.get_listing(&listing_id);
+        assert_eq!(listing.amount, amount - buy_amount);
+        assert_eq!(listing.status, ListingStatus::Active);
+
+        // Full fill: buyer takes the rest
+        client.buy_tokens(&buyer, &listing_id, &(amount - buy_amount), &usdc_token.address);
+
+        assert_eq!(token_balance(&env, &property_token.address, &buyer), amount);
+        assert_eq!(token_balance(&env, &property_token.address, &contract_id), 0);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 1000_0000000 - amount);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 0);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), listing_price);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 0);
+
+        let listing = client.get_listing(&listing_id);
+        assert_eq!(listing.amount, 0);
+        assert_eq!(listing.status, ListingStatus::Completed);
+        assert_eq!(
+            client.try_buy_tokens(&buyer, &listing_id, &1, &usdc_token.address),
+            Err(Ok(Error::ListingCancelled))
+        );
+    }
+
+    #[test]
+    fn test_buy_settles_in_listing_asset() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+
+        let property_token = create_token_contract(&env, &Address::generate(&env));
+        property_token.mint(&seller, &100_0000000);
+
+        // Listing needs a settlement asset
+        assert_eq!(
+            client.try_list_property(&seller, &property_token.address, &10_0000000, &5, &None),
+            Err(Ok(Error::NotInitialized))
+        );
+
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        client.set_payment_token(&usdc_token.address);
+        let listing_id = client.list_property(&seller, &property_token.address, &10_0000000, &5, &None);
+
+        // Switching the settlement asset leaves existing listings bound to theirs
+        let other_token = create_token_contract(&env, &Address::generate(&env));
+        client.set_payment_token(&other_token.address);
+        assert_eq!(client.payment_token(), Some(other_token.address.clone()));
+        assert_eq!(client.get_listing(&listing_id).payment_token, usdc_token.address);
+
+        other_token.mint(&buyer, &50_0000000);
+        token::TokenClient::new(&env, &other_token.address).approve(
+            &buyer,
+            &contract_id,
+            &50_0000000,
+            &(env.ledger().sequence() + 100),
+        );
+        assert_eq!(
+            client.try_buy_tokens(&buyer, &listing_id, &10_0000000, &other_token.address),
+            Err(Ok(Error::SettlementTokenMismatch))
+        );
+
+        // Payment must be both funded and approved
+        assert_eq!(
+            client.try_buy_tokens(&buyer, &listing_id, &10_0000000, &usdc_token.address),
+            Err(Ok(Error::InsufficientBalance))
+        );
+        usdc_token.mint(&buyer, &50_0000000);
+        assert_eq!(
+            client.try_buy_tokens(&buyer, &listing_id, &10_0000000, &usdc_token.address),
+            Err(Ok(Error::InsufficientAllowance))
+        );
+
+        // Nothing moved on failure
+        assert_eq!(token_balance(&env, &property_token.address, &contract_id), 10_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &buyer), 0);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 50_0000000);
+        assert_eq!(token_balance(&env, &other_token.address, &buyer), 50_0000000);
+        assert_eq!(client.get_listing(&listing_id).amount, 10_0000000);
+    }
+
+    #[test]
+    fn test_cancel_listing() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        let token_admin = Address::generate(&env);
+        let property_token = create_token_contract(&env, &token_admin);
+
+        property_token.mint(&seller, &1000_0000000);
+
+        client.initialize(&admin, &escrow, &registry);
+        client.set_payment_token(&create_token_contract(&env, &token_admin).address);
+
+        let amount = 250_0000000;
+        let price_per_token = 2000_0000000;
+
+        let listing_id = client.list_property(&seller, &property_token.address, &amount, &price_per_token, &None);
+
+        // Cancel listing
+        client.cancel_listing(&listing_id);
+
+        // Check listing status
+        let listing = client.get_listing(&listing_id);
+        assert_eq!(listing.status, ListingStatus::Cancelled);
+
+        // Check tokens returned to seller
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 1000_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &contract_id), 0);
+    }
+
+    #[test]
+    fn test_update_listing() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
+
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        let token_admin = Address::generate(&env);
+        let property_token = create_token_contract(&env, &token_admin);
+        property_token.mint(&seller, &1000_0000000);
+
+        client.initialize(&admin, &escrow, &registry);
+        client.set_payment_token(&create_token_contract(&env, &token_admin).address);
+
+        let listing_id = client.list_property(&seller, &property_token.address, &400_0000000, &10, &None);
+
+        // Nothing to change, a bad price, or more tokens than the seller holds
+        assert_eq!(client.try_update_listing(&listing_id, &None, &0), Err(Ok(Error::InvalidAmount)));
+        assert_eq!(client.try_update_listing(&listing_id, &Some(0), &0), Err(Ok(Error::InvalidPrice)));
+        assert_eq!(
+            client.try_update_listing(&listing_id, &None, &600_0000001),
+            Err(Ok(Error::InsufficientBalance))
+        );
+
+        // Withdrawing everything or more than listed is rejected, cancel instead
+        assert_eq!(
+            client.try_update_listing(&listing_id, &None, &-400_0000000),
+            Err(Ok(Error::InvalidListingAmount))
+        );
+        assert_eq!(
+            client.try_update_listing(&listing_id, &None, &-400_0000001),
+            Err(Ok(Error::InvalidListingAmount))
+        );
+
+        // Reprice keeps the id and emits only a price event
+        client.update_listing(&listing_id, &Some(12), &0);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("list_prc"), listing_id).into_val(&env),
+                    (10i128, 12i128).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.get_listing(&listing_id).price_per_token, 12);
+
+        // Adding tokens moves them into custody
+        client.update_listing(&listing_id, &None, &100_0000000);
+        assert_eq!(client.get_listing(&listing_id).amount, 500_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &contract_id), 500_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 500_0000000);
+
+        // Withdrawing and repricing together emits both events
+        client.update_listing(&listing_id, &Some(8), &-300_0000000);
+        assert_eq!(
+            env.events().all().slice(1..),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("list_prc"), listing_id).into_val(&env),
+                    (12i128, 8i128).into_val(&env),
+                ),
+                (
+                    contract_id.clone(),
+                    (symbol_short!("list_amt"), listing_id, seller.clone()).into_val(&env),
+                    (-300_0000000i128, 200_0000000i128).into_val(&env),
+                ),
+            ]
+        );
+        let listing = client.get_listing(&listing_id);
+        assert_eq!((listing.amount, listing.price_per_token), (200_0000000, 8));
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 800_0000000);
+
+        // Closed listings can't be changed
+        client.cancel_listing(&listing_id);
+        assert_eq!(
+            client.try_update_listing(&listing_id, &Some(9), &0),
+            Err(Ok(Error::ListingCancelled))
+        );
+    }
+
+    #[test]
+    fn test_listing_expiry() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
+
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        let token_admin = Address::generate(&env);
+        let property_token = create_token_contract(&env, &token_admin);
+        let usdc_token = create_token_contract(&env, &token_admin);
+        property_token.mint(&seller, &1000_0000000);
+        usdc_token.mint(&buyer, &1000_0000000);
+        token::TokenClient::new(&env, &usdc_token.address)
+            .approve(&buyer, &contract_id, &1000_0000000, &1000);
+
+        client.initialize(&admin, &escrow, &registry);
+        client.set_payment_token(&usdc_token.address);
+
+        // Expiry must be in the future
+        assert_eq!(
+            client.try_list_property(&seller, &property_token.address, &100_0000000, &1, &Some(1000)),
+            Err(Ok(Error::ListingExpired))
+        );
+
+        let listing_id = client.list_property(&seller, &property_token.address, &300_0000000, &1, &Some(2000));
+        let open_id = client.list_property(&seller, &property_token.address, &100_0000000, &1, &None);
+
+        // Open until the expiry, and only then can anyone close it
+        client.buy_tokens(&buyer, &listing_id, &100_0000000, &usdc_token.address);
+        assert_eq!(client.try_expire_listing(&listing_id), Err(Ok(Error::ListingNotExpired)));
+        assert_eq!(client.try_expire_listing(&open_id), Err(Ok(Error::ListingNotExpired)));
+
+        env.ledger().with_mut(|li| li.timestamp = 2000);
+        assert_eq!(
+            client.try_buy_tokens(&buyer, &listing_id, &1, &usdc_token.address),
+            Err(Ok(Error::ListingExpired))
+        );
+        assert_eq!(
+            client.try_update_listing(&listing_id, &Some(2), &0),
+            Err(Ok(Error::ListingExpired))
+        );
+
+        // Expired listings drop out of the token view, but keep their market
+        // value until they are closed
+        assert_eq!(client.get_listings(&property_token.address, &0, &10).listings.len(), 1);
+        assert_eq!(client.get_seller_listings(&seller, &0, &10).listings.len(), 2);
+        assert_eq!(client.calculate_market_cap(), 300_0000000);
+
+        client.expire_listing(&listing_id);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("list_exp"), listing_id, seller.clone()).into_val(&env),
+                    200_0000000i128.into_val(&env),
+                ),
+            ]
+        );
+        let listing = client.get_listing(&listing_id);
+        assert_eq!((listing.status, listing.amount), (ListingStatus::Expired, 0));
+        assert_eq!(client.get_seller_listings(&seller, &0, &10).listings.len(), 1);
+        assert_eq!(client.calculate_market_cap(), 100_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 800_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &buyer), 100_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &contract_id), 100_0000000);
+
+        // Only once
+        assert_eq!(client.try_expire_listing(&listing_id), Err(Ok(Error::ListingCancelled)));
+        assert_eq!(client.try_cancel_listing(&listing_id), Err(Ok(Error::ListingCancelled)));
+
+        // Listings without an expiry stay open
+        client.buy_tokens(&buyer, &open_id, &100_0000000, &usdc_token.address);
+        assert_eq!(client.get_listing(&open_id).status, ListingStatus::Completed);
+    }
+
+    #[test]
+    fn test_get_listings_by_token() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        let token_admin = Address::generate(&env);
+        let property_token = create_token_contract(&env, &token_admin);
+
+        property_token.mint(&seller, &1000_0000000);
+
+        client.initialize(&admin, &escrow, &registry);
+        client.set_payment_token(&create_token_contract(&env, &token_admin).address);
+
+        // Create multiple listings
+        client.list_property(&seller, &property_token.address, &100_0000000, &1000_0000000, &None);
+        client.list_property(&seller, &property_token.address, &200_0000000, &1500_0000000, &None);
+
+        // Get listings for this token
+        let page = client.get_listings(&property_token.address, &0, &10);
+        assert_eq!(page.listings.len(), 2);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_calculate_market_cap() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        let token_admin = Address::generate(&env);
+        let property_token = create_token_contract(&env, &token_admin);
+
+        property_token.mint(&seller, &1000_0000000);
+
+        client.initialize(&admin, &escrow, &registry);
+        client.set_payment_token(&create_token_contract(&env, &token_admin).address);
+
+        // List tokens
+        let amount1 = 100_0000000;
+        let price1 = 1000_0000000;
+        let amount2 = 200_0000000;
+        let price2 = 1500_0000000;
+
+        client.list_property(&seller, &property_token.address, &amount1, &price1, &None);
+        client.list_property(&seller, &property_token.address, &amount2, &price2, &None);
+
+        // Calculate market cap
+        let market_cap = client.calculate_market_cap();
+        let expected = (amount1 * price1) + (amount2 * price2);
+        assert_eq!(market_cap, expected);
+    }
+
+    #[test]
+    fn test_listing_indexes() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller_a = Address::generate(&env);
+        let seller_b = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        let token_admin = Address::generate(&env);
+        let token_x = create_token_contract(&env, &token_admin);
+        let token_y = create_token_contract(&env, &token_admin);
+        let usdc_token = create_token_contract(&env, &token_admin);
+        token_x.mint(&seller_a, &1000);
+        token_x.mint(&seller_b, &1000);
+        token_y.mint(&seller_a, &1000);
+        usdc_token.mint(&buyer, &1_000_000);
+        token::TokenClient::new(&env, &usdc_token.address)
+            .approve(&buyer, &contract_id, &1_000_000, &1000);
+
+        client.initialize(&admin, &escrow, &registry);
+        client.set_payment_token(&usdc_token.address);
+
+        let a1 = client.list_property(&seller_a, &token_x.address, &100, &10, &None);
+        let a2 = client.list_property(&seller_a, &token_x.address, &100, &20, &None);
+        let b1 = client.list_property(&seller_b, &token_x.address, &100, &30, &None);
+        let y1 = client.list_property(&seller_a, &token_y.address, &100, &5, &None);
+
+        // Pages follow the cursor to the end of the set
+        let page = client.get_listings(&token_x.address, &0, &2);
+        assert_eq!(page.listings.len(), 2);
+        assert_eq!(page.next_cursor, Some(2));
+        let page = client.get_listings(&token_x.address, &2, &2);
+        assert_eq!(page.listings.len(), 1);
+        assert_eq!(page.listings.get(0).unwrap().id, b1);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(client.get_listings(&token_x.address, &5, &2).listings.len(), 0);
+
+        let page = client.get_seller_listings(&seller_a, &0, &10);
+        assert_eq!(page.listings.len(), 3);
+        assert!(page.listings.iter().all(|listing| listing.seller == seller_a));
+
+        // Market cap is kept per token
+        assert_eq!(client.market_cap(&token_x.address), 100 * 10 + 100 * 20 + 100 * 30);
+        assert_eq!(client.market_cap(&token_y.address), 100 * 5);
+        assert_eq!(client.calculate_market_cap(), 6000 + 500);
+
+        // Buying takes value off the listing
+        client.buy_tokens(&buyer, &a1, &40, &usdc_token.address);
+        client.buy_tokens(&buyer, &b1, &50, &usdc_token.address);
+        assert_eq!(client.market_cap(&token_x.address), 60 * 10 + 100 * 20 + 50 * 30);
+
+        // Updates move it by the difference
+        client.update_listing(&a2, &Some(25), &0);
+        assert_eq!(client.market_cap(&token_x.address), 60 * 10 + 100 * 25 + 50 * 30);
+
+        // Sold out and cancelled listings leave the indexes
+        client.buy_tokens(&buyer, &a1, &60, &usdc_token.address);
+        client.cancel_listing(&y1);
+        assert_eq!(client.get_listing(&a1).status, ListingStatus::Completed);
+        assert_eq!(client.market_cap(&token_x.address), 100 * 25 + 50 * 30);
+        assert_eq!(client.market_cap(&token_y.address), 0);
+        assert_eq!(client.calculate_market_cap(), 4000);
+
+        let page = client.get_listings(&token_x.address, &0, &10);
+        assert_eq!(page.listings.len(), 2);
+        assert!(page.listings.iter().all(|listing| listing.id == a2 || listing.id == b1));
+        assert_eq!(client.get_listings(&token_y.address, &0, &10).listings.len(), 0);
+        let page = client.get_seller_listings(&seller_a, &0, &10);
+        assert_eq!(page.listings.len(), 1);
+        assert_eq!(page.listings.get(0).unwrap().id, a2);
+    }
+
+    #[test]
+    fn test_trade_ledger_and_candles() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 86400);
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let registry = Address::generate(&env);
+
+        let token_admin = Address::generate(&env);
+        let token_x = create_token_contract(&env, &token_admin);
+        let token_y = create_token_contract(&env, &token_admin);
+        let usdc_token = create_token_contract(&env, &token_admin);
+        token_x.mint(&seller, &1000);
+        token_y.mint(&seller, &1000);
+        usdc_token.mint(&buyer, &1_000_000);
+        token::TokenClient::new(&env, &usdc_token.address)
+            .approve(&buyer, &contract_id, &1_000_000, &1000);
+
+        client.initialize(&admin, &escrow, &registry);
+        client.set_payment_token(&usdc_token.address);
+
+        let cheap = client.list_property(&seller, &token_x.address, &500, &10, &None);
+        let dear = client.list_property(&seller, &token_x.address, &500, &30, &None);
+        let other = client.list_property(&seller, &token_y.address, &500, &99, &None);
+
+        // Two trades in the first hour, one in the third, one the next day
+        client.buy_tokens(&buyer, &dear, &10, &usdc_token.address);
+        env.ledger().with_mut(|li| li.timestamp = 86400 + 1800);
+        client.buy_tokens(&buyer, &cheap, &20, &usdc_token.address);
+        env.ledger().with_mut(|li| li.timestamp = 86400 + 2 * 3600 + 5);
+        client.buy_tokens(&buyer, &dear, &5, &usdc_token.address);
+        env.ledger().with_mut(|li| li.timestamp = 2 * 86400);
+        client.buy_tokens(&buyer, &cheap, &1, &usdc_token.address);
+        client.buy_tokens(&buyer, &other, &3, &usdc_token.address);
+
+        // Each token has its own ledger
+        assert_eq!(client.trade_count(&token_x.address), 4);
+        assert_eq!(client.trade_count(&token_y.address), 1);
+        let page = client.get_trades(&token_x.address, &0, &3);
+        assert_eq!(page.trades.len(), 3);
+        assert_eq!(page.next_cursor, Some(3));
+        assert!(page.trades.iter().all(|trade| trade.token == token_x.address));
+        assert_eq!(page.trades.get(1).unwrap().listing_id, Some(cheap));
+        let page = client.get_trades(&token_x.address, &3, &3);
+        assert_eq!(page.trades.len(), 1);
+        assert_eq!(page.trades.get(0).unwrap().timestamp, 2 * 86400);
+        assert_eq!(page.next_cursor, None);
+        assert_eq!(client.get_price_history(&token_y.address).get(0).unwrap().price, 3 * 99);
+
+        // Candles hold prices per token
+        let hour = client.get_candle(&token_x.address, &Interval::Hour, &(86400 + 60)).unwrap();
+        assert_eq!(
+            hour,
+            Candle {
+                start: 86400,
+                open: 30,
+                high: 30,
+                low: 10,
+                close: 10,
+                volume: 30,
+                value: 10 * 30 + 20 * 10,
+                trades: 2,
+            }
+        );
+        assert_eq!(client.get_candle(&token_x.address, &Interval::Hour, &(86400 + 3600)), None);
+
+        let day = client.get_candle(&token_x.address, &Interval::Day, &86400).unwrap();
+        assert_eq!((day.open, day.high, day.low, day.close), (30, 30, 10, 30));
+        assert_eq!((day.volume, day.trades), (35, 3));
+
+        // Ranges skip buckets without trades
+        let hours = client.get_candles(&token_x.address, &Interval::Hour, &86400, &(2 * 86400 + 1));
+        assert_eq!(hours.len(), 3);
+        assert_eq!(hours.get(1).unwrap().start, 86400 + 2 * 3600);
+        assert_eq!(hours.get(2).unwrap().start, 2 * 86400);
+        let days = client.get_candles(&token_x.address, &Interval::Day, &0, &(3 * 86400));
+        assert_eq!(days.len(), 2);
+        assert_eq!(client.get_candles(&token_y.address, &Interval::Day, &0, &(3 * 86400)).len(), 1);
+    }
+
+    #[test]
+    fn test_trade_fees() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
+
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        let fee_manager = Address::generate(&env);
+
+        let property_token = create_token_contract(&env, &Address::generate(&env));
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        property_token.mint(&seller, &1000_0000000);
+        usdc_token.mint(&buyer, &10_000_0000000);
+
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+        client.set_payment_token(&usdc_token.address);
+        token::TokenClient::new(&env, &usdc_token.address).approve(
+            &buyer,
+            &contract_id,
+            &10_000_0000000,
+            &(env.ledger().sequence() + 100),
+        );
 
-    fn create_token_contract<'a>(env: &Env, admin: &Address) -> token::StellarAssetClient<'a> {
-        token::StellarAssetClient::new(
-            env,
-            &env.register_stellar_asset_contract_v2(admin.clone())
-                .address(),
-        )
+        let listing_id = client.list_property(&seller, &property_token.address, &500_0000000, &10, &None);
+
+        // No fees until configured
+        assert_eq!(client.preview_fees(&listing_id, &100_0000000).buyer_fee, 0);
+
+        // Buyer pays 1% on top, seller gives up 2%
+        let schedule = FeeSchedule {
+            buyer_fee_bps: 100,
+            seller_fee_bps: 200,
+            min_fee: 0,
+        };
+        assert_eq!(
+            client.try_set_fees(&fee_manager, &schedule, &treasury),
+            Err(Ok(Error::NotAuthorized))
+        );
+        assert_eq!(
+            client.try_set_token_fees(&admin, &property_token.address, &Some(schedule.clone())),
+            Err(Ok(Error::NotInitialized))
+        );
+        client.grant_role(&Role::FeeManager, &fee_manager);
+        assert_eq!(
+            client.try_set_fees(&fee_manager, &FeeSchedule { buyer_fee_bps: 10001, ..schedule.clone() }, &treasury),
+            Err(Ok(Error::InvalidPercentage))
+        );
+        client.set_fees(&fee_manager, &schedule, &treasury);
+        assert_eq!(client.fee_recipient(), Some(treasury.clone()));
+
+        let quote = client.preview_fees(&listing_id, &100_0000000);
+        assert_eq!(
+            quote,
+            FeeQuote {
+                price: 1000_0000000,
+                buyer_fee: 10_0000000,
+                seller_fee: 20_0000000,
+                buyer_total: 1010_0000000,
+                seller_proceeds: 980_0000000,
+            }
+        );
+
+        client.buy_tokens(&buyer, &listing_id, &100_0000000, &usdc_token.address);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("purchase"), listing_id, buyer.clone()).into_val(&env),
+                    (seller.clone(), 100_0000000_i128, 1000_0000000_i128, 10_0000000_i128, 20_0000000_i128)
+                        .into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 10_000_0000000 - 1010_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), 980_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &treasury), 30_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 0);
+
+        let trade = client.get_price_history(&property_token.address).get(0).unwrap();
+        assert_eq!(trade.price, 1000_0000000);
+        assert_eq!(trade.buyer_fee, 10_0000000);
+        assert_eq!(trade.seller_fee, 20_0000000);
+
+        // A per-token schedule replaces the default, with a flat minimum
+        let token_schedule = FeeSchedule {
+            buyer_fee_bps: 0,
+            seller_fee_bps: 500,
+            min_fee: 5_0000000,
+        };
+        client.set_token_fees(&fee_manager, &property_token.address, &Some(token_schedule.clone()));
+        assert_eq!(client.fee_schedule(&property_token.address), token_schedule);
+
+        let quote = client.preview_fees(&listing_id, &1_0000000);
+        assert_eq!(quote.buyer_fee, 0);
+        assert_eq!(quote.seller_fee, 5_0000000);
+        assert_eq!(quote.seller_proceeds, 5_0000000);
+
+        client.buy_tokens(&buyer, &listing_id, &1_0000000, &usdc_token.address);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), 985_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &treasury), 35_0000000);
+
+        client.set_token_fees(&fee_manager, &property_token.address, &None);
+        assert_eq!(client.fee_schedule(&property_token.address), schedule);
     }
 
     #[test]
-    fn test_initialize() {
+    fn test_order_book_matching() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
+
         let env = Env::default();
-        let contract_id = env.register_contract(None, MarketplaceContract);
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
         let client = MarketplaceContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
-        let escrow = Address::generate(&env);
-        let registry = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        let property_token = create_token_contract(&env, &Address::generate(&env));
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        let token = property_token.address.clone();
+
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+        client.set_payment_token(&usdc_token.address);
+        let schedule = FeeSchedule {
+            buyer_fee_bps: 100,
+            seller_fee_bps: 100,
+            min_fee: 0,
+        };
+        client.set_fees(&admin, &schedule, &treasury);
 
-        client.initialize(&admin, &escrow, &registry);
+        let seller = || {
+            let seller = Address::generate(&env);
+            property_token.mint(&seller, &1000);
+            seller
+        };
+        let buyer = || {
+            let buyer = Address::generate(&env);
+            usdc_token.mint(&buyer, &10000);
+            token::TokenClient::new(&env, &usdc_token.address)
+                .approve(&buyer, &contract_id, &10000, &(env.ledger().sequence() + 100));
+            buyer
+        };
 
-        assert_eq!(client.admin(), admin);
-        assert_eq!(client.escrow_contract(), escrow);
-        assert_eq!(client.registry_contract(), registry);
+        // Asks rest best price first, oldest first within a price
+        let (seller_a, seller_b, seller_c) = (seller(), seller(), seller());
+        let listing_a = client.list_property(&seller_a, &token, &100, &10, &None);
+        let listing_b = client.list_property(&seller_b, &token, &50, &9, &None);
+        let listing_c = client.list_property(&seller_c, &token, &50, &9, &None);
+        assert_eq!(client.best_ask(&token), Some(9));
+        assert_eq!(client.best_bid(&token), None);
+        assert_eq!(
+            client.market_depth(&token, &5).asks,
+            vec![
+                &env,
+                PriceLevel { price: 9, amount: 100, orders: 2 },
+                PriceLevel { price: 10, amount: 100, orders: 1 },
+            ]
+        );
+
+        // A crossing bid sweeps the asks at their own prices
+        // Buyer holds 1200 + 12 fee and gets back what the cheaper asks saved,
+        // fees included
+        let taker = buyer();
+        let bid_id = client.place_bid(&taker, &token, &120, &10);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("purchase"), listing_a, taker.clone()).into_val(&env),
+                    (seller_a.clone(), 20i128, 200i128, 2i128, 2i128).into_val(&env),
+                ),
+            ]
+        );
+        let bid = client.get_bid(&bid_id);
+        assert_eq!((bid.status, bid.amount, bid.buyer_fee), (BidStatus::Filled, 0, 0));
+        assert_eq!(client.get_listing(&listing_b).status, ListingStatus::Completed);
+        assert_eq!(client.get_listing(&listing_c).status, ListingStatus::Completed);
+        assert_eq!(client.get_listing(&listing_a).amount, 80);
+        assert_eq!(token_balance(&env, &token, &taker), 120);
+        assert_eq!(token_balance(&env, &usdc_token.address, &taker), 10000 - 1100 - 10);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller_b), 450 - 4);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller_c), 450 - 4);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller_a), 200 - 2);
+        assert_eq!(token_balance(&env, &usdc_token.address, &treasury), 10 + 10);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 0);
+        let trade = client.get_price_history(&token).last().unwrap();
+        assert_eq!((trade.listing_id, trade.bid_id), (Some(listing_a), Some(bid_id)));
+        assert_eq!(client.best_ask(&token), Some(10));
+        assert_eq!(client.best_bid(&token), None);
+
+        // Bids below the ask rest, best price first, oldest first within a price
+        let (buyer_1, buyer_2, buyer_3) = (buyer(), buyer(), buyer());
+        let bid_1 = client.place_bid(&buyer_1, &token, &30, &8);
+        let bid_2 = client.place_bid(&buyer_2, &token, &20, &8);
+        let bid_3 = client.place_bid(&buyer_3, &token, &10, &9);
+        assert_eq!(client.best_bid(&token), Some(9));
+        assert_eq!(client.market_depth(&token, &1).bids, vec![&env, PriceLevel { price: 9, amount: 10, orders: 1 }]);
+        assert_eq!(
+            client.market_depth(&token, &5).bids,
+            vec![
+                &env,
+                PriceLevel { price: 9, amount: 10, orders: 1 },
+                PriceLevel { price: 8, amount: 50, orders: 2 },
+            ]
+        );
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 240 + 2 + 160 + 1 + 90);
+
+        // A crossing listing fills the bids at their own prices and never rests
+        let seller_d = seller();
+        let listing_d = client.list_property(&seller_d, &token, &50, &8, &None);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("bid_fill"), bid_2, seller_d.clone()).into_val(&env),
+                    (Some(listing_d), 10i128, 80i128, 0i128, 0i128).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.get_listing(&listing_d).status, ListingStatus::Completed);
+        assert_eq!(client.get_bid(&bid_3).status, BidStatus::Filled);
+        assert_eq!(client.get_bid(&bid_1).status, BidStatus::Filled);
+        assert_eq!(client.get_bid(&bid_2).amount, 10);
+        assert_eq!(token_balance(&env, &token, &buyer_3), 10);
+        assert_eq!(token_balance(&env, &token, &buyer_1), 30);
+        assert_eq!(token_balance(&env, &token, &buyer_2), 10);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller_d), 90 + (240 - 2) + 80);
+        assert_eq!(client.best_bid(&token), Some(8));
+        assert_eq!(client.best_ask(&token), Some(10));
+
+        // Orders from the same account don't match each other
+        let listing_e = client.list_property(&seller_a, &token, &10, &10, &None);
+        token::TokenClient::new(&env, &usdc_token.address)
+            .approve(&seller_a, &contract_id, &50, &(env.ledger().sequence() + 100));
+        client.place_bid(&seller_a, &token, &5, &10);
+        assert_eq!(client.get_listing(&listing_e).amount, 10);
+        assert_eq!(client.get_listing(&listing_a).amount, 80);
+
+        // Repricing below the best bid fills it; cancelling leaves the book
+        client.update_listing(&listing_e, &Some(8), &0);
+        assert_eq!(client.get_bid(&bid_2).status, BidStatus::Filled);
+        assert_eq!(client.get_listing(&listing_e).amount, 0);
+        client.cancel_listing(&listing_a);
+        assert_eq!(client.best_ask(&token), None);
+        assert_eq!(client.market_depth(&token, &5).bids.len(), 1);
     }
 
     #[test]
-    fn test_list_and_buy_property() {
+    fn test_bid_lifecycle() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
+
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, MarketplaceContract);
+        let contract_id = env.register(MarketplaceContract, ());
         let client = MarketplaceContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
         let seller = Address::generate(&env);
         let buyer = Address::generate(&env);
-        let escrow = Address::generate(&env);
-        let registry = Address::generate(&env);
+        let property_token = create_token_contract(&env, &Address::generate(&env));
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        let token = property_token.address.clone();
+        property_token.mint(&seller, &1000);
+        usdc_token.mint(&buyer, &10000);
+
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+        assert_eq!(
+            client.try_place_bid(&buyer, &token, &100, &5),
+            Err(Ok(Error::NotInitialized))
+        );
+        client.set_payment_token(&usdc_token.address);
+        assert_eq!(client.try_place_bid(&buyer, &token, &0, &5), Err(Ok(Error::InvalidAmount)));
+        assert_eq!(client.try_place_bid(&buyer, &token, &100, &0), Err(Ok(Error::InvalidPrice)));
+        assert_eq!(
+            client.try_place_bid(&buyer, &token, &100, &5),
+            Err(Ok(Error::InsufficientAllowance))
+        );
+        token::TokenClient::new(&env, &usdc_token.address)
+            .approve(&buyer, &contract_id, &10000, &(env.ledger().sequence() + 100));
+
+        // Payment is held by the contract while the bid is open
+        let bid_id = client.place_bid(&buyer, &token, &100, &5);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 500);
+        assert_eq!(client.get_bid(&bid_id).escrowed(), 500);
+
+        // Sellers fill directly from their own balance, buyers can't fill their own bids
+        assert_eq!(client.try_fill_bid(&buyer, &bid_id, &1), Err(Ok(Error::SelfTrade)));
+        assert_eq!(
+            client.try_fill_bid(&seller, &bid_id, &101),
+            Err(Ok(Error::InvalidListingAmount))
+        );
+        client.fill_bid(&seller, &bid_id, &40);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("bid_fill"), bid_id, seller.clone()).into_val(&env),
+                    (None::<u64>, 40i128, 200i128, 0i128, 0i128).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(token_balance(&env, &token, &buyer), 40);
+        assert_eq!(token_balance(&env, &token, &seller), 960);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), 200);
+        let trade = client.get_price_history(&token).last().unwrap();
+        assert_eq!((trade.listing_id, trade.bid_id, trade.amount), (None, Some(bid_id), 40));
+
+        // Cancelling returns what is still held
+        client.cancel_bid(&bid_id);
+        let bid = client.get_bid(&bid_id);
+        assert_eq!((bid.status, bid.amount), (BidStatus::Cancelled, 60));
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 10000 - 200);
+        assert_eq!(token_balance(&env, &usdc_token.address, &contract_id), 0);
+        assert_eq!(client.best_bid(&token), None);
+        assert_eq!(client.try_cancel_bid(&bid_id), Err(Ok(Error::ListingCancelled)));
+        assert_eq!(client.try_fill_bid(&seller, &bid_id, &1), Err(Ok(Error::ListingCancelled)));
+        assert_eq!(client.try_get_bid(&99), Err(Ok(Error::ListingNotFound)));
+
+        // Each side of a token's book is capped; a better price takes the
+        // place of the worst order, which stays open off the book
+        let mut last_bid = 0;
+        for _ in 0..orderbook::MAX_BOOK_ORDERS {
+            last_bid = client.place_bid(&buyer, &token, &1, &1);
+        }
+        assert_eq!(
+            client.try_place_bid(&buyer, &token, &1, &1),
+            Err(Ok(Error::OrderBookFull))
+        );
+        client.place_bid(&buyer, &token, &1, &2);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("bid_off"), last_bid, buyer.clone()).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.best_bid(&token), Some(2));
+        assert_eq!(client.get_bid(&last_bid).status, BidStatus::Open);
+        client.fill_bid(&seller, &last_bid, &1);
+        assert_eq!(client.get_bid(&last_bid).status, BidStatus::Filled);
+
+        // Listings keep working on a full book, buyable directly until they rest
+        let mut last_listing = 0;
+        for _ in 0..orderbook::MAX_BOOK_ORDERS {
+            last_listing = client.list_property(&seller, &token, &1, &5, &None);
+        }
+        let off_book = client.list_property(&seller, &token, &1, &5, &None);
+        assert_eq!(client.market_depth(&token, &10).asks.get(0).unwrap().orders, orderbook::MAX_BOOK_ORDERS);
+        client.buy_tokens(&buyer, &off_book, &1, &usdc_token.address);
+        assert_eq!(client.get_listing(&off_book).status, ListingStatus::Completed);
+
+        client.list_property(&seller, &token, &1, &4, &None);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("list_off"), last_listing, seller.clone()).into_val(&env),
+                    ().into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.best_ask(&token), Some(4));
+        assert_eq!(client.get_listing(&last_listing).status, ListingStatus::Active);
+        client.buy_tokens(&buyer, &last_listing, &1, &usdc_token.address);
+        assert_eq!(client.get_listing(&last_listing).status, ListingStatus::Completed);
+    }
 
-        // Create property token
-        let token_admin = Address::generate(&env);
-        let property_token = create_token_contract(&env, &token_admin);
+    #[test]
+    fn test_listing_skips_bids_it_cannot_deliver_to() {
+        use property_token::{PropertyTokenContract, PropertyTokenContractClient};
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal, String};
 
-        // Mint tokens to seller
-        property_token.mint(&seller, &1000_0000000);
+        let env = Env::default();
+        env.mock_all_auths();
 
-        client.initialize(&admin, &escrow, &registry);
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
 
-        // Seller lists 250 tokens at 2000 USDC per token
-        let amount = 250_0000000;
-        let price_per_token = 2000_0000000;
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let frozen_buyer = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let token = env.register(PropertyTokenContract, ());
+        let property_token = PropertyTokenContractClient::new(&env, &token);
+        property_token.initialize(
+            &admin,
+            &Symbol::new(&env, "PROP001"),
+            &String::from_str(&env, "Property Token"),
+            &String::from_str(&env, "PROP"),
+            &1000,
+        );
+        property_token.transfer(&admin, &seller, &100);
+
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        let usdc = usdc_token.address.clone();
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+        client.set_payment_token(&usdc);
+        for bidder in [&frozen_buyer, &buyer] {
+            usdc_token.mint(bidder, &1000);
+            token::TokenClient::new(&env, &usdc)
+                .approve(bidder, &contract_id, &1000, &(env.ledger().sequence() + 100));
+        }
 
-        let listing_id = client.list_property(&seller, &property_token.address, &amount, &price_per_token);
+        // The best bid can no longer receive the tokens
+        let frozen_bid = client.place_bid(&frozen_buyer, &token, &50, &6);
+        let bid_id = client.place_bid(&buyer, &token, &50, &5);
+        property_token.freeze(&admin, &frozen_buyer);
+
+        // The listing cancels that bid and fills the next one
+        let listing_id = client.list_property(&seller, &token, &80, &5, &None);
+        assert_eq!(client.get_bid(&frozen_bid).status, BidStatus::Cancelled);
+        assert_eq!(client.get_bid(&bid_id).status, BidStatus::Filled);
+        assert_eq!(client.get_listing(&listing_id).amount, 30);
+        assert_eq!(property_token.balance(&buyer), 50);
+        assert_eq!(property_token.balance(&frozen_buyer), 0);
+        assert_eq!(client.best_ask(&token), Some(5));
+
+        // Its payment waits for the buyer to claim it
+        assert_eq!(client.claimable_refund(&frozen_buyer, &usdc), 300);
+        assert_eq!(token_balance(&env, &usdc, &frozen_buyer), 700);
+        assert_eq!(client.claim_refund(&frozen_buyer, &usdc), 300);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("rfnd_clm"), frozen_buyer.clone(), usdc.clone()).into_val(&env),
+                    300i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(token_balance(&env, &usdc, &frozen_buyer), 1000);
+        assert_eq!(client.claimable_refund(&frozen_buyer, &usdc), 0);
+        assert_eq!(client.claim_refund(&frozen_buyer, &usdc), 0);
+    }
 
-        // Check listing was created
-        let listing = client.get_listing(&listing_id);
-        assert_eq!(listing.seller, seller);
-        assert_eq!(listing.amount, amount);
-        assert_eq!(listing.price_per_token, price_per_token);
-        assert_eq!(listing.status, ListingStatus::Active);
+    #[test]
+    fn test_english_auction() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
 
-        // Check tokens transferred to marketplace
-        assert_eq!(property_token.balance(&contract_id), amount);
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 1000);
 
-        // Buyer buys 100 tokens
-        let buy_amount = 100_0000000;
-        client.buy_tokens(&buyer, &listing_id, &buy_amount);
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
+        let bidder_a = Address::generate(&env);
+        let bidder_b = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        let property_token = create_token_contract(&env, &Address::generate(&env));
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        let token = property_token.address.clone();
+        let usdc = usdc_token.address.clone();
+        property_token.mint(&seller, &1000);
+        for bidder in [&bidder_a, &bidder_b] {
+            usdc_token.mint(bidder, &100_000);
+            token::TokenClient::new(&env, &usdc)
+                .approve(bidder, &contract_id, &100_000, &(env.ledger().sequence() + 100));
+        }
 
-        // Check buyer received tokens
-        assert_eq!(property_token.balance(&buyer), buy_amount);
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+        client.set_payment_token(&usdc);
+        let schedule = FeeSchedule {
+            buyer_fee_bps: 100,
+            seller_fee_bps: 100,
+            min_fee: 0,
+        };
+        client.set_fees(&admin, &schedule, &treasury);
 
-        // Check listing updated
-        let listing = client.get_listing(&listing_id);
-        assert_eq!(listing.amount, amount - buy_amount);
-        assert_eq!(listing.status, ListingStatus::Active);
+        assert_eq!(
+            client.try_create_auction(&seller, &token, &500, &5000, &100, &1000),
+            Err(Ok(Error::ListingExpired))
+        );
+        assert_eq!(
+            client.try_create_auction(&seller, &token, &500, &5000, &0, &10_000),
+            Err(Ok(Error::InvalidPrice))
+        );
+
+        let auction_id = client.create_auction(&seller, &token, &500, &5000, &100, &10_000);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("auc_new"), auction_id, seller.clone()).into_val(&env),
+                    (token.clone(), 500i128, 5000i128, 10_000u64).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(token_balance(&env, &token, &contract_id), 500);
+
+        // The first bid must reach the reserve, later ones beat it by the increment
+        assert_eq!(client.try_bid_auction(&bidder_a, &auction_id, &4999), Err(Ok(Error::InvalidPrice)));
+        client.bid_auction(&bidder_a, &auction_id, &5000);
+        assert_eq!(token_balance(&env, &usdc, &contract_id), 5000 + 50);
+        assert_eq!(client.auction_min_bid(&auction_id), 5100);
+        assert_eq!(client.try_bid_auction(&bidder_b, &auction_id, &5099), Err(Ok(Error::InvalidPrice)));
+
+        // Outbidding credits the previous high bid and its fee as a refund
+        client.bid_auction(&bidder_b, &auction_id, &5100);
+        assert_eq!(
+            env.events().all().slice(1..),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("auc_bid"), auction_id, bidder_b.clone()).into_val(&env),
+                    5100i128.into_val(&env),
+                ),
+                (
+                    contract_id.clone(),
+                    (symbol_short!("auc_rfnd"), auction_id, bidder_a.clone()).into_val(&env),
+                    5050i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.claimable_refund(&bidder_a, &usdc), 5050);
+        assert_eq!(token_balance(&env, &usdc, &bidder_a), 100_000 - 5050);
+        assert_eq!(token_balance(&env, &usdc, &contract_id), 5050 + 5100 + 51);
+        assert_eq!(client.try_settle_auction(&auction_id), Err(Ok(Error::AuctionNotEnded)));
+
+        // A bid near the end moves the end back
+        env.ledger().with_mut(|li| li.timestamp = 9700);
+        client.bid_auction(&bidder_a, &auction_id, &6000);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("auc_ext"), auction_id).into_val(&env),
+                    10_300u64.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.get_auction(&auction_id).end_time, 10_300);
+        assert_eq!(client.claimable_refund(&bidder_b, &usdc), 5151);
+
+        env.ledger().with_mut(|li| li.timestamp = 10_000);
+        assert_eq!(client.try_settle_auction(&auction_id), Err(Ok(Error::AuctionNotEnded)));
+
+        env.ledger().with_mut(|li| li.timestamp = 10_300);
+        assert_eq!(client.try_bid_auction(&bidder_b, &auction_id, &7000), Err(Ok(Error::ListingExpired)));
+
+        // Anyone settles once ended; the lot and payment change hands
+        client.settle_auction(&auction_id);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("auc_done"), auction_id).into_val(&env),
+                    (Some(bidder_a.clone()), 6000i128, 60i128, 60i128).into_val(&env),
+                ),
+            ]
+        );
+        let auction = client.get_auction(&auction_id);
+        assert_eq!((auction.status, auction.bid_count), (AuctionStatus::Sold, 3));
+        assert_eq!(token_balance(&env, &token, &bidder_a), 500);
+        assert_eq!(token_balance(&env, &usdc, &bidder_a), 100_000 - 5050 - 6060);
+        assert_eq!(token_balance(&env, &usdc, &seller), 6000 - 60);
+        assert_eq!(token_balance(&env, &usdc, &treasury), 120);
+        assert_eq!(token_balance(&env, &usdc, &contract_id), 5050 + 5151);
+
+        // Outbid bidders claim their refunds
+        assert_eq!(client.claim_refund(&bidder_a, &usdc), 5050);
+        assert_eq!(client.claim_refund(&bidder_b, &usdc), 5151);
+        assert_eq!(token_balance(&env, &usdc, &bidder_a), 100_000 - 6060);
+        assert_eq!(token_balance(&env, &usdc, &bidder_b), 100_000);
+        assert_eq!(token_balance(&env, &usdc, &contract_id), 0);
+        let trade = client.get_price_history(&token).last().unwrap();
+        assert_eq!((trade.listing_id, trade.bid_id, trade.price), (None, None, 6000));
+
+        assert_eq!(client.try_settle_auction(&auction_id), Err(Ok(Error::ListingCancelled)));
     }
 
     #[test]
-    fn test_cancel_listing() {
+    fn test_auction_without_bids() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
+
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let seller = Address::generate(&env);
+        let property_token = create_token_contract(&env, &Address::generate(&env));
+        property_token.mint(&seller, &1000);
+
+        client.initialize(&Address::generate(&env), &Address::generate(&env), &Address::generate(&env));
+        client.set_payment_token(&create_token_contract(&env, &Address::generate(&env)).address);
+
+        let auction_id = client.create_auction(&seller, &property_token.address, &1000, &5000, &100, &500);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 0);
+
+        // Nobody met the reserve, so the lot goes back
+        env.ledger().with_mut(|li| li.timestamp = 500);
+        client.settle_auction(&auction_id);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("auc_done"), auction_id).into_val(&env),
+                    (None::<Address>, 0i128, 0i128, 0i128).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.get_auction(&auction_id).status, AuctionStatus::Unsold);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 1000);
+        assert_eq!(client.try_get_auction(&99), Err(Ok(Error::ListingNotFound)));
+    }
+
+    #[test]
+    fn test_auction_winner_cannot_receive() {
+        use property_token::{PropertyTokenContract, PropertyTokenContractClient};
+        use soroban_sdk::String;
+
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, MarketplaceContract);
+        let contract_id = env.register(MarketplaceContract, ());
         let client = MarketplaceContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
         let seller = Address::generate(&env);
-        let escrow = Address::generate(&env);
-        let registry = Address::generate(&env);
+        let bidder = Address::generate(&env);
+        let token_id = env.register(PropertyTokenContract, ());
+        let property_token = PropertyTokenContractClient::new(&env, &token_id);
+        property_token.initialize(
+            &admin,
+            &Symbol::new(&env, "PROP001"),
+            &String::from_str(&env, "Property Token"),
+            &String::from_str(&env, "PROP"),
+            &1000,
+        );
+        property_token.transfer(&admin, &seller, &100);
+
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+        client.set_payment_token(&usdc_token.address);
+        let schedule = FeeSchedule {
+            buyer_fee_bps: 100,
+            seller_fee_bps: 100,
+            min_fee: 0,
+        };
+        client.set_fees(&admin, &schedule, &Address::generate(&env));
+        usdc_token.mint(&bidder, &10000);
+        token::TokenClient::new(&env, &usdc_token.address)
+            .approve(&bidder, &contract_id, &10000, &(env.ledger().sequence() + 100));
+
+        let auction_id = client.create_auction(&seller, &token_id, &100, &5000, &100, &500);
+        client.bid_auction(&bidder, &auction_id, &5000);
+
+        // The winner is frozen before the auction settles
+        property_token.freeze(&admin, &bidder);
+        env.ledger().with_mut(|li| li.timestamp = client.get_auction(&auction_id).end_time);
+        client.settle_auction(&auction_id);
+
+        assert_eq!(client.get_auction(&auction_id).status, AuctionStatus::Unsold);
+        assert_eq!(property_token.balance(&seller), 100);
+        assert_eq!(property_token.balance(&bidder), 0);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), 0);
+        assert_eq!(client.trade_count(&token_id), 0);
+
+        // The bid and its fee wait for the bidder to claim them
+        assert_eq!(client.claimable_refund(&bidder, &usdc_token.address), 5050);
+        assert_eq!(client.claim_refund(&bidder, &usdc_token.address), 5050);
+        assert_eq!(token_balance(&env, &usdc_token.address, &bidder), 10000);
+    }
 
-        let token_admin = Address::generate(&env);
-        let property_token = create_token_contract(&env, &token_admin);
+    #[test]
+    fn test_dutch_auction() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
+
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let seller = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let property_token = create_token_contract(&env, &Address::generate(&env));
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        let token = property_token.address.clone();
+        let usdc = usdc_token.address.clone();
+        property_token.mint(&seller, &1000);
+        usdc_token.mint(&buyer, &100_000);
+        token::TokenClient::new(&env, &usdc)
+            .approve(&buyer, &contract_id, &100_000, &(env.ledger().sequence() + 100));
+
+        client.initialize(&Address::generate(&env), &Address::generate(&env), &Address::generate(&env));
+        client.set_payment_token(&usdc);
+
+        // 1000 per token from t=2000, falling to 400 at t=8000, open until t=10000
+        let schedule = DutchAuction {
+            start_price: 1000,
+            floor_price: 400,
+            start_time: 2000,
+            end_time: 8000,
+            floor_duration: 2000,
+        };
+        assert_eq!(
+            client.try_list_dutch_auction(&seller, &token, &100, &DutchAuction { floor_price: 1000, ..schedule.clone() }),
+            Err(Ok(Error::InvalidPrice))
+        );
+        assert_eq!(
+            client.try_list_dutch_auction(&seller, &token, &100, &DutchAuction { end_time: 2000, ..schedule.clone() }),
+            Err(Ok(Error::ListingExpired))
+        );
+
+        let listing_id = client.list_dutch_auction(&seller, &token, &100, &schedule);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("dutch_new"), listing_id).into_val(&env),
+                    (1000i128, 400i128, 2000u64, 8000u64).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.get_dutch_auction(&listing_id), Some(schedule));
+        assert_eq!(client.get_listing(&listing_id).expires_at, Some(10_000));
+        assert_eq!(client.best_ask(&token), None);
+
+        // The price holds until the start, then falls in a straight line to the floor
+        assert_eq!(client.current_price(&listing_id), 1000);
+        env.ledger().with_mut(|li| li.timestamp = 2000);
+        assert_eq!(client.current_price(&listing_id), 1000);
+        env.ledger().with_mut(|li| li.timestamp = 3000);
+        assert_eq!(client.current_price(&listing_id), 900);
+        env.ledger().with_mut(|li| li.timestamp = 7999);
+        assert_eq!(client.current_price(&listing_id), 401);
+        env.ledger().with_mut(|li| li.timestamp = 10_000);
+        assert_eq!(client.current_price(&listing_id), 400);
+
+        // Buyers pay the price at the time of purchase
+        env.ledger().with_mut(|li| li.timestamp = 5000);
+        assert_eq!(client.preview_fees(&listing_id, &10).price, 7000);
+        client.buy_tokens(&buyer, &listing_id, &10, &usdc);
+        assert_eq!(token_balance(&env, &usdc, &seller), 7000);
+        assert_eq!(client.get_price_history(&token).last().unwrap().price, 7000);
+
+        env.ledger().with_mut(|li| li.timestamp = 9000);
+        client.buy_tokens(&buyer, &listing_id, &20, &usdc);
+        assert_eq!(token_balance(&env, &usdc, &seller), 7000 + 8000);
+        assert_eq!(token_balance(&env, &token, &buyer), 30);
+        assert_eq!(client.calculate_market_cap(), 70 * 400);
+
+        // The schedule sets the price, but tokens can still be added
+        assert_eq!(client.try_update_listing(&listing_id, &Some(500), &0), Err(Ok(Error::InvalidPrice)));
+        client.update_listing(&listing_id, &None, &30);
+        assert_eq!(client.get_listing(&listing_id).amount, 100);
+        assert_eq!(client.best_ask(&token), None);
+
+        // Fixed-price listings sell at their own price
+        let fixed_id = client.list_property(&seller, &token, &10, &600, &None);
+        assert_eq!(client.get_dutch_auction(&fixed_id), None);
+        assert_eq!(client.current_price(&fixed_id), 600);
+
+        // Once the floor has held for its duration the listing expires
+        env.ledger().with_mut(|li| li.timestamp = 10_000);
+        assert_eq!(client.try_buy_tokens(&buyer, &listing_id, &10, &usdc), Err(Ok(Error::ListingExpired)));
+        client.expire_listing(&listing_id);
+        assert_eq!(client.get_listing(&listing_id).status, ListingStatus::Expired);
+        assert_eq!(token_balance(&env, &token, &seller), 1000 - 30 - 10);
+    }
+
+    // Marketplace wired to a real escrow, with a listing of 250 tokens at 2 USDC
+    fn setup_checkout<'a>(
+        env: &Env,
+    ) -> (
+        MarketplaceContractClient<'a>,
+        escrow::EscrowContractClient<'a>,
+        token::StellarAssetClient<'a>,
+        token::StellarAssetClient<'a>,
+        Address,
+        Address,
+        u64,
+    ) {
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(env, &contract_id);
+        let escrow_id = env.register(escrow::EscrowContract, ());
+        let escrow_client = escrow::EscrowContractClient::new(env, &escrow_id);
+
+        let admin = Address::generate(env);
+        let seller = Address::generate(env);
+        let usdc_token = create_token_contract(env, &Address::generate(env));
+        let property_token = create_token_contract(env, &Address::generate(env));
+
+        client.initialize(&admin, &escrow_id, &Address::generate(env));
+        client.set_payment_token(&usdc_token.address);
+        escrow_client.initialize(&admin, &usdc_token.address, &contract_id);
 
         property_token.mint(&seller, &1000_0000000);
+        let listing_id = client.list_property(&seller, &property_token.address, &250_0000000, &2, &None);
 
-        client.initialize(&admin, &escrow, &registry);
+        (client, escrow_client, usdc_token, property_token, admin, seller, listing_id)
+    }
 
-        let amount = 250_0000000;
-        let price_per_token = 2000_0000000;
+    #[test]
+    fn test_reservation_checkout() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, escrow_client, usdc_token, property_token, admin, seller, listing_id) =
+            setup_checkout(&env);
+        let buyer = Address::generate(&env);
+        let other_buyer = Address::generate(&env);
+        usdc_token.mint(&buyer, &1000_0000000);
+
+        assert_eq!(client.try_reserve_tokens(&seller, &listing_id, &1), Err(Ok(Error::SelfTrade)));
+        let reservation_id = client.reserve_tokens(&buyer, &listing_id, &100_0000000);
+        let reservation = client.get_reservation(&reservation_id);
+        assert_eq!(reservation.status, ReservationStatus::Pending);
+        assert_eq!(reservation.price, 200_0000000);
+
+        // Payment sits in escrow, reserved tokens are off the listing
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 800_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &escrow_client.address), 200_0000000);
+        assert_eq!(client.get_listing(&listing_id).amount, 150_0000000);
+        assert_eq!(client.reserved_amount(&listing_id), 100_0000000);
+        assert_eq!(client.calculate_market_cap(), 150_0000000 * 2);
+        assert_eq!(
+            client.try_buy_tokens(&other_buyer, &listing_id, &200_0000000, &usdc_token.address),
+            Err(Ok(Error::InvalidListingAmount))
+        );
+
+        // Only a compliance officer confirms
+        let officer = Address::generate(&env);
+        assert_eq!(
+            client.try_confirm_reservation(&officer, &reservation_id),
+            Err(Ok(Error::NotAuthorized))
+        );
+        client.grant_role(&Role::ComplianceOfficer, &officer);
+        client.confirm_reservation(&officer, &reservation_id);
+
+        assert_eq!(client.get_reservation(&reservation_id).status, ReservationStatus::Confirmed);
+        assert_eq!(client.reserved_amount(&listing_id), 0);
+        assert_eq!(token_balance(&env, &property_token.address, &buyer), 100_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &client.address), 150_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), 200_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &escrow_client.address), 0);
+        assert_eq!(client.get_price_history(&property_token.address).len(), 1);
+        assert_eq!(
+            client.try_confirm_reservation(&admin, &reservation_id),
+            Err(Ok(Error::EscrowNotLocked))
+        );
 
-        let listing_id = client.list_property(&seller, &property_token.address, &amount, &price_per_token);
+        // Fees are fixed at reservation and split on confirmation
+        let treasury = Address::generate(&env);
+        let schedule = FeeSchedule {
+            buyer_fee_bps: 100,
+            seller_fee_bps: 100,
+            min_fee: 0,
+        };
+        client.set_fees(&admin, &schedule, &treasury);
+
+        // Reserving the rest keeps the listing open until confirmed
+        let last_id = client.reserve_tokens(&buyer, &listing_id, &150_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &escrow_client.address), 303_0000000);
+        assert_eq!(client.get_listing(&listing_id).status, ListingStatus::Active);
+        client.set_fees(&admin, &FeeSchedule { seller_fee_bps: 5000, ..schedule }, &treasury);
+        client.confirm_reservation(&admin, &last_id);
+        assert_eq!(client.get_listing(&listing_id).status, ListingStatus::Completed);
+        assert_eq!(token_balance(&env, &property_token.address, &buyer), 250_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 1000_0000000 - 200_0000000 - 303_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &seller), 200_0000000 + 297_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &treasury), 6_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &client.address), 0);
+    }
 
-        // Cancel listing
+    #[test]
+    fn test_reservation_refund() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let (client, escrow_client, usdc_token, property_token, admin, seller, listing_id) =
+            setup_checkout(&env);
+        let buyer = Address::generate(&env);
+        let outsider = Address::generate(&env);
+        usdc_token.mint(&buyer, &1000_0000000);
+
+        // Compliance can unwind a reservation early, nobody else can
+        let first = client.reserve_tokens(&buyer, &listing_id, &100_0000000);
+        assert_eq!(
+            client.try_refund_reservation(&outsider, &first),
+            Err(Ok(Error::NotAuthorized))
+        );
+        client.refund_reservation(&admin, &first);
+        assert_eq!(client.get_reservation(&first).status, ReservationStatus::Refunded);
+        assert_eq!(client.get_listing(&listing_id).amount, 250_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 1000_0000000);
+
+        // After the timeout it can't be confirmed, and anyone can unwind it
+        let second = client.reserve_tokens(&buyer, &listing_id, &100_0000000);
+        env.ledger().with_mut(|li| li.timestamp += reservations::RESERVATION_TIMEOUT);
+        assert_eq!(client.try_confirm_reservation(&admin, &second), Err(Ok(Error::ListingExpired)));
+        client.refund_reservation(&outsider, &second);
+        assert_eq!(client.get_listing(&listing_id).amount, 250_0000000);
+        assert_eq!(client.reserved_amount(&listing_id), 0);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 1000_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &escrow_client.address), 0);
+
+        // The buyer may reclaim the payment from escrow first
+        let third = client.reserve_tokens(&buyer, &listing_id, &50_0000000);
+        env.ledger().with_mut(|li| li.timestamp += reservations::RESERVATION_TIMEOUT);
+        let escrow_id = client.get_reservation(&third).escrow_id;
+        escrow_client.refund_to_buyer(&buyer, &escrow_id);
+        client.refund_reservation(&outsider, &third);
+        assert_eq!(client.get_listing(&listing_id).amount, 250_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 1000_0000000);
+
+        // Tokens reserved from a cancelled listing go back to the seller
+        let fourth = client.reserve_tokens(&buyer, &listing_id, &50_0000000);
         client.cancel_listing(&listing_id);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 950_0000000);
+        client.refund_reservation(&admin, &fourth);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 1000_0000000);
+        assert_eq!(token_balance(&env, &property_token.address, &client.address), 0);
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 1000_0000000);
+        assert_eq!(client.get_price_history(&property_token.address).len(), 0);
+    }
 
-        // Check listing status
-        let listing = client.get_listing(&listing_id);
-        assert_eq!(listing.status, ListingStatus::Cancelled);
+    #[test]
+    fn test_buy_respects_holder_cap() {
+        use property_token::{PropertyTokenContract, PropertyTokenContractClient};
+        use soroban_sdk::String;
 
-        // Check tokens returned to seller
-        assert_eq!(property_token.balance(&seller), 1000_0000000);
-        assert_eq!(property_token.balance(&contract_id), 0);
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+
+        // Property token capped at 5% per holder, marketplace exempt
+        let token_id = env.register(PropertyTokenContract, ());
+        let property_token = PropertyTokenContractClient::new(&env, &token_id);
+        property_token.initialize(
+            &admin,
+            &Symbol::new(&env, "PROP001"),
+            &String::from_str(&env, "Property Token"),
+            &String::from_str(&env, "PROP"),
+            &1000_0000000,
+        );
+        property_token.set_holder_cap(&admin, &500);
+        property_token.set_cap_exempt(&admin, &contract_id, &true);
+
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        client.set_payment_token(&usdc_token.address);
+
+        let listing_id = client.list_property(&admin, &token_id, &100_0000000, &1, &None);
+
+        usdc_token.mint(&buyer, &100_0000000);
+        token::TokenClient::new(&env, &usdc_token.address).approve(
+            &buyer,
+            &contract_id,
+            &100_0000000,
+            &(env.ledger().sequence() + 100),
+        );
+
+        client.buy_tokens(&buyer, &listing_id, &50_0000000, &usdc_token.address);
+        assert_eq!(property_token.balance(&buyer), 50_0000000);
+
+        assert_eq!(token_balance(&env, &usdc_token.address, &admin), 50_0000000);
+
+        // A rejected delivery also reverts the payment
+        let result = client.try_buy_tokens(&buyer, &listing_id, &1, &usdc_token.address);
+        assert_eq!(result, Err(Ok(Error::HolderCapExceeded)));
+        assert_eq!(token_balance(&env, &usdc_token.address, &buyer), 50_0000000);
+        assert_eq!(token_balance(&env, &usdc_token.address, &admin), 50_0000000);
     }
 
     #[test]
-    fn test_get_listings_by_token() {
+    fn test_listed_tokens_earn_no_distribution() {
+        use property_token::{PropertyTokenContract, PropertyTokenContractClient};
+        use soroban_sdk::String;
+
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, MarketplaceContract);
+        let contract_id = env.register(MarketplaceContract, ());
         let client = MarketplaceContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
         let seller = Address::generate(&env);
-        let escrow = Address::generate(&env);
-        let registry = Address::generate(&env);
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+        client.set_payment_token(&create_token_contract(&env, &Address::generate(&env)).address);
+
+        let token_id = env.register(PropertyTokenContract, ());
+        let property_token = PropertyTokenContractClient::new(&env, &token_id);
+        property_token.initialize(
+            &admin,
+            &Symbol::new(&env, "PROP001"),
+            &String::from_str(&env, "Property Token"),
+            &String::from_str(&env, "PROP"),
+            &1000,
+        );
+        property_token.set_distribution_custodian(&contract_id, &true);
+        property_token.transfer(&admin, &seller, &200);
 
-        let token_admin = Address::generate(&env);
-        let property_token = create_token_contract(&env, &token_admin);
+        // Half of the seller's tokens sit with the marketplace
+        client.list_property(&seller, &token_id, &100, &5, &None);
+        assert_eq!(property_token.balance(&contract_id), 100);
 
-        property_token.mint(&seller, &1000_0000000);
+        let rent_token = create_token_contract(&env, &Address::generate(&env));
+        rent_token.mint(&admin, &900);
+        property_token.deposit_distribution(&rent_token.address, &900, &1);
 
-        client.initialize(&admin, &escrow, &registry);
+        // The income is split over the 900 tokens held outside custody
+        assert_eq!(property_token.claimable_distribution(&admin, &rent_token.address), 800);
+        assert_eq!(property_token.claimable_distribution(&seller, &rent_token.address), 100);
+        assert_eq!(property_token.claimable_distribution(&contract_id, &rent_token.address), 0);
+    }
 
-        // Create multiple listings
-        client.list_property(&seller, &property_token.address, &100_0000000, &1000_0000000);
-        client.list_property(&seller, &property_token.address, &200_0000000, &1500_0000000);
+    #[test]
+    fn test_trades_sync_registry_ownership() {
+        use property_token::{PropertyTokenContract, PropertyTokenContractClient};
+        use registry::{OwnershipRegistryContract, OwnershipRegistryContractClient};
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal, String};
 
-        // Get listings for this token
-        let listings = client.get_listings(&property_token.address);
-        assert_eq!(listings.len(), 2);
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
+        let registry_id = env.register(OwnershipRegistryContract, ());
+        let registry = OwnershipRegistryContractClient::new(&env, &registry_id);
+
+        let admin = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let property_id = Symbol::new(&env, "PROP001");
+
+        let token_id = env.register(PropertyTokenContract, ());
+        let property_token = PropertyTokenContractClient::new(&env, &token_id);
+        property_token.initialize(
+            &admin,
+            &property_id,
+            &String::from_str(&env, "Property Token"),
+            &String::from_str(&env, "PROP"),
+            &1000_0000000,
+        );
+
+        registry.initialize(&admin);
+        registry.register_property(
+            &property_id,
+            &admin,
+            &String::from_str(&env, "Address"),
+            &5_000_000_0000000,
+            &String::from_str(&env, "LEGAL-001"),
+            &token_id,
+        );
+        registry.set_marketplace(&contract_id);
+
+        let usdc_token = create_token_contract(&env, &Address::generate(&env));
+        client.initialize(&admin, &Address::generate(&env), &registry_id);
+        client.set_payment_token(&usdc_token.address);
+
+        usdc_token.mint(&buyer, &1000_0000000);
+        token::TokenClient::new(&env, &usdc_token.address).approve(
+            &buyer,
+            &contract_id,
+            &1000_0000000,
+            &(env.ledger().sequence() + 100),
+        );
+
+        let listing_id = client.list_property(&admin, &token_id, &400_0000000, &1, &None);
+        client.buy_tokens(&buyer, &listing_id, &100_0000001, &usdc_token.address);
+
+        // Shares follow token balances; tokens in custody aren't anyone's share
+        let owners = registry.get_property_owners(&property_id, &0, &10);
+        assert_eq!(owners.len(), 2);
+        assert_eq!(registry.verify_ownership(&property_id, &admin), (true, 6000));
+        assert_eq!(registry.verify_ownership(&property_id, &buyer), (true, 1000));
+        assert_eq!(registry.verify_ownership(&property_id, &contract_id), (false, 0));
+
+        client.buy_tokens(&buyer, &listing_id, &299_9999999, &usdc_token.address);
+        let owners = registry.get_property_owners(&property_id, &0, &10);
+        assert_eq!(owners.len(), 2);
+        assert_eq!(registry.verify_ownership(&property_id, &admin), (true, 6000));
+        assert_eq!(registry.verify_ownership(&property_id, &buyer), (true, 4000));
+
+        // A token claiming the same property id can't rewrite its owners
+        let fake_id = env.register(PropertyTokenContract, ());
+        let fake_token = PropertyTokenContractClient::new(&env, &fake_id);
+        fake_token.initialize(
+            &buyer,
+            &property_id,
+            &String::from_str(&env, "Fake Token"),
+            &String::from_str(&env, "FAKE"),
+            &10_0000000,
+        );
+        usdc_token.mint(&admin, &20_0000000);
+        token::TokenClient::new(&env, &usdc_token.address).approve(
+            &admin,
+            &contract_id,
+            &20_0000000,
+            &(env.ledger().sequence() + 100),
+        );
+        let fake_listing = client.list_property(&buyer, &fake_id, &10_0000000, &1, &None);
+        client.buy_tokens(&admin, &fake_listing, &10_0000000, &usdc_token.address);
+        assert_eq!(registry.get_property_owners(&property_id, &0, &10), owners);
+
+        // Holders beyond the registry's per-update cap don't hold up trading
+        for _ in 0..MAX_SYNCED_OWNERS {
+            property_token.transfer(&admin, &Address::generate(&env), &1);
+        }
+        assert_eq!(property_token.holder_count(), MAX_SYNCED_OWNERS + 2);
+        let listing_id = client.list_property(&admin, &token_id, &10_0000000, &1, &None);
+        client.buy_tokens(&buyer, &listing_id, &10_0000000, &usdc_token.address);
+        assert_eq!(registry.verify_ownership(&property_id, &admin), (true, 5899));
+        assert_eq!(registry.verify_ownership(&property_id, &buyer), (true, 4100));
+
+        // A registry left behind by an outside transfer doesn't block the trade
+        let holder = Address::generate(&env);
+        property_token.transfer(&buyer, &holder, &410_0000000);
+        let listing_id = client.list_property(&holder, &token_id, &10_0000000, &1, &None);
+        client.buy_tokens(&admin, &listing_id, &10_0000000, &usdc_token.address);
+        let sync_failed = (
+            contract_id.clone(),
+            (symbol_short!("reg_fail"), property_id.clone()).into_val(&env),
+            vec![&env, admin.clone(), holder.clone()].into_val(&env),
+        );
+        assert!(env.events().all().contains(&sync_failed));
+        assert_eq!(property_token.balance(&holder), 400_0000000);
+        assert_eq!(registry.verify_ownership(&property_id, &admin), (true, 5899));
+
+        // Anyone can bring it back in line
+        client.sync_registry(&token_id, &vec![&env, buyer.clone(), holder.clone(), admin.clone()]);
+        assert_eq!(registry.verify_ownership(&property_id, &buyer), (true, 0));
+        assert_eq!(registry.verify_ownership(&property_id, &holder), (true, 4000));
+        assert_eq!(registry.verify_ownership(&property_id, &admin), (true, 5999));
+        assert_eq!(
+            client.try_sync_registry(&token_id, &Vec::new(&env)),
+            Err(Ok(Error::InvalidAmount))
+        );
     }
 
     #[test]
-    fn test_calculate_market_cap() {
+    fn test_migrate_requires_payment_token() {
+        use soroban_sdk::vec;
+
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, MarketplaceContract);
+        let contract_id = env.register(MarketplaceContract, ());
         let client = MarketplaceContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
         let seller = Address::generate(&env);
-        let escrow = Address::generate(&env);
-        let registry = Address::generate(&env);
+        let property_token = Address::generate(&env);
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+
+        // Version 1 state: one listing, a trade on it and one on a listing that is gone
+        let old_listing = ListingV1 {
+            id: 0,
+            seller: seller.clone(),
+            token: property_token.clone(),
+            amount: 50,
+            price_per_token: 20,
+            status: ListingStatus::Active,
+            created_at: 1000,
+        };
+        let lost_trade = TradeV1 {
+            listing_id: 7,
+            buyer: admin.clone(),
+            seller: seller.clone(),
+            amount: 5,
+            price: 100,
+            timestamp: 1500,
+        };
+        let old_trade = TradeV1 { listing_id: 0, timestamp: 2000, ..lost_trade.clone() };
+        env.as_contract(&contract_id, || {
+            storage::set_persistent(&env, &DataKey::Listing(0), &old_listing);
+            storage::set_persistent(&env, &DataKey::ListingCounter, &1u64);
+            storage::set_persistent(&env, &migration::trade_key(&env), &vec![&env, lost_trade.clone(), old_trade.clone()]);
+            env.storage().instance().remove(&DataKey::SchemaVersion);
+        });
+
+        // Old listings can't be read without the token they settle in
+        assert_eq!(client.try_migrate(&0, &10), Err(Ok(Error::NotInitialized)));
+        assert_eq!(client.schema_version(), 1);
+        env.as_contract(&contract_id, || {
+            let raw: Option<ListingV1> = storage::get_persistent(&env, &DataKey::Listing(0));
+            assert_eq!(raw, Some(old_listing.clone()));
+            let history: Option<Vec<TradeV1>> = storage::get_persistent(&env, &migration::trade_key(&env));
+            assert_eq!(history.unwrap().len(), 2);
+        });
+
+        let usdc_token = create_token_contract(&env, &admin);
+        client.set_payment_token(&usdc_token.address);
+        assert_eq!(client.migrate(&0, &10), Some(1));
+        assert_eq!(client.get_listing(&0).payment_token, usdc_token.address);
+        assert_eq!(client.trade_count(&property_token), 1);
+
+        // A trade whose listing is gone stays in the old history
+        assert_eq!(client.try_migrate(&1, &10), Err(Ok(Error::ListingNotFound)));
+        assert_eq!(client.schema_version(), 1);
+        env.as_contract(&contract_id, || {
+            let history: Option<Vec<TradeV1>> = storage::get_persistent(&env, &migration::trade_key(&env));
+            assert_eq!(history, Some(vec![&env, lost_trade.clone()]));
+        });
+    }
+
+    #[test]
+    fn test_upgrade_and_migrate_listings() {
+        use soroban_sdk::{symbol_short, testutils::Events, vec, IntoVal};
+
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(MarketplaceContract, ());
+        let client = MarketplaceContractClient::new(&env, &contract_id);
 
+        let admin = Address::generate(&env);
+        let seller = Address::generate(&env);
         let token_admin = Address::generate(&env);
         let property_token = create_token_contract(&env, &token_admin);
-
         property_token.mint(&seller, &1000_0000000);
 
-        client.initialize(&admin, &escrow, &registry);
+        client.initialize(&admin, &Address::generate(&env), &Address::generate(&env));
+        assert_eq!(client.schema_version(), migration::SCHEMA_VERSION);
+        let usdc_token = create_token_contract(&env, &token_admin);
+        client.set_payment_token(&usdc_token.address);
 
-        // List tokens
-        let amount1 = 100_0000000;
-        let price1 = 1000_0000000;
-        let amount2 = 200_0000000;
-        let price2 = 1500_0000000;
+        // Live listing in the current layout
+        let listing_id = client.list_property(&seller, &property_token.address, &100_0000000, &10_0000000, &None);
+
+        // Listings left behind by a version 1 deployment
+        let old_listing = ListingV1 {
+            id: 1,
+            seller: seller.clone(),
+            token: property_token.address.clone(),
+            amount: 50_0000000,
+            price_per_token: 20_0000000,
+            status: ListingStatus::Active,
+            created_at: 1000,
+        };
+        let old_listing_2 = ListingV1 {
+            id: 2,
+            amount: 30_0000000,
+            price_per_token: 10_0000000,
+            created_at: 2000,
+            ..old_listing.clone()
+        };
+        // And its trade history
+        let old_trade = TradeV1 {
+            listing_id: 2,
+            buyer: admin.clone(),
+            seller: seller.clone(),
+            amount: 10_0000000,
+            price: 100_0000000,
+            timestamp: 2500,
+        };
+        let old_trade_2 = TradeV1 {
+            listing_id: 1,
+            amount: 5_0000000,
+            price: 25_0000000,
+            timestamp: 4500,
+            ..old_trade.clone()
+        };
+        let old_trade_3 = TradeV1 {
+            listing_id,
+            amount: 10_0000000,
+            price: 60_0000000,
+            timestamp: 5000,
+            ..old_trade.clone()
+        };
+        let old_trades = vec![&env, old_trade.clone(), old_trade_2.clone(), old_trade_3.clone()];
+        property_token.mint(&contract_id, &80_0000000);
+        env.as_contract(&contract_id, || {
+            storage::set_persistent(&env, &DataKey::Listing(1), &old_listing);
+            storage::set_persistent(&env, &DataKey::Listing(2), &old_listing_2);
+            storage::set_persistent(&env, &DataKey::ListingCounter, &3u64);
+            storage::set_persistent(&env, &migration::trade_key(&env), &old_trades);
+            env.storage().instance().remove(&DataKey::SchemaVersion);
+        });
+        assert_eq!(client.schema_version(), 1);
+
+        let wasm_hash = stellar_property_access::testutils::upload_upgrade_wasm(&env);
+        client.upgrade(&wasm_hash);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("upgrade"), admin.clone()).into_val(&env),
+                    wasm_hash.to_array().into_val(&env),
+                ),
+            ]
+        );
 
-        client.list_property(&seller, &property_token.address, &amount1, &price1);
-        client.list_property(&seller, &property_token.address, &amount2, &price2);
+        // Run this crate's code as the new version at the same address
+        env.register_at(&contract_id, MarketplaceContract, ());
+
+        // Old entries are readable before they are rewritten
+        let converted = client.get_listing(&1);
+        assert_eq!(converted.amount, 50_0000000);
+        assert_eq!(converted.updated_at, 1000);
+        assert_eq!(converted.payment_token, usdc_token.address);
+        assert_eq!(converted.expires_at, None);
+        let converted_2 = client.get_listing(&2);
+        assert_eq!(converted_2.updated_at, 2000);
+        assert_eq!(converted_2.payment_token, usdc_token.address);
+        // Older listings are indexed by the migration
+        assert_eq!(client.calculate_market_cap(), 100_0000000 * 10_0000000);
+        assert_eq!(client.get_listings(&property_token.address, &0, &10).listings.len(), 1);
+        assert_eq!(client.best_ask(&property_token.address), Some(10_0000000));
+
+        // New trades go straight to the token's ledger
+        env.ledger().with_mut(|li| li.timestamp = 6000);
+        usdc_token.mint(&admin, &(10_0000000 * 10_0000000));
+        token::TokenClient::new(&env, &usdc_token.address)
+            .approve(&admin, &contract_id, &(10_0000000 * 10_0000000), &1000);
+        client.buy_tokens(&admin, &listing_id, &10_0000000, &usdc_token.address);
+        assert_eq!(client.trade_count(&property_token.address), 1);
+        let live_trade = client.get_price_history(&property_token.address).get(0).unwrap();
+        assert_eq!((live_trade.listing_id, live_trade.timestamp), (Some(listing_id), 6000));
+
+        assert_eq!(client.try_migrate(&0, &0), Err(Ok(Error::InvalidAmount)));
+        assert_eq!(client.migrate(&0, &1), Some(1));
+        assert_eq!(client.schema_version(), 1);
+
+        // Old trades move once every listing is converted, newest first
+        assert_eq!(client.migrate(&1, &2), Some(3));
+        assert_eq!(client.trade_count(&property_token.address), 3);
+        assert_eq!(
+            client.get_price_history(&property_token.address),
+            vec![
+                &env,
+                old_trade_2.clone().of_token(property_token.address.clone()),
+                old_trade_3.clone().of_token(property_token.address.clone()),
+                live_trade.clone(),
+            ]
+        );
+        assert_eq!(client.schema_version(), 1);
+        assert_eq!(client.migrate(&3, &2), None);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    contract_id.clone(),
+                    (symbol_short!("migrate"),).into_val(&env),
+                    (1u32, migration::SCHEMA_VERSION).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.schema_version(), migration::SCHEMA_VERSION);
 
-        // Calculate market cap
-        let market_cap = client.calculate_market_cap();
-        let expected = (amount1 * price1) + (amount2 * price2);
-        assert_eq!(market_cap, expected);
+        // Older listings now rest on the order book and count in the indexes
+        assert_eq!(
+            client.calculate_market_cap(),
+            90_0000000 * 10_0000000 + 50_0000000 * 20_0000000 + 30_0000000 * 10_0000000
+        );
+        assert_eq!(client.market_cap(&property_token.address), client.calculate_market_cap());
+        assert_eq!(client.get_seller_listings(&seller, &0, &10).listings.len(), 3);
+        assert_eq!(client.best_ask(&property_token.address), Some(10_0000000));
+        assert_eq!(client.market_depth(&property_token.address, &10).asks.len(), 2);
+
+        // Old trades are in the token's ledger in the order they happened
+        let converted_trades = client.get_price_history(&property_token.address);
+        assert_eq!(
+            converted_trades,
+            vec![
+                &env,
+                old_trade.of_token(property_token.address.clone()),
+                old_trade_2.of_token(property_token.address.clone()),
+                old_trade_3.of_token(property_token.address.clone()),
+                live_trade,
+            ]
+        );
+        assert_eq!(converted_trades.get(0).unwrap().buyer_fee, 0);
+        assert_eq!(converted_trades.get(1).unwrap().listing_id, Some(1));
+        assert_eq!(converted_trades.get(1).unwrap().bid_id, None);
+
+        // And built its candles
+        let day = client.get_candle(&property_token.address, &Interval::Day, &0).unwrap();
+        assert_eq!((day.open, day.high, day.low, day.close), (10, 10_0000000, 5, 10_0000000));
+        assert_eq!((day.volume, day.trades), (35_0000000, 4));
+        let hours = client.get_candles(&property_token.address, &Interval::Hour, &0, &7200);
+        assert_eq!(hours.len(), 2);
+        let hour = hours.get(1).unwrap();
+        assert_eq!((hour.start, hour.open, hour.close, hour.trades), (3600, 5, 10_0000000, 3));
+
+        env.as_contract(&contract_id, || {
+            let stored: Listing = storage::get_persistent(&env, &DataKey::Listing(1)).unwrap();
+            assert_eq!(stored, converted);
+            let stored: Listing = storage::get_persistent(&env, &DataKey::Listing(2)).unwrap();
+            assert_eq!(stored, converted_2);
+            assert!(!storage::has_persistent(&env, &migration::trade_key(&env)));
+        });
+
+        // All listings keep working after the migration
+        assert_eq!(client.get_listing(&listing_id).amount, 90_0000000);
+        client.cancel_listing(&1);
+        client.cancel_listing(&2);
+        assert_eq!(token_balance(&env, &property_token.address, &seller), 980_0000000);
     }
 }
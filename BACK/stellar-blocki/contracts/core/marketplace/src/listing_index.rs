@@ -0,0 +1,126 @@
+// Active listing index
+//
+// Active listings are kept in indexed sets per token and per seller, like
+// the holder index of property-token: `At(set, i)` maps positions to listing
+// ids and `Slot(set, id)` maps back, and removal swaps the last listing into
+// the freed slot. Each indexed listing also records the value it adds to its
+// token's market cap (amount times price, the floor price for Dutch
+// auctions), so the token and total aggregates move by the difference
+// whenever a listing is saved.
+
+use soroban_sdk::{contracttype, Address, Env};
+use stellar_property_storage as storage;
+
+use crate::{dutch, Listing, ListingStatus};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum ListingSet {
+    Token(Address),
+    Seller(Address),
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum IndexKey {
+    Count(ListingSet),
+    At(ListingSet, u32),
+    Slot(ListingSet, u64),
+    Value(u64),                    // Market value of an indexed listing
+    TokenValue(Address),
+    TotalValue,
+}
+
+/// Number of active listings in a set
+pub fn count(env: &Env, set: &ListingSet) -> u32 {
+    storage::get_persistent(env, &IndexKey::Count(set.clone())).unwrap_or(0)
+}
+
+pub fn listing_at(env: &Env, set: &ListingSet, index: u32) -> Option<u64> {
+    storage::get_persistent(env, &IndexKey::At(set.clone(), index))
+}
+
+fn add(env: &Env, set: &ListingSet, listing_id: u64) {
+    let index = count(env, set);
+    storage::set_persistent(env, &IndexKey::At(set.clone(), index), &listing_id);
+    storage::set_persistent(env, &IndexKey::Slot(set.clone(), listing_id), &index);
+    storage::set_persistent(env, &IndexKey::Count(set.clone()), &(index + 1));
+}
+
+fn remove(env: &Env, set: &ListingSet, listing_id: u64) {
+    let index: u32 = match storage::get_persistent(env, &IndexKey::Slot(set.clone(), listing_id)) {
+        Some(index) => index,
+        None => return,
+    };
+
+    // Move the last listing into the freed slot
+    let last_index = count(env, set) - 1;
+    if index != last_index {
+        let last = listing_at(env, set, last_index).unwrap();
+        storage::set_persistent(env, &IndexKey::At(set.clone(), index), &last);
+        storage::set_persistent(env, &IndexKey::Slot(set.clone(), last), &index);
+    }
+
+    storage::remove_persistent(env, &IndexKey::At(set.clone(), last_index));
+    storage::remove_persistent(env, &IndexKey::Slot(set.clone(), listing_id));
+    storage::set_persistent(env, &IndexKey::Count(set.clone()), &last_index);
+}
+
+/// Value of the active listings of `token`
+pub fn token_value(env: &Env, token: &Address) -> i128 {
+    storage::get_persistent(env, &IndexKey::TokenValue(token.clone())).unwrap_or(0)
+}
+
+/// Value of all active listings
+pub fn total_value(env: &Env) -> i128 {
+    storage::get_persistent(env, &IndexKey::TotalValue).unwrap_or(0)
+}
+
+fn adjust_value(env: &Env, token: &Address, change: i128) {
+    if change == 0 {
+        return;
+    }
+
+    let token_value = token_value(env, token).saturating_add(change);
+    if token_value == 0 {
+        storage::remove_persistent(env, &IndexKey::TokenValue(token.clone()));
+    } else {
+        storage::set_persistent(env, &IndexKey::TokenValue(token.clone()), &token_value);
+    }
+    storage::set_persistent(env, &IndexKey::TotalValue, &total_value(env).saturating_add(change));
+}
+
+fn value_of(env: &Env, listing: &Listing) -> i128 {
+    let price = dutch::read(env, listing.id)
+        .map_or(listing.price_per_token, |auction| auction.floor_price);
+    listing.amount.saturating_mul(price)
+}
+
+/// Keep the indexes and market value in sync with a saved listing
+pub fn sync(env: &Env, listing: &Listing) {
+    let key = IndexKey::Value(listing.id);
+    let old_value: Option<i128> = storage::get_persistent(env, &key);
+    let new_value = (listing.status == ListingStatus::Active).then(|| value_of(env, listing));
+
+    match (old_value, new_value) {
+        (None, Some(value)) => {
+            add(env, &ListingSet::Token(listing.token.clone()), listing.id);
+            add(env, &ListingSet::Seller(listing.seller.clone()), listing.id);
+            storage::set_persistent(env, &key, &value);
+            adjust_value(env, &listing.token, value);
+        }
+        (Some(old), Some(value)) => {
+            if value != old {
+                storage::set_persistent(env, &key, &value);
+                adjust_value(env, &listing.token, value - old);
+            }
+        }
+        (Some(old), None) => {
+            remove(env, &ListingSet::Token(listing.token.clone()), listing.id);
+            remove(env, &ListingSet::Seller(listing.seller.clone()), listing.id);
+            storage::remove_persistent(env, &key);
+            adjust_value(env, &listing.token, -old);
+        }
+        (None, None) => {}
+    }
+}
@@ -0,0 +1,182 @@
+// Storage layout versions
+//
+//   1 - initial layout
+//   2 - `Listing` records its payment token, last update and expiry; `Trade`
+//       records its token, the bid it filled and the fees paid. Active
+//       listings rest in the order book and the listing indexes, trades are
+//       kept in per-token ledgers with candles
+//
+// Listings stored by version 1 are converted when read, so the contract keeps
+// working right after an upgrade. `migrate` rewrites them in batches, puts
+// active ones on the order book and in the listing indexes, then moves the
+// old trade history, and records the new version once both are done. Old
+// listings settle in the marketplace payment token, so they can only be read
+// once the admin has set it, and never expire; `migrate` refuses to run
+// before then.
+//
+// Version 1 kept every trade in one history entry. Trades made after the
+// upgrade go straight to the ledgers; the old ones are moved newest first in
+// front of them, so each ledger stays in the order trades happened. Their
+// token is that of the listing they filled, and they paid no fees. A trade
+// whose listing can't be read stays in the old history and stops the move.
+
+use soroban_sdk::{contracttype, Address, Env, Map, Symbol, TryFromVal, Val, Vec};
+use stellar_property_errors::MarketplaceError as Error;
+use stellar_property_events as events;
+use stellar_property_storage::{self as storage, DataKey};
+
+use crate::{dutch, listing_index, trades};
+use crate::orderbook::{self, Side};
+use crate::{Listing, ListingStatus, Trade};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+// SECURITY: Maximum listings or trades converted per call, keeps each call within budget
+pub const MAX_MIGRATION_BATCH: u32 = 100;
+
+/// `Listing` as stored by schema version 1
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ListingV1 {
+    pub id: u64,
+    pub seller: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub price_per_token: i128,
+    pub status: ListingStatus,
+    pub created_at: u64,
+}
+
+impl ListingV1 {
+    fn settled_in(self, payment_token: Address) -> Listing {
+        Listing {
+            id: self.id,
+            seller: self.seller,
+            token: self.token,
+            payment_token,
+            amount: self.amount,
+            price_per_token: self.price_per_token,
+            status: self.status,
+            created_at: self.created_at,
+            updated_at: self.created_at,
+            expires_at: None,
+        }
+    }
+}
+
+// Returns the listing and whether it is stored in an old layout
+fn load(env: &Env, listing_id: u64) -> Option<(Listing, bool)> {
+    let raw: Map<Symbol, Val> = storage::get_persistent(env, &DataKey::Listing(listing_id))?;
+    let val = raw.to_val();
+
+    if raw.contains_key(Symbol::new(env, "payment_token")) {
+        return Listing::try_from_val(env, &val).ok().map(|listing| (listing, false));
+    }
+
+    let old = ListingV1::try_from_val(env, &val).ok()?;
+    let payment_token = crate::configured_payment_token(env)?;
+    Some((old.settled_in(payment_token), true))
+}
+
+/// Read a listing written by any schema version
+pub fn read_listing(env: &Env, listing_id: u64) -> Option<Listing> {
+    load(env, listing_id).map(|(listing, _)| listing)
+}
+
+/// Rewrite listings `[start, end)` that still use an old layout and put the
+/// active ones on the order book and in the listing indexes
+pub fn migrate_listings(env: &Env, start: u64, end: u64) {
+    for listing_id in start..end {
+        let Some((listing, outdated)) = load(env, listing_id) else {
+            continue;
+        };
+
+        if outdated {
+            storage::set_persistent(env, &DataKey::Listing(listing_id), &listing);
+        }
+        listing_index::sync(env, &listing);
+
+        // Listings past the book cap stay buyable directly
+        if listing.status == ListingStatus::Active
+            && dutch::read(env, listing_id).is_none()
+            && !orderbook::contains(env, Side::Ask, &listing.token, listing_id)
+        {
+            let _ = orderbook::insert(env, Side::Ask, &listing.token, listing_id, listing.price_per_token);
+        }
+    }
+}
+
+/// `Trade` as stored by schema version 1
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TradeV1 {
+    pub listing_id: u64,
+    pub buyer: Address,
+    pub seller: Address,
+    pub amount: i128,
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+impl TradeV1 {
+    // Trades of version 1 filled a listing and paid no fees
+    pub fn of_token(self, token: Address) -> Trade {
+        Trade {
+            token,
+            listing_id: Some(self.listing_id),
+            bid_id: None,
+            buyer: self.buyer,
+            seller: self.seller,
+            amount: self.amount,
+            price: self.price,
+            buyer_fee: 0,
+            seller_fee: 0,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// Move up to `limit` of the newest trades of the old history to the front
+/// of the per-token ledgers, returns whether any are left
+pub fn migrate_trades(env: &Env, limit: u32) -> Result<bool, Error> {
+    let history: Vec<TradeV1> = match storage::get_persistent(env, &trade_key(env)) {
+        Some(history) => history,
+        None => return Ok(false),
+    };
+
+    let batch_start = history.len() - limit.min(history.len());
+    let mut kept = history.len();
+    while kept > batch_start {
+        let old = history.get_unchecked(kept - 1);
+        let Some(listing) = read_listing(env, old.listing_id) else {
+            break;
+        };
+        trades::archive(env, &old.of_token(listing.token));
+        kept -= 1;
+    }
+
+    if kept == history.len() {
+        return Err(Error::ListingNotFound);
+    }
+
+    if kept > 0 {
+        storage::set_persistent(env, &trade_key(env), &history.slice(..kept));
+        return Ok(true);
+    }
+
+    storage::remove_persistent(env, &trade_key(env));
+    Ok(false)
+}
+
+pub fn trade_key(env: &Env) -> DataKey {
+    DataKey::PropertyData(Symbol::new(env, "trade"))
+}
+
+/// Record that storage now follows the current layout
+pub fn finish(env: &Env) {
+    let from_version = storage::get_schema_version(env);
+    if from_version != SCHEMA_VERSION {
+        storage::set_schema_version(env, SCHEMA_VERSION);
+        events::schema_migrated(env, from_version, SCHEMA_VERSION);
+    }
+}
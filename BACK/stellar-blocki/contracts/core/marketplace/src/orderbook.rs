@@ -0,0 +1,181 @@
+// Limit order book
+//
+// Bids are buy orders whose payment is held by this contract until they fill
+// or are cancelled. The open listings (asks) and bids of each token are kept
+// sorted best price first, oldest first within a price, so matching and the
+// market views only read the front of each side. A bid holds its buyer fee
+// from the moment it is placed and pays it out pro rata as it fills.
+//
+// Each side holds a bounded number of orders. On a full side a better priced
+// order takes the place of the worst one, which stays open off the book: an
+// evicted listing can still be bought and an evicted bid still filled or
+// cancelled by id, they just no longer match new orders.
+
+use soroban_sdk::{contracttype, Address, Env, Vec};
+use stellar_property_errors::MarketplaceError as Error;
+use stellar_property_storage as storage;
+
+// SECURITY: Maximum resting orders per side of a token's book, bounds matching
+pub const MAX_BOOK_ORDERS: u32 = 100;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BidStatus {
+    Open,
+    Filled,
+    Cancelled,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Bid {
+    pub id: u64,
+    pub buyer: Address,
+    pub token: Address,            // Property token wanted
+    pub payment_token: Address,    // Asset the bid pays in (USDC)
+    pub amount: i128,              // Tokens still wanted
+    pub price_per_token: i128,     // Highest price the buyer pays per token
+    pub buyer_fee: i128,           // Buyer fee still held for the unfilled amount
+    pub status: BidStatus,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Bid {
+    /// Payment held for the unfilled amount, including its buyer fee
+    pub fn escrowed(&self) -> i128 {
+        self.amount * self.price_per_token + self.buyer_fee
+    }
+
+    /// Buyer fee owed for filling `amount` of the bid
+    pub fn fee_share(&self, amount: i128) -> Result<i128, Error> {
+        if amount == self.amount {
+            return Ok(self.buyer_fee);
+        }
+
+        Ok(self.buyer_fee.checked_mul(amount).ok_or(Error::InvalidAmount)? / self.amount)
+    }
+}
+
+/// A resting order's place in the book
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BookEntry {
+    pub id: u64,                   // Listing id for asks, bid id for bids
+    pub price: i128,
+}
+
+/// Open amount at one price
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceLevel {
+    pub price: i128,
+    pub amount: i128,
+    pub orders: u32,
+}
+
+/// Best price levels on each side of a token's book
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarketDepth {
+    pub bids: Vec<PriceLevel>,     // Highest price first
+    pub asks: Vec<PriceLevel>,     // Lowest price first
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum BookKey {
+    Bid(u64),
+    BidCounter,
+    Bids(Address),
+    Asks(Address),
+}
+
+fn book_key(side: Side, token: &Address) -> BookKey {
+    match side {
+        Side::Bid => BookKey::Bids(token.clone()),
+        Side::Ask => BookKey::Asks(token.clone()),
+    }
+}
+
+pub fn next_bid_id(env: &Env) -> u64 {
+    storage::get_and_increment_counter(env, BookKey::BidCounter)
+}
+
+pub fn read_bid(env: &Env, bid_id: u64) -> Option<Bid> {
+    storage::get_persistent(env, &BookKey::Bid(bid_id))
+}
+
+pub fn write_bid(env: &Env, bid: &Bid) {
+    storage::set_persistent(env, &BookKey::Bid(bid.id), bid);
+}
+
+/// Resting orders on one side of a token's book, best first
+pub fn entries(env: &Env, side: Side, token: &Address) -> Vec<BookEntry> {
+    storage::get_persistent(env, &book_key(side, token)).unwrap_or(Vec::new(env))
+}
+
+/// Rest an order behind every order at the same or a better price
+/// Returns the id of the order it pushed off a full side; an order no better
+/// than the worst on a full side fails with `OrderBookFull`
+pub fn insert(env: &Env, side: Side, token: &Address, id: u64, price: i128) -> Result<Option<u64>, Error> {
+    let mut book = entries(env, side, token);
+
+    let mut index = book.len();
+    for (i, entry) in book.iter().enumerate() {
+        let behind = match side {
+            Side::Bid => price > entry.price,
+            Side::Ask => price < entry.price,
+        };
+        if behind {
+            index = i as u32;
+            break;
+        }
+    }
+
+    let mut evicted = None;
+    if book.len() >= MAX_BOOK_ORDERS {
+        if index >= book.len() {
+            return Err(Error::OrderBookFull);
+        }
+        evicted = book.pop_back().map(|entry| entry.id);
+    }
+
+    book.insert(index, BookEntry { id, price });
+    storage::set_persistent(env, &book_key(side, token), &book);
+    Ok(evicted)
+}
+
+/// Take an order off the book, if it rests there
+pub fn remove(env: &Env, side: Side, token: &Address, id: u64) {
+    let mut book = entries(env, side, token);
+    if let Some(index) = book.iter().position(|entry| entry.id == id) {
+        book.remove(index as u32);
+        storage::set_persistent(env, &book_key(side, token), &book);
+    }
+}
+
+/// Whether an order rests on the book
+pub fn contains(env: &Env, side: Side, token: &Address, id: u64) -> bool {
+    entries(env, side, token).iter().any(|entry| entry.id == id)
+}
+
+/// Add an order's open amount to the last level, or start a new one
+pub fn add_to_levels(levels: &mut Vec<PriceLevel>, price: i128, amount: i128) {
+    if let Some(mut last) = levels.last() {
+        if last.price == price {
+            last.amount += amount;
+            last.orders += 1;
+            levels.set(levels.len() - 1, last);
+            return;
+        }
+    }
+
+    levels.push_back(PriceLevel { price, amount, orders: 1 });
+}
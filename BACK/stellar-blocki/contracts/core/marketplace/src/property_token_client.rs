@@ -0,0 +1,31 @@
+// PropertyTokenContract interface used for registry sync
+//
+// Declared here instead of importing the compiled contract, so the marketplace
+// builds without the property token WASM. Signatures must match
+// `PropertyTokenContract`.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env, String, Symbol};
+
+/// Mirrors `property_token::TokenMetadata`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenMetadata {
+    pub property_id: Symbol,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u32,
+    pub total_supply: i128,
+}
+
+#[contractclient(name = "PropertyTokenContractClient")]
+#[allow(dead_code)]
+pub trait PropertyTokenContract {
+    /// Token metadata, including the property it represents
+    fn metadata(env: Env) -> TokenMetadata;
+
+    /// Share of the total supply held by `owner`, in basis points
+    fn get_ownership_percentage(env: Env, owner: Address) -> i128;
+
+    /// Whether `account` is flagged as a custody contract
+    fn is_distribution_custodian(env: Env, account: Address) -> bool;
+}
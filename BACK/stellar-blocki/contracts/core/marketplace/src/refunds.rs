@@ -0,0 +1,50 @@
+// Refunds
+//
+// Payment owed back to an account the marketplace doesn't push to, like an
+// outbid auction bid or the escrow of a bid cancelled because its tokens
+// couldn't be delivered. It is
+// credited per account and asset, and the account claims it with
+// `claim_refund`, so an account that can't receive the asset only holds up
+// its own refund.
+
+use soroban_sdk::{contracttype, token, Address, Env};
+use stellar_property_errors::MarketplaceError as Error;
+use stellar_property_events as events;
+use stellar_property_storage as storage;
+
+#[contracttype]
+#[derive(Clone)]
+enum RefundKey {
+    Balance(Address, Address),     // By account and asset
+}
+
+/// Refund waiting for `account` in `asset`
+pub fn claimable(env: &Env, account: &Address, asset: &Address) -> i128 {
+    storage::get_persistent(env, &RefundKey::Balance(account.clone(), asset.clone())).unwrap_or(0)
+}
+
+/// Owe `amount` of `asset` to `account`
+pub fn credit(env: &Env, account: &Address, asset: &Address, amount: i128) -> Result<(), Error> {
+    let balance = claimable(env, account, asset)
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    storage::set_persistent(env, &RefundKey::Balance(account.clone(), asset.clone()), &balance);
+    Ok(())
+}
+
+/// Pay out everything owed to `account` in `asset`
+pub fn claim(env: &Env, account: &Address, asset: &Address) -> i128 {
+    let amount = claimable(env, account, asset);
+    if amount <= 0 {
+        return 0;
+    }
+
+    storage::remove_persistent(env, &RefundKey::Balance(account.clone(), asset.clone()));
+
+    let asset_client = token::TokenClient::new(env, asset);
+    asset_client.transfer(&env.current_contract_address(), account, &amount);
+
+    events::refund_claimed(env, account.clone(), asset.clone(), amount);
+
+    amount
+}
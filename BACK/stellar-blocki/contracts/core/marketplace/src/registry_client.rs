@@ -0,0 +1,39 @@
+// OwnershipRegistryContract interface used for registry sync
+//
+// Declared here instead of importing the compiled contract, so the marketplace
+// builds without the registry WASM. Signatures must match
+// `OwnershipRegistryContract`.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env, String, Symbol, Vec};
+
+/// Mirrors `registry::OwnerInfo`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnerInfo {
+    pub owner: Address,
+    pub percentage: i128,
+}
+
+/// Mirrors `registry::PropertyMetadata`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PropertyMetadata {
+    pub property_id: Symbol,
+    pub address: String,
+    pub valuation: i128,
+    pub legal_id: String,
+    pub verified: bool,
+    pub verification_date: u64,
+    pub token_contract: Address,
+    pub verified_by: Option<Address>,
+}
+
+#[contractclient(name = "OwnershipRegistryContractClient")]
+#[allow(dead_code)]
+pub trait OwnershipRegistryContract {
+    /// Set the share of each listed owner of a property
+    fn update_ownership(env: Env, caller: Address, property_id: Symbol, new_holders: Vec<OwnerInfo>);
+
+    /// Registered details of a property
+    fn get_property_metadata(env: Env, property_id: Symbol) -> PropertyMetadata;
+}
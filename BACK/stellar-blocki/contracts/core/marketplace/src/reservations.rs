@@ -0,0 +1,82 @@
+// Two-phase checkout
+//
+// A reservation takes tokens out of a listing and locks the buyer's payment in
+// EscrowContract. Confirming it pays the seller and delivers the tokens;
+// refunding it returns the payment and puts the tokens back on the listing.
+
+use soroban_sdk::{contracttype, Address, Env};
+use stellar_property_storage as storage;
+
+use crate::FeeQuote;
+
+// How long a reservation holds tokens before anyone can unwind it (24 hours)
+pub const RESERVATION_TIMEOUT: u64 = 86400;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReservationStatus {
+    Pending,
+    Confirmed,
+    Refunded,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reservation {
+    pub id: u64,
+    pub listing_id: u64,
+    pub buyer: Address,
+    pub amount: i128,
+    pub price: i128,       // Price before fees
+    pub buyer_fee: i128,   // Locked in escrow on top of the price
+    pub seller_fee: i128,
+    pub escrow_id: u64,
+    pub status: ReservationStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+}
+
+impl Reservation {
+    /// Fees fixed when the reservation was made
+    pub fn fee_quote(&self) -> FeeQuote {
+        FeeQuote {
+            price: self.price,
+            buyer_fee: self.buyer_fee,
+            seller_fee: self.seller_fee,
+            buyer_total: self.price + self.buyer_fee,
+            seller_proceeds: self.price - self.seller_fee,
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum ReservationKey {
+    Reservation(u64),
+    Counter,
+    Reserved(u64), // Tokens of a listing held by pending reservations
+}
+
+pub fn next_id(env: &Env) -> u64 {
+    storage::get_and_increment_counter(env, ReservationKey::Counter)
+}
+
+pub fn read(env: &Env, reservation_id: u64) -> Option<Reservation> {
+    storage::get_persistent(env, &ReservationKey::Reservation(reservation_id))
+}
+
+pub fn write(env: &Env, reservation: &Reservation) {
+    storage::set_persistent(env, &ReservationKey::Reservation(reservation.id), reservation);
+}
+
+pub fn reserved(env: &Env, listing_id: u64) -> i128 {
+    storage::get_persistent(env, &ReservationKey::Reserved(listing_id)).unwrap_or(0)
+}
+
+pub fn set_reserved(env: &Env, listing_id: u64, amount: i128) {
+    if amount == 0 {
+        storage::remove_persistent(env, &ReservationKey::Reserved(listing_id));
+    } else {
+        storage::set_persistent(env, &ReservationKey::Reserved(listing_id), &amount);
+    }
+}
@@ -1,5 +1,5 @@
 use soroban_sdk::{Address, Env, Vec};
-use stellar_property_errors::Error;
+use stellar_property_errors::MarketplaceError as Error;
 
 // Soroswap Router Contract Address (Mainnet)
 // CAG5LRYQ5JVEUI5TEID72EYOVX44TTUJT5BQR2J6J77FH65PCCFAJDDH
@@ -10,6 +10,7 @@ mod soroswap {
     use soroban_sdk::{contractclient, Address, Env, Vec};
 
     #[contractclient(name = "SoroswapRouterClient")]
+    #[allow(dead_code)]
     pub trait SoroswapRouter {
         /// Swap exact tokens for tokens
         /// path: [token_in, token_out] or [token_in, intermediate, token_out]
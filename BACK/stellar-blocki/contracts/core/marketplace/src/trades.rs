@@ -0,0 +1,145 @@
+// Trade ledger
+//
+// Each property token has its own ledger of trades, one storage entry per
+// trade numbered from 0 in the order they happened, so recording a trade
+// never rewrites the ones before it. Every trade also updates the hourly and
+// daily OHLCV candles of its token: the price per token of the first, highest,
+// lowest and last trade in the bucket, with the tokens and payment traded.
+//
+// Trades moved from the history of schema version 1 are archived in front of
+// the ledger, newest first, so positions still follow the order trades
+// happened while the migration runs alongside new trades.
+
+use soroban_sdk::{contracttype, Address, Env};
+use stellar_property_storage as storage;
+
+use crate::Trade;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Interval {
+    Hour,
+    Day,
+}
+
+impl Interval {
+    pub fn seconds(self) -> u64 {
+        match self {
+            Interval::Hour => 3600,
+            Interval::Day => 86400,
+        }
+    }
+
+    /// Start of the bucket holding `timestamp`
+    pub fn bucket_start(self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.seconds()
+    }
+}
+
+/// Trades of a token in one bucket, prices per token before fees
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Candle {
+    pub start: u64,                // Bucket start, a multiple of the interval
+    pub open: i128,
+    pub high: i128,
+    pub low: i128,
+    pub close: i128,
+    pub volume: i128,              // Tokens traded
+    pub value: i128,               // Payment traded before fees
+    pub trades: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum TradeKey {
+    Trade(Address, u32),           // By token and position among recorded trades
+    Count(Address),
+    Archived(Address, u32),        // By token and position among archived trades, newest first
+    ArchivedCount(Address),
+    Candle(Address, Interval, u64), // By token, interval and bucket start
+}
+
+/// Number of trades in a token's ledger
+pub fn count(env: &Env, token: &Address) -> u32 {
+    archived_count(env, token) + recorded_count(env, token)
+}
+
+fn recorded_count(env: &Env, token: &Address) -> u32 {
+    storage::get_persistent(env, &TradeKey::Count(token.clone())).unwrap_or(0)
+}
+
+fn archived_count(env: &Env, token: &Address) -> u32 {
+    storage::get_persistent(env, &TradeKey::ArchivedCount(token.clone())).unwrap_or(0)
+}
+
+/// Trade at `index` of a token's ledger, archived trades come first
+pub fn read(env: &Env, token: &Address, index: u32) -> Option<Trade> {
+    let archived = archived_count(env, token);
+    if index < archived {
+        storage::get_persistent(env, &TradeKey::Archived(token.clone(), archived - 1 - index))
+    } else {
+        storage::get_persistent(env, &TradeKey::Trade(token.clone(), index - archived))
+    }
+}
+
+pub fn candle(env: &Env, token: &Address, interval: Interval, start: u64) -> Option<Candle> {
+    storage::get_persistent(env, &TradeKey::Candle(token.clone(), interval, start))
+}
+
+/// Append a trade to its token's ledger and candles
+pub fn record(env: &Env, trade: &Trade) {
+    let index = recorded_count(env, &trade.token);
+    storage::set_persistent(env, &TradeKey::Trade(trade.token.clone(), index), trade);
+    storage::set_persistent(env, &TradeKey::Count(trade.token.clone()), &(index + 1));
+
+    for interval in [Interval::Hour, Interval::Day] {
+        add_to_candle(env, trade, interval, false);
+    }
+}
+
+/// Put a trade older than every trade of its token at the front of the
+/// ledger and add it to the candles
+pub fn archive(env: &Env, trade: &Trade) {
+    let index = archived_count(env, &trade.token);
+    storage::set_persistent(env, &TradeKey::Archived(trade.token.clone(), index), trade);
+    storage::set_persistent(env, &TradeKey::ArchivedCount(trade.token.clone()), &(index + 1));
+
+    for interval in [Interval::Hour, Interval::Day] {
+        add_to_candle(env, trade, interval, true);
+    }
+}
+
+// An earlier trade opens the candle, a later one closes it
+fn add_to_candle(env: &Env, trade: &Trade, interval: Interval, earlier: bool) {
+    let start = interval.bucket_start(trade.timestamp);
+    let price = trade.price / trade.amount;
+
+    let candle = match candle(env, &trade.token, interval, start) {
+        Some(mut candle) => {
+            candle.high = candle.high.max(price);
+            candle.low = candle.low.min(price);
+            if earlier {
+                candle.open = price;
+            } else {
+                candle.close = price;
+            }
+            candle.volume = candle.volume.saturating_add(trade.amount);
+            candle.value = candle.value.saturating_add(trade.price);
+            candle.trades += 1;
+            candle
+        }
+        None => Candle {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: trade.amount,
+            value: trade.price,
+            trades: 1,
+        },
+    };
+
+    storage::set_persistent(env, &TradeKey::Candle(trade.token.clone(), interval, start), &candle);
+}
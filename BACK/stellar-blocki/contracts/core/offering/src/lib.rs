@@ -0,0 +1,664 @@
+#![no_std]
+
+use soroban_sdk::{contract, contractimpl, contracttype, token, Address, BytesN, Env};
+use stellar_property_access as access;
+use stellar_property_errors::OfferingError as Error;
+use stellar_property_events as events;
+use stellar_property_storage as storage;
+
+// Property token interface used to screen investors before taking payment
+mod token_interface {
+    use soroban_sdk::{contractclient, Address, Env};
+
+    #[contractclient(name = "PropertyTokenClient")]
+    #[allow(dead_code)]
+    pub trait PropertyToken {
+        fn can_receive(env: Env, account: Address) -> bool;
+    }
+}
+
+use token_interface::PropertyTokenClient;
+
+// Property tokens use 7 decimals; prices are quoted per whole token
+const TOKEN_UNIT: i128 = 10_000_000;
+
+// Storage layout version written by this code (1 = initial layout)
+const SCHEMA_VERSION: u32 = 1;
+
+#[contracttype]
+#[derive(Clone)]
+enum OfferingKey {
+    Token,
+    PaymentToken,
+    Terms,
+    State,
+    Investment(Address),
+}
+
+/// Sale terms, token amounts in token units (7 decimals)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OfferingTerms {
+    pub price_per_token: i128, // Payment token units per whole property token
+    pub soft_cap: i128,        // Tokens that must sell for the offering to succeed
+    pub hard_cap: i128,        // Tokens on offer, taken into custody at initialize
+    pub min_ticket: i128,      // Minimum tokens per investor
+    pub max_ticket: i128,      // Maximum tokens per investor
+    pub deadline: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OfferingStatus {
+    Active,
+    Succeeded,
+    Failed,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OfferingState {
+    pub status: OfferingStatus,
+    pub sold: i128,
+    pub raised: i128,
+    pub investors: u32,
+    pub finalized_at: u64, // 0 while active
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Investment {
+    pub tokens: i128,
+    pub paid: i128,
+    pub settled: bool, // Tokens claimed or payment refunded
+}
+
+#[contract]
+pub struct OfferingContract;
+
+#[contractimpl]
+impl OfferingContract {
+    /// Open an offering and take custody of `terms.hard_cap` tokens from the issuer
+    /// The offering contract must be able to hold the tokens (allowlisted or
+    /// cap exempt when the property token enforces compliance)
+    pub fn initialize(
+        env: Env,
+        issuer: Address,
+        token: Address,
+        payment_token: Address,
+        terms: OfferingTerms,
+    ) -> Result<(), Error> {
+        if storage::is_initialized(&env) {
+            return Err(Error::AlreadyInitialized);
+        }
+
+        issuer.require_auth();
+
+        if terms.price_per_token <= 0
+            || terms.soft_cap <= 0
+            || terms.soft_cap > terms.hard_cap
+            || terms.min_ticket <= 0
+            || terms.min_ticket > terms.max_ticket
+            || terms.max_ticket > terms.hard_cap
+            || terms.deadline <= env.ledger().timestamp()
+        {
+            return Err(Error::InvalidAmount);
+        }
+
+        storage::set_admin(&env, &issuer);
+        env.storage().instance().set(&OfferingKey::Token, &token);
+        env.storage().instance().set(&OfferingKey::PaymentToken, &payment_token);
+        env.storage().instance().set(&OfferingKey::Terms, &terms);
+        Self::write_state(
+            &env,
+            &OfferingState {
+                status: OfferingStatus::Active,
+                sold: 0,
+                raised: 0,
+                investors: 0,
+                finalized_at: 0,
+            },
+        );
+
+        storage::set_schema_version(&env, SCHEMA_VERSION);
+        storage::set_initialized(&env);
+
+        // Take custody of the issuance
+        let token_client = token::TokenClient::new(&env, &token);
+        token_client.transfer(&issuer, &env.current_contract_address(), &terms.hard_cap);
+
+        events::offering_opened(
+            &env,
+            token,
+            issuer,
+            terms.hard_cap,
+            terms.price_per_token,
+            terms.deadline,
+        );
+
+        Ok(())
+    }
+
+    /// Buy `amount` tokens at the fixed price, returns the amount paid
+    /// Tokens are delivered by `claim` once the offering succeeds
+    pub fn invest(env: Env, investor: Address, amount: i128) -> Result<i128, Error> {
+        investor.require_auth();
+
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let terms = Self::read_terms(&env)?;
+        let mut state = Self::read_state(&env)?;
+
+        if state.status != OfferingStatus::Active || env.ledger().timestamp() >= terms.deadline {
+            return Err(Error::OfferingClosed);
+        }
+
+        let sold = state.sold.checked_add(amount).ok_or(Error::InvalidAmount)?;
+        if sold > terms.hard_cap {
+            return Err(Error::HardCapExceeded);
+        }
+
+        let mut investment = Self::read_investment(&env, &investor);
+        let ticket = investment.tokens.checked_add(amount).ok_or(Error::InvalidAmount)?;
+        if ticket < terms.min_ticket {
+            return Err(Error::TicketBelowMinimum);
+        }
+        if ticket > terms.max_ticket {
+            return Err(Error::TicketAboveMaximum);
+        }
+
+        // Reject investors who could not receive the tokens at claim time
+        let token: Address = env.storage().instance().get(&OfferingKey::Token).unwrap();
+        if !PropertyTokenClient::new(&env, &token).can_receive(&investor) {
+            return Err(Error::InvestorNotEligible);
+        }
+
+        let cost = Self::cost_of(&terms, amount)?;
+        if cost <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // ===== EFFECTS =====
+        if investment.tokens == 0 {
+            state.investors += 1;
+        }
+        investment.tokens = ticket;
+        investment.paid = investment.paid.checked_add(cost).ok_or(Error::InvalidAmount)?;
+        state.sold = sold;
+        state.raised = state.raised.checked_add(cost).ok_or(Error::InvalidAmount)?;
+
+        storage::set_persistent(&env, &OfferingKey::Investment(investor.clone()), &investment);
+        Self::write_state(&env, &state);
+
+        // ===== INTERACTIONS =====
+        let payment_token: Address = env.storage().instance().get(&OfferingKey::PaymentToken).unwrap();
+        let payment_client = token::TokenClient::new(&env, &payment_token);
+        payment_client.transfer(&investor, &env.current_contract_address(), &cost);
+
+        events::offering_invested(&env, investor, amount, cost);
+
+        Ok(cost)
+    }
+
+    /// Close the offering after the deadline, or early once the hard cap sells out
+    /// On success the issuer receives the proceeds and the unsold tokens; on
+    /// failure the issuer gets every token back and investors are refunded
+    pub fn finalize(env: Env) -> Result<OfferingStatus, Error> {
+        let terms = Self::read_terms(&env)?;
+        let mut state = Self::read_state(&env)?;
+
+        if state.status != OfferingStatus::Active {
+            return Err(Error::OfferingClosed);
+        }
+
+        if env.ledger().timestamp() < terms.deadline && state.sold < terms.hard_cap {
+            return Err(Error::OfferingNotEnded);
+        }
+
+        let succeeded = state.sold >= terms.soft_cap;
+        state.status = if succeeded {
+            OfferingStatus::Succeeded
+        } else {
+            OfferingStatus::Failed
+        };
+        state.finalized_at = env.ledger().timestamp();
+        Self::write_state(&env, &state);
+
+        let issuer = storage::get_admin(&env);
+        let token: Address = env.storage().instance().get(&OfferingKey::Token).unwrap();
+        let token_client = token::TokenClient::new(&env, &token);
+
+        let returned = if succeeded { terms.hard_cap - state.sold } else { terms.hard_cap };
+        if returned > 0 {
+            token_client.transfer(&env.current_contract_address(), &issuer, &returned);
+        }
+
+        if succeeded && state.raised > 0 {
+            let payment_token: Address = env.storage().instance().get(&OfferingKey::PaymentToken).unwrap();
+            let payment_client = token::TokenClient::new(&env, &payment_token);
+            payment_client.transfer(&env.current_contract_address(), &issuer, &state.raised);
+        }
+
+        events::offering_finalized(&env, succeeded, state.sold, state.raised);
+
+        Ok(state.status)
+    }
+
+    /// Deliver an investor's tokens after a successful offering (permissionless)
+    pub fn claim(env: Env, investor: Address) -> Result<i128, Error> {
+        let state = Self::read_state(&env)?;
+        match state.status {
+            OfferingStatus::Succeeded => {}
+            OfferingStatus::Failed => return Err(Error::SoftCapNotReached),
+            OfferingStatus::Active => return Err(Error::OfferingNotEnded),
+        }
+
+        let mut investment = Self::read_investment(&env, &investor);
+        if investment.tokens == 0 || investment.settled {
+            return Err(Error::NothingToSettle);
+        }
+
+        investment.settled = true;
+        storage::set_persistent(&env, &OfferingKey::Investment(investor.clone()), &investment);
+
+        let token: Address = env.storage().instance().get(&OfferingKey::Token).unwrap();
+        let token_client = token::TokenClient::new(&env, &token);
+        token_client.transfer(&env.current_contract_address(), &investor, &investment.tokens);
+
+        events::offering_claimed(&env, investor, investment.tokens);
+
+        Ok(investment.tokens)
+    }
+
+    /// Return an investor's full payment after a failed offering (permissionless)
+    pub fn refund(env: Env, investor: Address) -> Result<i128, Error> {
+        let state = Self::read_state(&env)?;
+        match state.status {
+            OfferingStatus::Failed => {}
+            OfferingStatus::Succeeded => return Err(Error::SoftCapReached),
+            OfferingStatus::Active => return Err(Error::OfferingNotEnded),
+        }
+
+        let mut investment = Self::read_investment(&env, &investor);
+        if investment.paid == 0 || investment.settled {
+            return Err(Error::NothingToSettle);
+        }
+
+        investment.settled = true;
+        storage::set_persistent(&env, &OfferingKey::Investment(investor.clone()), &investment);
+
+        let payment_token: Address = env.storage().instance().get(&OfferingKey::PaymentToken).unwrap();
+        let payment_client = token::TokenClient::new(&env, &payment_token);
+        payment_client.transfer(&env.current_contract_address(), &investor, &investment.paid);
+
+        events::offering_refunded(&env, investor, investment.paid);
+
+        Ok(investment.paid)
+    }
+
+    // ========== UPGRADES ==========
+
+    /// Replace the contract code, keeping storage (issuer only)
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        access::upgrade(&env, &new_wasm_hash);
+        Ok(())
+    }
+
+    /// Storage layout version of this contract
+    pub fn schema_version(env: Env) -> u32 {
+        storage::get_schema_version(&env)
+    }
+
+    // ========== VIEW FUNCTIONS ==========
+
+    /// Get the sale terms
+    pub fn get_terms(env: Env) -> Result<OfferingTerms, Error> {
+        Self::read_terms(&env)
+    }
+
+    /// Get the sale progress
+    pub fn get_state(env: Env) -> Result<OfferingState, Error> {
+        Self::read_state(&env)
+    }
+
+    /// Get an investor's allocation and payment
+    pub fn get_investment(env: Env, investor: Address) -> Investment {
+        Self::read_investment(&env, &investor)
+    }
+
+    /// Cost of buying `amount` tokens at the offering price
+    pub fn quote(env: Env, amount: i128) -> Result<i128, Error> {
+        let terms = Self::read_terms(&env)?;
+        Self::cost_of(&terms, amount)
+    }
+
+    /// Get the issuer
+    pub fn issuer(env: Env) -> Address {
+        storage::get_admin(&env)
+    }
+
+    // ========== INTERNAL HELPERS ==========
+
+    fn read_terms(env: &Env) -> Result<OfferingTerms, Error> {
+        env.storage()
+            .instance()
+            .get(&OfferingKey::Terms)
+            .ok_or(Error::NotInitialized)
+    }
+
+    fn read_state(env: &Env) -> Result<OfferingState, Error> {
+        env.storage()
+            .instance()
+            .get(&OfferingKey::State)
+            .ok_or(Error::NotInitialized)
+    }
+
+    fn write_state(env: &Env, state: &OfferingState) {
+        env.storage().instance().set(&OfferingKey::State, state);
+    }
+
+    // Fractions of a payment unit are rounded up, in the issuer's favour
+    fn cost_of(terms: &OfferingTerms, amount: i128) -> Result<i128, Error> {
+        let value = amount.checked_mul(terms.price_per_token).ok_or(Error::InvalidAmount)?;
+        let cost = value / TOKEN_UNIT;
+        if value % TOKEN_UNIT > 0 {
+            return Ok(cost + 1);
+        }
+        Ok(cost)
+    }
+
+    fn read_investment(env: &Env, investor: &Address) -> Investment {
+        storage::get_persistent(env, &OfferingKey::Investment(investor.clone())).unwrap_or(Investment {
+            tokens: 0,
+            paid: 0,
+            settled: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use property_token::{PropertyTokenContract, PropertyTokenContractClient};
+    use soroban_sdk::{
+        symbol_short,
+        testutils::{Address as _, Events, Ledger},
+        vec, Env, IntoVal, String, Symbol,
+    };
+
+    struct Setup<'a> {
+        offering: OfferingContractClient<'a>,
+        property: PropertyTokenContractClient<'a>,
+        usdc: token::StellarAssetClient<'a>,
+        issuer: Address,
+    }
+
+    // 1,000 tokens on offer at 50 USDC each, soft cap 400, tickets 10..=300
+    fn setup<'a>(env: &Env) -> Setup<'a> {
+        let issuer = Address::generate(env);
+
+        let property_id = env.register(PropertyTokenContract, ());
+        let property = PropertyTokenContractClient::new(env, &property_id);
+        property.initialize(
+            &issuer,
+            &Symbol::new(env, "PROP001"),
+            &String::from_str(env, "Property Token"),
+            &String::from_str(env, "PROP"),
+            &10_000_0000000,
+        );
+
+        let usdc_admin = Address::generate(env);
+        let usdc = token::StellarAssetClient::new(
+            env,
+            &env.register_stellar_asset_contract_v2(usdc_admin).address(),
+        );
+
+        let offering_id = env.register(OfferingContract, ());
+        let offering = OfferingContractClient::new(env, &offering_id);
+        offering.initialize(
+            &issuer,
+            &property.address,
+            &usdc.address,
+            &OfferingTerms {
+                price_per_token: 50_0000000,
+                soft_cap: 400_0000000,
+                hard_cap: 1_000_0000000,
+                min_ticket: 10_0000000,
+                max_ticket: 300_0000000,
+                deadline: 1000,
+            },
+        );
+
+        Setup { offering, property, usdc, issuer }
+    }
+
+    #[test]
+    fn test_initialize_takes_custody() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let s = setup(&env);
+
+        assert_eq!(s.property.balance(&s.offering.address), 1_000_0000000);
+        assert_eq!(s.property.balance(&s.issuer), 9_000_0000000);
+        assert_eq!(s.offering.get_state().status, OfferingStatus::Active);
+        assert_eq!(s.offering.issuer(), s.issuer);
+        assert_eq!(s.offering.quote(&2_0000000), 100_0000000);
+    }
+
+    #[test]
+    fn test_invest_enforces_tickets_and_caps() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let s = setup(&env);
+        let investor = Address::generate(&env);
+        s.usdc.mint(&investor, &100_000_0000000);
+
+        assert_eq!(
+            s.offering.try_invest(&investor, &5_0000000),
+            Err(Ok(Error::TicketBelowMinimum))
+        );
+        assert_eq!(
+            s.offering.try_invest(&investor, &301_0000000),
+            Err(Ok(Error::TicketAboveMaximum))
+        );
+
+        assert_eq!(s.offering.invest(&investor, &200_0000000), 10_000_0000000);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    s.offering.address.clone(),
+                    (symbol_short!("sto_inv"), investor.clone()).into_val(&env),
+                    (200_0000000i128, 10_000_0000000i128).into_val(&env),
+                ),
+            ]
+        );
+
+        // The maximum ticket is cumulative, the minimum applies to the total
+        assert_eq!(
+            s.offering.try_invest(&investor, &101_0000000),
+            Err(Ok(Error::TicketAboveMaximum))
+        );
+        s.offering.invest(&investor, &1_0000000);
+
+        let investment = s.offering.get_investment(&investor);
+        assert_eq!(investment.tokens, 201_0000000);
+        assert_eq!(investment.paid, 10_050_0000000);
+
+        // Fill up to the hard cap with other investors
+        for _ in 0..2 {
+            let other = Address::generate(&env);
+            s.usdc.mint(&other, &100_000_0000000);
+            s.offering.invest(&other, &300_0000000);
+        }
+        let last = Address::generate(&env);
+        s.usdc.mint(&last, &100_000_0000000);
+        assert_eq!(
+            s.offering.try_invest(&last, &200_0000000),
+            Err(Ok(Error::HardCapExceeded))
+        );
+        s.offering.invest(&last, &199_0000000);
+
+        let state = s.offering.get_state();
+        assert_eq!(state.sold, 1_000_0000000);
+        assert_eq!(state.investors, 4);
+
+        // A sold-out offering can close before the deadline
+        assert_eq!(s.offering.finalize(), OfferingStatus::Succeeded);
+        assert_eq!(s.offering.try_invest(&last, &1_0000000), Err(Ok(Error::OfferingClosed)));
+    }
+
+    #[test]
+    fn test_cost_rounds_up() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let s = setup(&env);
+        let investor = Address::generate(&env);
+        s.usdc.mint(&investor, &100_0000000);
+
+        // 0.3 USDC per whole token
+        let offering = OfferingContractClient::new(&env, &env.register(OfferingContract, ()));
+        offering.initialize(
+            &s.issuer,
+            &s.property.address,
+            &s.usdc.address,
+            &OfferingTerms {
+                price_per_token: 3_000_000,
+                soft_cap: 1,
+                hard_cap: 100_0000000,
+                min_ticket: 1,
+                max_ticket: 100_0000000,
+                deadline: 1000,
+            },
+        );
+
+        assert_eq!(offering.quote(&10_0000000), 3_0000000);
+        assert_eq!(offering.quote(&5), 2);
+
+        // Small top-ups pay at least the offering price
+        assert_eq!(offering.invest(&investor, &10_0000000), 3_0000000);
+        for _ in 0..3 {
+            assert_eq!(offering.invest(&investor, &1), 1);
+        }
+        let investment = offering.get_investment(&investor);
+        assert_eq!(investment.tokens, 10_0000003);
+        assert_eq!(investment.paid, 3_0000003);
+    }
+
+    #[test]
+    fn test_successful_offering_pays_issuer() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let s = setup(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        s.usdc.mint(&alice, &100_000_0000000);
+        s.usdc.mint(&bob, &100_000_0000000);
+
+        s.offering.invest(&alice, &300_0000000);
+        s.offering.invest(&bob, &200_0000000);
+
+        assert_eq!(s.offering.try_finalize(), Err(Ok(Error::OfferingNotEnded)));
+        assert_eq!(s.offering.try_claim(&alice), Err(Ok(Error::OfferingNotEnded)));
+
+        env.ledger().set_timestamp(1000);
+        assert_eq!(s.offering.try_invest(&alice, &1_0000000), Err(Ok(Error::OfferingClosed)));
+
+        assert_eq!(s.offering.finalize(), OfferingStatus::Succeeded);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    s.offering.address.clone(),
+                    (symbol_short!("sto_fin"),).into_val(&env),
+                    (true, 500_0000000i128, 25_000_0000000i128).into_val(&env),
+                ),
+            ]
+        );
+
+        // Proceeds and the 500 unsold tokens go to the issuer
+        let usdc = token::TokenClient::new(&env, &s.usdc.address);
+        assert_eq!(usdc.balance(&s.issuer), 25_000_0000000);
+        assert_eq!(usdc.balance(&s.offering.address), 0);
+        assert_eq!(s.property.balance(&s.issuer), 9_500_0000000);
+
+        assert_eq!(s.offering.claim(&alice), 300_0000000);
+        assert_eq!(s.offering.claim(&bob), 200_0000000);
+        assert_eq!(s.property.balance(&alice), 300_0000000);
+        assert_eq!(s.property.balance(&s.offering.address), 0);
+
+        assert_eq!(s.offering.try_claim(&alice), Err(Ok(Error::NothingToSettle)));
+        assert_eq!(s.offering.try_refund(&alice), Err(Ok(Error::SoftCapReached)));
+        assert_eq!(s.offering.try_finalize(), Err(Ok(Error::OfferingClosed)));
+    }
+
+    #[test]
+    fn test_failed_offering_refunds_investors() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let s = setup(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        s.usdc.mint(&alice, &100_000_0000000);
+        s.usdc.mint(&bob, &100_000_0000000);
+
+        s.offering.invest(&alice, &250_0000000);
+        s.offering.invest(&bob, &100_0000000);
+
+        env.ledger().set_timestamp(1000);
+        assert_eq!(s.offering.finalize(), OfferingStatus::Failed);
+
+        // Every token returns to the issuer
+        assert_eq!(s.property.balance(&s.issuer), 10_000_0000000);
+
+        assert_eq!(s.offering.refund(&alice), 12_500_0000000);
+        assert_eq!(
+            vec![&env, env.events().all().last().unwrap()],
+            vec![
+                &env,
+                (
+                    s.offering.address.clone(),
+                    (symbol_short!("sto_rfnd"), alice.clone()).into_val(&env),
+                    12_500_0000000i128.into_val(&env),
+                ),
+            ]
+        );
+        s.offering.refund(&bob);
+
+        let usdc = token::TokenClient::new(&env, &s.usdc.address);
+        assert_eq!(usdc.balance(&alice), 100_000_0000000);
+        assert_eq!(usdc.balance(&bob), 100_000_0000000);
+        assert_eq!(usdc.balance(&s.issuer), 0);
+
+        assert_eq!(s.offering.try_refund(&alice), Err(Ok(Error::NothingToSettle)));
+        assert_eq!(s.offering.try_claim(&bob), Err(Ok(Error::SoftCapNotReached)));
+    }
+
+    #[test]
+    fn test_invest_requires_eligible_investor() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let s = setup(&env);
+        s.property.set_compliance(&s.issuer, &None, &false, &false, &true);
+
+        let investor = Address::generate(&env);
+        s.usdc.mint(&investor, &100_000_0000000);
+        assert_eq!(
+            s.offering.try_invest(&investor, &10_0000000),
+            Err(Ok(Error::InvestorNotEligible))
+        );
+
+        s.property.set_allowlisted(&s.issuer, &investor, &true);
+        s.offering.invest(&investor, &10_0000000);
+    }
+}
@@ -1,7 +1,8 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, Symbol, Vec};
-use stellar_property_errors::Error;
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Symbol, Vec};
+use stellar_property_access as access;
+use stellar_property_errors::OracleError as Error;
 use stellar_property_storage::{self as storage, DataKey};
 
 /// Price data structure from Redstone Oracle
@@ -173,18 +174,26 @@ impl OracleConsumer {
         Ok(converted)
     }
 
-    /// Update admin (only current admin)
-    pub fn update_admin(env: Env, new_admin: Address) -> Result<(), Error> {
-        let current_admin = storage::get_admin(&env);
-        current_admin.require_auth();
+    /// Nominate a new admin; takes effect once they call `accept_admin` (admin only)
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        access::propose_admin(&env, &new_admin);
+        Ok(())
+    }
 
-        storage::set_admin(&env, &new_admin);
+    /// Accept a pending admin nomination (pending admin only)
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        access::accept_admin(&env).map_err(|_| Error::NotAuthorized)?;
+        Ok(())
+    }
 
-        env.events().publish(
-            (Symbol::new(&env, "admin_updated"),),
-            new_admin,
-        );
+    /// Get the nominated admin awaiting acceptance
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        access::pending_admin(&env)
+    }
 
+    /// Replace the contract code, keeping storage (admin only)
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        access::upgrade(&env, &new_wasm_hash);
         Ok(())
     }
 
@@ -197,7 +206,14 @@ impl OracleConsumer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Env};
+    use soroban_sdk::{testutils::{Address as _, Ledger}, Env};
+
+    fn setup<'a>(env: &Env, admin: &Address) -> OracleConsumerClient<'a> {
+        let contract_id = env.register(OracleConsumer, ());
+        let client = OracleConsumerClient::new(env, &contract_id);
+        client.initialize(admin);
+        client
+    }
 
     #[test]
     fn test_initialize() {
@@ -205,13 +221,10 @@ mod tests {
         env.mock_all_auths();
 
         let admin = Address::generate(&env);
-        let contract_id = env.register_contract(None, OracleConsumer);
-        let client = OracleConsumerClient::new(&env, &contract_id);
-
-        let result = client.initialize(&admin);
-        assert!(result.is_ok());
+        let client = setup(&env, &admin);
 
         assert_eq!(client.get_admin(), admin);
+        assert_eq!(client.try_initialize(&admin), Err(Ok(Error::AlreadyInitialized)));
     }
 
     #[test]
@@ -220,10 +233,7 @@ mod tests {
         env.mock_all_auths();
 
         let admin = Address::generate(&env);
-        let contract_id = env.register_contract(None, OracleConsumer);
-        let client = OracleConsumerClient::new(&env, &contract_id);
-
-        client.initialize(&admin);
+        let client = setup(&env, &admin);
 
         // Update XLM price to $0.12
         let asset = Symbol::new(&env, "XLM");
@@ -231,61 +241,57 @@ mod tests {
         let timestamp = env.ledger().timestamp();
         let confidence = 100; // 1%
 
-        let result = client.update_price(&asset, &price, &timestamp, &confidence);
-        assert!(result.is_ok());
+        client.update_price(&asset, &price, &timestamp, &confidence);
 
         // Get price
-        let price_data = client.get_price(&asset);
-        assert!(price_data.is_ok());
-
-        let data = price_data.unwrap();
+        let data = client.get_price(&asset);
         assert_eq!(data.price, price);
         assert_eq!(data.timestamp, timestamp);
         assert_eq!(data.confidence, confidence);
+
+        assert_eq!(client.try_update_price(&asset, &0, &timestamp, &confidence), Err(Ok(Error::InvalidAmount)));
+        assert_eq!(
+            client.try_get_price(&Symbol::new(&env, "BTC")),
+            Err(Ok(Error::PriceNotFound))
+        );
     }
 
     #[test]
-    #[should_panic(expected = "StalePrice")]
     fn test_stale_price_error() {
         let env = Env::default();
         env.mock_all_auths();
+        env.ledger().set_timestamp(10_000);
 
         let admin = Address::generate(&env);
-        let contract_id = env.register_contract(None, OracleConsumer);
-        let client = OracleConsumerClient::new(&env, &contract_id);
-
-        client.initialize(&admin);
+        let client = setup(&env, &admin);
 
         // Update price with old timestamp
         let asset = Symbol::new(&env, "XLM");
-        let old_timestamp = env.ledger().timestamp().saturating_sub(7200); // 2 hours ago
+        let old_timestamp = env.ledger().timestamp() - 7200; // 2 hours ago
 
         client.update_price(&asset, &1_200_000, &old_timestamp, &100);
 
-        // Should panic because stale
-        client.get_price(&asset);
+        assert_eq!(client.try_get_price(&asset), Err(Ok(Error::StalePrice)));
+        assert_eq!(client.get_price_age(&asset), 7200);
     }
 
     #[test]
     fn test_cached_price_returns_stale() {
         let env = Env::default();
         env.mock_all_auths();
+        env.ledger().set_timestamp(10_000);
 
         let admin = Address::generate(&env);
-        let contract_id = env.register_contract(None, OracleConsumer);
-        let client = OracleConsumerClient::new(&env, &contract_id);
-
-        client.initialize(&admin);
+        let client = setup(&env, &admin);
 
         // Update price with old timestamp
         let asset = Symbol::new(&env, "XLM");
-        let old_timestamp = env.ledger().timestamp().saturating_sub(7200); // 2 hours ago
+        let old_timestamp = env.ledger().timestamp() - 7200; // 2 hours ago
 
         client.update_price(&asset, &1_200_000, &old_timestamp, &100);
 
         // get_cached_price should work even if stale
-        let cached = client.get_cached_price(&asset);
-        assert!(cached.is_ok());
+        assert_eq!(client.get_cached_price(&asset).price, 1_200_000);
     }
 
     #[test]
@@ -294,10 +300,7 @@ mod tests {
         env.mock_all_auths();
 
         let admin = Address::generate(&env);
-        let contract_id = env.register_contract(None, OracleConsumer);
-        let client = OracleConsumerClient::new(&env, &contract_id);
-
-        client.initialize(&admin);
+        let client = setup(&env, &admin);
 
         let timestamp = env.ledger().timestamp();
 
@@ -312,11 +315,45 @@ mod tests {
         // Convert 100 XLM to USDC
         // 100 XLM * $0.12 / $1.00 = 12 USDC
         let amount_xlm = 100_0000000; // 100 XLM with 7 decimals
-        let converted = client.convert_price(&xlm, &usdc, &amount_xlm);
+        assert_eq!(client.convert_price(&xlm, &usdc, &amount_xlm), 12_0000000);
+    }
 
-        assert!(converted.is_ok());
-        // Should be approximately 12 USDC
-        let result = converted.unwrap();
-        assert!(result > 11_0000000 && result < 13_0000000);
+    #[test]
+    fn test_admin_handover() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let new_admin = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        assert_eq!(client.try_accept_admin(), Err(Ok(Error::NotAuthorized)));
+
+        client.propose_admin(&new_admin);
+        assert_eq!(client.pending_admin(), Some(new_admin.clone()));
+        assert_eq!(client.get_admin(), admin);
+
+        client.accept_admin();
+        assert_eq!(client.get_admin(), new_admin);
+        assert_eq!(client.pending_admin(), None);
+    }
+
+    #[test]
+    fn test_upgrade_keeps_prices() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let asset = Symbol::new(&env, "XLM");
+        client.update_price(&asset, &1_200_000, &env.ledger().timestamp(), &100);
+
+        let wasm_hash = stellar_property_access::testutils::upload_upgrade_wasm(&env);
+        client.upgrade(&wasm_hash);
+        env.register_at(&client.address, OracleConsumer, ());
+
+        assert_eq!(client.get_admin(), admin);
+        assert_eq!(client.get_price(&asset).price, 1_200_000);
     }
 }
@@ -0,0 +1,146 @@
+// Historical balance and supply checkpoints
+//
+// Each account keeps an append-only list of (ledger, balance) entries stored
+// under individual keys, so writes cost O(1) and lookups are a binary search.
+// Several changes within one ledger collapse into a single entry holding the
+// end-of-ledger value.
+
+use soroban_sdk::{contracttype, Address, Env};
+use stellar_property_events as events;
+use stellar_property_storage as storage;
+
+#[contracttype]
+#[derive(Clone)]
+enum CheckpointKey {
+    BalanceCount(Address),
+    Balance(Address, u32), // (holder, index)
+    SupplyCount,
+    Supply(u32),
+    SnapshotCounter,
+    Snapshot(u64),
+}
+
+#[contracttype]
+#[derive(Clone)]
+struct Checkpoint {
+    ledger: u32,
+    value: i128,
+}
+
+fn push_checkpoint(
+    env: &Env,
+    count_key: &CheckpointKey,
+    entry_key: impl Fn(u32) -> CheckpointKey,
+    value: i128,
+) {
+    let ledger = env.ledger().sequence();
+    let count: u32 = storage::get_persistent(env, count_key).unwrap_or(0);
+
+    if count > 0 {
+        let last_key = entry_key(count - 1);
+        let last: Checkpoint = storage::get_persistent(env, &last_key).unwrap();
+        if last.ledger == ledger {
+            // Same ledger: keep only the latest value
+            storage::set_persistent(env, &last_key, &Checkpoint { ledger, value });
+            return;
+        }
+    }
+
+    storage::set_persistent(env, &entry_key(count), &Checkpoint { ledger, value });
+    storage::set_persistent(env, count_key, &(count + 1));
+}
+
+fn lookup_checkpoint(
+    env: &Env,
+    count_key: &CheckpointKey,
+    entry_key: impl Fn(u32) -> CheckpointKey,
+    ledger: u32,
+) -> i128 {
+    let count: u32 = storage::get_persistent(env, count_key).unwrap_or(0);
+
+    // Binary search for the last checkpoint at or before `ledger`
+    let mut low = 0;
+    let mut high = count;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let checkpoint: Checkpoint = storage::get_persistent(env, &entry_key(mid)).unwrap();
+        if checkpoint.ledger <= ledger {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    if low == 0 {
+        return 0;
+    }
+
+    let checkpoint: Checkpoint = storage::get_persistent(env, &entry_key(low - 1)).unwrap();
+    checkpoint.value
+}
+
+/// Record `holder`'s balance at the current ledger
+pub fn write_balance(env: &Env, holder: &Address, balance: i128) {
+    push_checkpoint(
+        env,
+        &CheckpointKey::BalanceCount(holder.clone()),
+        |index| CheckpointKey::Balance(holder.clone(), index),
+        balance,
+    );
+}
+
+/// Balance of `holder` at the end of `ledger`
+pub fn balance_at(env: &Env, holder: &Address, ledger: u32) -> i128 {
+    lookup_checkpoint(
+        env,
+        &CheckpointKey::BalanceCount(holder.clone()),
+        |index| CheckpointKey::Balance(holder.clone(), index),
+        ledger,
+    )
+}
+
+/// Current circulating supply (minted minus burned)
+pub fn current_supply(env: &Env) -> i128 {
+    supply_at(env, env.ledger().sequence())
+}
+
+/// Record a change of `delta` to the circulating supply
+pub fn write_supply_change(env: &Env, delta: i128) -> Option<i128> {
+    let new_supply = current_supply(env).checked_add(delta)?;
+    push_checkpoint(env, &CheckpointKey::SupplyCount, CheckpointKey::Supply, new_supply);
+    Some(new_supply)
+}
+
+/// Record `holder`'s balance if its latest checkpoint doesn't match it
+pub fn seed_balance(env: &Env, holder: &Address, balance: i128) {
+    if balance_at(env, holder, env.ledger().sequence()) != balance {
+        write_balance(env, holder, balance);
+    }
+}
+
+/// Record `supply` if the latest supply checkpoint doesn't match it
+pub fn seed_supply(env: &Env, supply: i128) {
+    if current_supply(env) != supply {
+        push_checkpoint(env, &CheckpointKey::SupplyCount, CheckpointKey::Supply, supply);
+    }
+}
+
+/// Circulating supply at the end of `ledger`
+pub fn supply_at(env: &Env, ledger: u32) -> i128 {
+    lookup_checkpoint(env, &CheckpointKey::SupplyCount, CheckpointKey::Supply, ledger)
+}
+
+/// Record the current ledger under a new snapshot id
+pub fn snapshot(env: &Env) -> u64 {
+    let snapshot_id = storage::get_and_increment_counter(env, CheckpointKey::SnapshotCounter);
+    let ledger = env.ledger().sequence();
+    storage::set_persistent(env, &CheckpointKey::Snapshot(snapshot_id), &ledger);
+
+    events::snapshot_taken(env, snapshot_id, ledger);
+
+    snapshot_id
+}
+
+pub fn snapshot_ledger(env: &Env, snapshot_id: u64) -> Option<u32> {
+    storage::get_persistent(env, &CheckpointKey::Snapshot(snapshot_id))
+}
@@ -0,0 +1,161 @@
+// Transfer compliance rules
+//
+// When configured, every address receiving tokens (mint, transfer,
+// transfer_from) must pass one of:
+//   - be on the admin-managed allowlist (when the allowlist is enabled)
+//   - hold the required credentials in the configured ZKVerifier contract
+// With no configuration stored, transfers are unrestricted.
+//
+// Independently, an optional per-holder cap limits any single balance to a
+// share of the total supply. The admin and cap-exempt accounts (issuer,
+// escrow, marketplace) are not limited.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env};
+use stellar_property_errors::TokenError as Error;
+use stellar_property_storage as storage;
+
+// Mirrors `zk_verifier::CredentialType`
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CredentialType {
+    Kyc,
+    Accredited,
+}
+
+#[contractclient(name = "CredentialVerifierClient")]
+#[allow(dead_code)]
+pub trait CredentialVerifier {
+    fn has_credential(env: Env, subject: Address, credential_type: CredentialType) -> bool;
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum ComplianceKey {
+    Config,
+    Allowlist(Address),
+    HolderCapBps,
+    CapExempt(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComplianceConfig {
+    pub verifier: Option<Address>, // ZKVerifier contract
+    pub require_kyc: bool,
+    pub require_accredited: bool,
+    pub use_allowlist: bool,
+}
+
+pub fn get_config(env: &Env) -> Option<ComplianceConfig> {
+    env.storage().instance().get(&ComplianceKey::Config)
+}
+
+pub fn set_config(env: &Env, config: &ComplianceConfig) -> Result<(), Error> {
+    // Credential requirements need a verifier to ask
+    if config.verifier.is_none() && (config.require_kyc || config.require_accredited) {
+        return Err(Error::VerifierNotSet);
+    }
+
+    env.storage().instance().set(&ComplianceKey::Config, config);
+    Ok(())
+}
+
+pub fn clear_config(env: &Env) {
+    env.storage().instance().remove(&ComplianceKey::Config);
+}
+
+pub fn is_allowlisted(env: &Env, account: &Address) -> bool {
+    storage::get_persistent(env, &ComplianceKey::Allowlist(account.clone())).unwrap_or(false)
+}
+
+pub fn set_allowlisted(env: &Env, account: &Address, allowed: bool) {
+    let key = ComplianceKey::Allowlist(account.clone());
+    if allowed {
+        storage::set_persistent(env, &key, &true);
+    } else {
+        storage::remove_persistent(env, &key);
+    }
+}
+
+/// Check that `receiver` may hold tokens under the current rules
+pub fn check_receiver(env: &Env, receiver: &Address) -> Result<(), Error> {
+    let config = match get_config(env) {
+        Some(config) => config,
+        None => return Ok(()),
+    };
+
+    if config.use_allowlist && is_allowlisted(env, receiver) {
+        return Ok(());
+    }
+
+    let verifier = match config.verifier {
+        Some(verifier) => CredentialVerifierClient::new(env, &verifier),
+        None if config.use_allowlist => return Err(Error::ReceiverNotAllowlisted),
+        None => return Ok(()),
+    };
+
+    if config.require_kyc && !verifier.has_credential(receiver, &CredentialType::Kyc) {
+        return Err(Error::KycCredentialRequired);
+    }
+
+    if config.require_accredited && !verifier.has_credential(receiver, &CredentialType::Accredited) {
+        return Err(Error::AccreditationRequired);
+    }
+
+    // A verifier with no credential requirements only leaves the allowlist
+    if config.use_allowlist && !config.require_kyc && !config.require_accredited {
+        return Err(Error::ReceiverNotAllowlisted);
+    }
+
+    Ok(())
+}
+
+/// Maximum share of the total supply a single holder may own (0 = no cap)
+pub fn get_holder_cap(env: &Env) -> i128 {
+    env.storage().instance().get(&ComplianceKey::HolderCapBps).unwrap_or(0)
+}
+
+pub fn set_holder_cap(env: &Env, cap_bps: i128) -> Result<(), Error> {
+    // Using basis points (10000 = 100%), 0 disables the cap
+    if !(0..=10000).contains(&cap_bps) {
+        return Err(Error::InvalidPercentage);
+    }
+
+    env.storage().instance().set(&ComplianceKey::HolderCapBps, &cap_bps);
+    Ok(())
+}
+
+pub fn is_cap_exempt(env: &Env, account: &Address) -> bool {
+    storage::get_persistent(env, &ComplianceKey::CapExempt(account.clone())).unwrap_or(false)
+}
+
+pub fn set_cap_exempt(env: &Env, account: &Address, exempt: bool) {
+    let key = ComplianceKey::CapExempt(account.clone());
+    if exempt {
+        storage::set_persistent(env, &key, &true);
+    } else {
+        storage::remove_persistent(env, &key);
+    }
+}
+
+/// Check that `holder` may end up with `new_balance` out of `total_supply`
+pub fn check_holder_cap(
+    env: &Env,
+    holder: &Address,
+    new_balance: i128,
+    total_supply: i128,
+) -> Result<(), Error> {
+    let cap_bps = get_holder_cap(env);
+    if cap_bps == 0 || *holder == storage::get_admin(env) || is_cap_exempt(env, holder) {
+        return Ok(());
+    }
+
+    // new_balance / total_supply > cap_bps / 10000, without rounding
+    let limit = total_supply.checked_mul(cap_bps).ok_or(Error::InvalidAmount)?;
+    let share = new_balance.checked_mul(10000).ok_or(Error::InvalidAmount)?;
+    if share > limit {
+        return Err(Error::HolderCapExceeded);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,264 @@
+// Rental income distribution to property token holders
+//
+// Uses a cumulative "points per share" accumulator per settlement asset so a
+// deposit is O(1) regardless of the number of holders:
+//
+//   earned(holder) = (points_per_share * balance(holder) + correction(holder)) / MAGNITUDE
+//
+// Every balance change adjusts the holder's correction by
+// `-points_per_share * delta`, which freezes each holder's share of a deposit
+// at the balance they held when it was recorded.
+//
+// Custody contracts (marketplace, escrow, offering) hold tokens for others and
+// can't claim. Once flagged as custodians their balances earn nothing and are
+// left out of the supply a deposit is divided over, so the income goes to the
+// holders who can claim it.
+
+use soroban_sdk::{contracttype, token, Address, Env, Vec};
+use stellar_property_errors::TokenError as Error;
+use stellar_property_events as events;
+use stellar_property_storage as storage;
+
+// Fixed-point scale for points per share
+const MAGNITUDE: i128 = 1_000_000_000_000;
+
+// SECURITY: Maximum settlement assets, bounds the work done on every transfer
+const MAX_DISTRIBUTION_ASSETS: u32 = 10;
+
+#[contracttype]
+#[derive(Clone)]
+enum DistributionKey {
+    Manager,
+    Assets,
+    Counter,
+    Distribution(u64),
+    PointsPerShare(Address),
+    Correction(Address, Address), // (asset, holder)
+    Claimed(Address, Address),    // (asset, holder)
+    TotalDeposited(Address),
+    TotalClaimed(Address),
+    Custodian(Address),
+    CustodySupply,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Distribution {
+    pub id: u64,
+    pub asset: Address,
+    pub amount: i128,
+    pub period: u64,      // Manager-defined period label (e.g. 202501)
+    pub supply: i128,     // Supply the deposit was divided over
+    pub ledger: u32,      // Ledger at which holder balances were recorded
+    pub timestamp: u64,
+}
+
+/// Property manager allowed to deposit income (defaults to the admin)
+pub fn get_manager(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DistributionKey::Manager)
+        .unwrap_or_else(|| storage::get_admin(env))
+}
+
+pub fn set_manager(env: &Env, manager: &Address) {
+    env.storage().instance().set(&DistributionKey::Manager, manager);
+}
+
+/// Settlement assets that have received at least one deposit
+pub fn get_assets(env: &Env) -> Vec<Address> {
+    storage::get_persistent(env, &DistributionKey::Assets).unwrap_or(Vec::new(env))
+}
+
+fn points_per_share(env: &Env, asset: &Address) -> i128 {
+    storage::get_persistent(env, &DistributionKey::PointsPerShare(asset.clone())).unwrap_or(0)
+}
+
+fn correction(env: &Env, asset: &Address, holder: &Address) -> i128 {
+    storage::get_persistent(env, &DistributionKey::Correction(asset.clone(), holder.clone()))
+        .unwrap_or(0)
+}
+
+pub fn claimed(env: &Env, asset: &Address, holder: &Address) -> i128 {
+    storage::get_persistent(env, &DistributionKey::Claimed(asset.clone(), holder.clone()))
+        .unwrap_or(0)
+}
+
+pub fn total_deposited(env: &Env, asset: &Address) -> i128 {
+    storage::get_persistent(env, &DistributionKey::TotalDeposited(asset.clone())).unwrap_or(0)
+}
+
+pub fn total_claimed(env: &Env, asset: &Address) -> i128 {
+    storage::get_persistent(env, &DistributionKey::TotalClaimed(asset.clone())).unwrap_or(0)
+}
+
+pub fn get_distribution(env: &Env, distribution_id: u64) -> Option<Distribution> {
+    storage::get_persistent(env, &DistributionKey::Distribution(distribution_id))
+}
+
+/// Whether `account` holds tokens in custody and earns no income
+pub fn is_custodian(env: &Env, account: &Address) -> bool {
+    storage::has_persistent(env, &DistributionKey::Custodian(account.clone()))
+}
+
+/// Tokens held by custodians, left out of every deposit
+pub fn custody_supply(env: &Env) -> i128 {
+    storage::get_persistent(env, &DistributionKey::CustodySupply).unwrap_or(0)
+}
+
+fn add_custody_supply(env: &Env, delta: i128) -> Result<(), Error> {
+    let new_supply = custody_supply(env).checked_add(delta).ok_or(Error::InvalidAmount)?;
+    storage::set_persistent(env, &DistributionKey::CustodySupply, &new_supply);
+    Ok(())
+}
+
+/// Flag or unflag `account` as a custodian
+/// Income it earned before is kept; its balance stops or starts earning now
+pub fn set_custodian(env: &Env, account: &Address, custodian: bool) -> Result<(), Error> {
+    if is_custodian(env, account) == custodian {
+        return Ok(());
+    }
+
+    let balance = storage::get_balance(env, account);
+    if custodian {
+        on_balance_change(env, account, -balance)?;
+        storage::set_persistent(env, &DistributionKey::Custodian(account.clone()), &true);
+        add_custody_supply(env, balance)
+    } else {
+        storage::remove_persistent(env, &DistributionKey::Custodian(account.clone()));
+        add_custody_supply(env, -balance)?;
+        on_balance_change(env, account, balance)
+    }
+}
+
+/// Amount of `asset` earned by `holder` and not yet claimed
+pub fn claimable(env: &Env, asset: &Address, holder: &Address) -> Result<i128, Error> {
+    let balance = if is_custodian(env, holder) { 0 } else { storage::get_balance(env, holder) };
+    let earned = points_per_share(env, asset)
+        .checked_mul(balance)
+        .and_then(|v| v.checked_add(correction(env, asset, holder)))
+        .ok_or(Error::InvalidAmount)?
+        / MAGNITUDE;
+
+    Ok(earned - claimed(env, asset, holder))
+}
+
+/// Keep accrued income stable across a balance change of `delta` for `holder`
+pub fn on_balance_change(env: &Env, holder: &Address, delta: i128) -> Result<(), Error> {
+    if delta == 0 {
+        return Ok(());
+    }
+
+    if is_custodian(env, holder) {
+        return add_custody_supply(env, delta);
+    }
+
+    for asset in get_assets(env).iter() {
+        let pps = points_per_share(env, &asset);
+        if pps == 0 {
+            continue;
+        }
+
+        let adjustment = pps.checked_mul(delta).ok_or(Error::InvalidAmount)?;
+        let new_correction = correction(env, &asset, holder)
+            .checked_sub(adjustment)
+            .ok_or(Error::InvalidAmount)?;
+        storage::set_persistent(
+            env,
+            &DistributionKey::Correction(asset.clone(), holder.clone()),
+            &new_correction,
+        );
+    }
+
+    Ok(())
+}
+
+/// Record a deposit of `amount` of `asset` for `period`, split over the
+/// part of `supply` held outside custody
+pub fn deposit(
+    env: &Env,
+    manager: &Address,
+    asset: &Address,
+    amount: i128,
+    period: u64,
+    supply: i128,
+) -> Result<u64, Error> {
+    let supply = supply.checked_sub(custody_supply(env)).ok_or(Error::InvalidAmount)?;
+    if amount <= 0 || supply <= 0 {
+        return Err(Error::InvalidAmount);
+    }
+
+    // Register the asset so transfers start tracking corrections for it
+    let mut assets = get_assets(env);
+    if !assets.contains(asset) {
+        // SECURITY: Enforce maximum settlement assets
+        if assets.len() >= MAX_DISTRIBUTION_ASSETS {
+            return Err(Error::InvalidAmount);
+        }
+        assets.push_back(asset.clone());
+        storage::set_persistent(env, &DistributionKey::Assets, &assets);
+    }
+
+    let increment = amount
+        .checked_mul(MAGNITUDE)
+        .ok_or(Error::InvalidAmount)?
+        / supply;
+    let new_pps = points_per_share(env, asset)
+        .checked_add(increment)
+        .ok_or(Error::InvalidAmount)?;
+    storage::set_persistent(env, &DistributionKey::PointsPerShare(asset.clone()), &new_pps);
+
+    let new_total = total_deposited(env, asset)
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    storage::set_persistent(env, &DistributionKey::TotalDeposited(asset.clone()), &new_total);
+
+    let distribution_id = storage::get_and_increment_counter(env, DistributionKey::Counter);
+    let distribution = Distribution {
+        id: distribution_id,
+        asset: asset.clone(),
+        amount,
+        period,
+        supply,
+        ledger: env.ledger().sequence(),
+        timestamp: env.ledger().timestamp(),
+    };
+    storage::set_persistent(env, &DistributionKey::Distribution(distribution_id), &distribution);
+
+    // Pull the income into the token contract
+    let asset_client = token::TokenClient::new(env, asset);
+    asset_client.transfer(manager, &env.current_contract_address(), &amount);
+
+    events::distribution_deposited(env, distribution_id, asset.clone(), amount, period);
+
+    Ok(distribution_id)
+}
+
+/// Pay out everything `holder` has earned in `asset`
+pub fn claim(env: &Env, holder: &Address, asset: &Address) -> Result<i128, Error> {
+    let amount = claimable(env, asset, holder)?;
+    if amount <= 0 {
+        return Ok(0);
+    }
+
+    let new_claimed = claimed(env, asset, holder)
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    storage::set_persistent(
+        env,
+        &DistributionKey::Claimed(asset.clone(), holder.clone()),
+        &new_claimed,
+    );
+
+    let new_total = total_claimed(env, asset)
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    storage::set_persistent(env, &DistributionKey::TotalClaimed(asset.clone()), &new_total);
+
+    let asset_client = token::TokenClient::new(env, asset);
+    asset_client.transfer(&env.current_contract_address(), holder, &amount);
+
+    events::distribution_claimed(env, holder.clone(), asset.clone(), amount);
+
+    Ok(amount)
+}
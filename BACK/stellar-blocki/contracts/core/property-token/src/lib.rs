@@ -1,12 +1,36 @@
 #![no_std]
 
-use soroban_sdk::{contract, contractimpl, contracttype, token, Address, Env, String, Symbol, Vec};
-use stellar_property_errors::Error;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, panic_with_error,
+    token::{self, TokenInterface},
+    Address, Bytes, BytesN, Env, Map, String, Symbol, Vec,
+};
+use stellar_property_access::{self as access, Role};
+use stellar_property_errors::TokenError as Error;
 use stellar_property_events as events;
 use stellar_property_storage::{self as storage, DataKey};
 
-// SECURITY: Maximum number of token owners to prevent unbounded storage
-const MAX_OWNERS: u32 = 1000;
+mod checkpoints;
+mod compliance;
+mod controls;
+mod distribution;
+mod migration;
+mod owners;
+mod permits;
+mod redemption;
+mod vesting;
+
+pub use compliance::{ComplianceConfig, CredentialType};
+pub use controls::ForcedTransfer;
+pub use distribution::Distribution;
+pub use permits::Permit;
+pub use redemption::Redemption;
+pub use vesting::VestingSchedule;
+
+// SECURITY: Maximum holders returned per page by `list_owners`
+const MAX_OWNERS_PAGE: u32 = 100;
+// SECURITY: Maximum recipients per `batch_transfer` / `batch_mint`
+const MAX_BATCH_SIZE: u32 = 100;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -26,6 +50,13 @@ pub struct OwnershipInfo {
     pub percentage: i128,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnersPage {
+    pub owners: Vec<OwnershipInfo>,
+    pub next_cursor: Option<u32>, // None when the last holder was returned
+}
+
 #[contract]
 pub struct PropertyTokenContract;
 
@@ -70,18 +101,15 @@ impl PropertyTokenContract {
 
         // Mint all tokens to admin automatically (decentralized pattern)
         // This ensures the property owner receives all tokens in a single transaction
-        storage::set_balance(&env, &admin, total_supply);
+        Self::write_balance(&env, &admin, total_supply)?;
         env.storage().persistent().set(&DataKey::TotalMinted, &total_supply);
-
-        // Track admin as owner
-        let mut owners_list = Vec::new(&env);
-        owners_list.push_back(admin.clone());
-        env.storage().persistent().set(&DataKey::OwnersList, &owners_list);
+        checkpoints::write_supply_change(&env, total_supply).ok_or(Error::InvalidAmount)?;
 
         // Emit mint event
-        events::mint(&env, admin.clone(), total_supply);
+        events::mint(&env, admin.clone(), admin.clone(), total_supply);
 
         // Mark as initialized
+        storage::set_schema_version(&env, migration::SCHEMA_VERSION);
         storage::set_initialized(&env);
 
         Ok(())
@@ -90,508 +118,2258 @@ impl PropertyTokenContract {
     /// Mint tokens to an address with ownership percentage
     pub fn mint(
         env: Env,
+        caller: Address,
         to: Address,
         amount: i128,
         percentage: i128,
     ) -> Result<(), Error> {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
-
-        // Validations
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
-        }
+        access::require_role(&env, Role::Minter, &caller).map_err(|_| Error::NotAuthorized)?;
 
         if percentage <= 0 || percentage > 10000 {
             // Using basis points (10000 = 100%)
             return Err(Error::InvalidPercentage);
         }
 
-        // Check if minting would exceed total supply
-        let metadata: TokenMetadata = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, "metadata"))
-            .unwrap();
-
-        let current_supply = Self::total_minted(env.clone());
-        let new_total = current_supply.checked_add(amount)
-            .ok_or(Error::InvalidAmount)?;
-
-        if new_total > metadata.total_supply {
-            return Err(Error::MintExceedsSupply);
-        }
+        Self::mint_tokens(&env, &caller, &to, amount)
+    }
 
-        // Mint tokens
-        let current_balance = storage::get_balance(&env, &to);
-        let new_balance = current_balance.checked_add(amount)
-            .ok_or(Error::InvalidAmount)?;
-        storage::set_balance(&env, &to, new_balance);
+    /// Mint tokens to `to` under a vesting schedule (minter role)
+    /// Tokens stay locked for `cliff_seconds`, then vest linearly over `vesting_seconds`
+    pub fn mint_vesting(
+        env: Env,
+        caller: Address,
+        to: Address,
+        amount: i128,
+        cliff_seconds: u64,
+        vesting_seconds: u64,
+        revocable: bool,
+    ) -> Result<u32, Error> {
+        access::require_role(&env, Role::Minter, &caller).map_err(|_| Error::NotAuthorized)?;
 
-        // Update total minted
-        env.storage().persistent().set(&DataKey::TotalMinted, &new_total);
+        Self::mint_tokens(&env, &caller, &to, amount)?;
 
-        // Track owner if new
-        Self::add_owner_to_list(&env, &to)?;
+        Self::add_vesting_schedule(&env, &to, amount, cliff_seconds, vesting_seconds, revocable)
+    }
 
-        // Emit mint event
-        events::mint(&env, to.clone(), amount);
+    /// Mint to many recipients at once (minter role)
+    /// The whole batch is validated before any balance changes
+    pub fn batch_mint(
+        env: Env,
+        caller: Address,
+        recipients: Vec<(Address, i128)>,
+    ) -> Result<(), Error> {
+        access::require_role(&env, Role::Minter, &caller).map_err(|_| Error::NotAuthorized)?;
 
-        Ok(())
+        Self::mint_batch(&env, &caller, &recipients)
     }
 
-    /// Transfer tokens from one address to another
-    pub fn transfer(
+    /// Transfer from `from` to many recipients at once
+    /// The whole batch is validated before any balance changes
+    pub fn batch_transfer(
         env: Env,
         from: Address,
-        to: Address,
-        amount: i128,
+        recipients: Vec<(Address, i128)>,
     ) -> Result<(), Error> {
         from.require_auth();
 
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
+        controls::check_accounts(&env, &[&from])?;
+        for (to, _) in recipients.iter() {
+            controls::check_accounts(&env, &[&to])?;
         }
 
-        // Check balance
+        let (credits, total) = Self::collect_batch(&env, &recipients)?;
+        vesting::check_unlocked(&env, &from, total)?;
+
         let from_balance = storage::get_balance(&env, &from);
-        if from_balance < amount {
+        if from_balance < total {
             return Err(Error::InsufficientBalance);
         }
 
-        // Update balances with checked arithmetic
-        let new_from_balance = from_balance.checked_sub(amount)
+        // Final balances, `from` may also be a recipient
+        let new_from_balance = from_balance
+            .checked_sub(total)
+            .and_then(|balance| balance.checked_add(credits.get(from.clone()).unwrap_or(0)))
             .ok_or(Error::InvalidAmount)?;
-        storage::set_balance(&env, &from, new_from_balance);
+        let mut new_balances: Map<Address, i128> = Map::new(&env);
+        for (to, credit) in credits.iter() {
+            if to == from {
+                continue;
+            }
+            let new_balance = storage::get_balance(&env, &to)
+                .checked_add(credit)
+                .ok_or(Error::InvalidAmount)?;
+            new_balances.set(to, new_balance);
+        }
 
-        let to_balance = storage::get_balance(&env, &to);
-        let new_to_balance = to_balance.checked_add(amount)
-            .ok_or(Error::InvalidAmount)?;
-        storage::set_balance(&env, &to, new_to_balance);
+        // Every receiver must pass compliance before anything is written
+        let total_supply = Self::total_supply(env.clone());
+        for to in credits.keys().iter() {
+            compliance::check_receiver(&env, &to)?;
+            let new_balance = new_balances.get(to.clone()).unwrap_or(new_from_balance);
+            compliance::check_holder_cap(&env, &to, new_balance, total_supply)?;
+        }
 
-        // Track new owner if needed
-        Self::add_owner_to_list(&env, &to)?;
+        Self::write_balance(&env, &from, new_from_balance)?;
+        for (to, new_balance) in new_balances.iter() {
+            Self::write_balance(&env, &to, new_balance)?;
+        }
 
-        // Emit transfer event
-        events::transfer(&env, from, to, amount);
+        for (to, amount) in recipients.iter() {
+            events::transfer(&env, from.clone(), to, amount);
+        }
 
         Ok(())
     }
 
-    /// Transfer tokens on behalf of another address (requires allowance)
-    pub fn transfer_from(
+    /// Move tokens from the admin's holdings to `to` under a vesting schedule (admin only)
+    /// Used when the whole supply was issued to the admin at initialization
+    pub fn grant_vesting(
         env: Env,
-        spender: Address,
-        from: Address,
         to: Address,
         amount: i128,
-    ) -> Result<(), Error> {
-        spender.require_auth();
+        cliff_seconds: u64,
+        vesting_seconds: u64,
+        revocable: bool,
+    ) -> Result<u32, Error> {
+        let admin = access::require_admin(&env);
 
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
-        }
+        controls::check_accounts(&env, &[&admin, &to])?;
+        vesting::check_unlocked(&env, &admin, amount)?;
+        Self::move_balance(&env, &admin, &to, amount)?;
 
-        // Check allowance
-        let allowance = storage::get_allowance(&env, &from, &spender);
-        if allowance < amount {
-            return Err(Error::InsufficientAllowance);
-        }
+        events::transfer(&env, admin, to.clone(), amount);
 
-        // Check balance
-        let from_balance = storage::get_balance(&env, &from);
-        if from_balance < amount {
-            return Err(Error::InsufficientBalance);
-        }
+        Self::add_vesting_schedule(&env, &to, amount, cliff_seconds, vesting_seconds, revocable)
+    }
 
-        // Update allowance with checked arithmetic
-        let new_allowance = allowance.checked_sub(amount)
-            .ok_or(Error::InvalidAmount)?;
-        storage::set_allowance(&env, &from, &spender, new_allowance);
+    /// Cancel a revocable schedule; unvested tokens return to the admin (admin only)
+    pub fn revoke_vesting(env: Env, holder: Address, schedule_index: u32) -> Result<i128, Error> {
+        let admin = access::require_admin(&env);
 
-        // Update balances with checked arithmetic
-        let new_from_balance = from_balance.checked_sub(amount)
-            .ok_or(Error::InvalidAmount)?;
-        storage::set_balance(&env, &from, new_from_balance);
+        let unvested = vesting::revoke(&env, &holder, schedule_index)?;
 
-        let to_balance = storage::get_balance(&env, &to);
-        let new_to_balance = to_balance.checked_add(amount)
-            .ok_or(Error::InvalidAmount)?;
-        storage::set_balance(&env, &to, new_to_balance);
+        if unvested > 0 {
+            Self::move_balance(&env, &holder, &admin, unvested)?;
+            events::transfer(&env, holder.clone(), admin, unvested);
+        }
 
-        // Track new owner if needed
-        Self::add_owner_to_list(&env, &to)?;
+        events::vesting_revoked(&env, holder, schedule_index, unvested);
 
-        // Emit transfer event
-        events::transfer(&env, from, to, amount);
+        Ok(unvested)
+    }
 
-        Ok(())
+    /// Get all vesting schedules of `holder`
+    pub fn vesting_schedules(env: Env, holder: Address) -> Vec<VestingSchedule> {
+        vesting::get_schedules(&env, &holder)
     }
 
-    /// Approve spender to transfer tokens on behalf of owner
-    pub fn approve(
-        env: Env,
-        owner: Address,
-        spender: Address,
-        amount: i128,
-    ) -> Result<(), Error> {
-        owner.require_auth();
+    /// Get the unvested part of `holder`'s balance
+    pub fn locked_balance(env: Env, holder: Address) -> i128 {
+        vesting::locked(&env, &holder)
+    }
 
-        if amount < 0 {
-            return Err(Error::InvalidAmount);
+    /// Get the part of `holder`'s balance that can be transferred now
+    pub fn releasable_balance(env: Env, holder: Address) -> i128 {
+        let balance = storage::get_balance(&env, &holder);
+        let locked = vesting::locked(&env, &holder);
+        if balance > locked {
+            balance - locked
+        } else {
+            0
         }
+    }
 
-        storage::set_allowance(&env, &owner, &spender, amount);
+    // ========== CHECKPOINTS ==========
 
-        Ok(())
+    /// Record the current ledger as a snapshot and return its id
+    /// Balances at the snapshot are read with `balance_at(owner, snapshot_ledger(id))`
+    pub fn snapshot(env: Env) -> u64 {
+        checkpoints::snapshot(&env)
     }
 
-    /// Burn tokens from an address
-    pub fn burn(env: Env, from: Address, amount: i128) -> Result<(), Error> {
-        from.require_auth();
+    /// Get the ledger recorded for a snapshot
+    pub fn snapshot_ledger(env: Env, snapshot_id: u64) -> Option<u32> {
+        checkpoints::snapshot_ledger(&env, snapshot_id)
+    }
 
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
-        }
+    /// Get the balance `owner` held at the end of `ledger`
+    pub fn balance_at(env: Env, owner: Address, ledger: u32) -> i128 {
+        checkpoints::balance_at(&env, &owner, ledger)
+    }
 
-        let balance = storage::get_balance(&env, &from);
-        if balance < amount {
-            return Err(Error::InsufficientBalance);
-        }
+    /// Get the circulating supply (minted minus burned) at the end of `ledger`
+    pub fn total_supply_at(env: Env, ledger: u32) -> i128 {
+        checkpoints::supply_at(&env, ledger)
+    }
 
-        // Use checked arithmetic to prevent underflow
-        let new_balance = balance.checked_sub(amount)
-            .ok_or(Error::InvalidAmount)?;
-        storage::set_balance(&env, &from, new_balance);
+    // ========== RENTAL DISTRIBUTIONS ==========
 
-        events::burn(&env, from, amount);
+    /// Set the property manager allowed to deposit rental income (admin only)
+    pub fn set_distribution_manager(env: Env, manager: Address) -> Result<(), Error> {
+        access::require_admin(&env);
+
+        distribution::set_manager(&env, &manager);
+        events::distribution_manager_set(&env, manager);
 
         Ok(())
     }
 
-    // ========== VIEW FUNCTIONS ==========
+    /// Flag `account` as a custody contract (marketplace, escrow, offering)
+    /// whose balance earns no income, or clear the flag (admin only)
+    pub fn set_distribution_custodian(env: Env, account: Address, custodian: bool) -> Result<(), Error> {
+        access::require_admin(&env);
 
-    /// Get balance of an address
-    pub fn balance_of(env: Env, owner: Address) -> i128 {
-        storage::get_balance(&env, &owner)
+        distribution::set_custodian(&env, &account, custodian)?;
+        events::distribution_custodian_set(&env, account, custodian);
+
+        Ok(())
     }
 
-    /// Get allowance of spender for owner
-    pub fn allowance(env: Env, owner: Address, spender: Address) -> i128 {
-        storage::get_allowance(&env, &owner, &spender)
+    /// Check whether `account` is flagged as a custody contract
+    pub fn is_distribution_custodian(env: Env, account: Address) -> bool {
+        distribution::is_custodian(&env, &account)
     }
 
-    /// Get ownership percentage for an address (in basis points: 10000 = 100%)
-    pub fn get_ownership_percentage(env: Env, owner: Address) -> i128 {
-        let balance = storage::get_balance(&env, &owner);
-        let metadata: TokenMetadata = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, "metadata"))
-            .unwrap();
+    /// Deposit rental income in any SEP-41 `asset` for `period`
+    /// Each holder's share is fixed by their balance at this ledger; balances
+    /// held by custodians are left out
+    pub fn deposit_distribution(
+        env: Env,
+        asset: Address,
+        amount: i128,
+        period: u64,
+    ) -> Result<u64, Error> {
+        let manager = distribution::get_manager(&env);
+        manager.require_auth();
 
-        if metadata.total_supply == 0 {
-            return 0;
-        }
+        let supply = checkpoints::current_supply(&env);
+        distribution::deposit(&env, &manager, &asset, amount, period, supply)
+    }
 
-        // Calculate percentage in basis points with checked arithmetic
-        balance.checked_mul(10000)
-            .and_then(|v| v.checked_div(metadata.total_supply))
-            .unwrap_or(0)
+    /// Claim all income earned by `holder` in `asset`, returns the amount paid
+    pub fn claim_distribution(env: Env, holder: Address, asset: Address) -> Result<i128, Error> {
+        holder.require_auth();
+
+        distribution::claim(&env, &holder, &asset)
     }
 
-    /// List all owners with their balances (for small number of owners)
-    /// Note: This is expensive and should be used sparingly
-    pub fn list_all_owners(env: Env) -> Vec<OwnershipInfo> {
-        let owners_list: Vec<Address> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::OwnersList)
-            .unwrap_or(Vec::new(&env));
+    /// Get income earned by `holder` in `asset` that has not been claimed yet
+    pub fn claimable_distribution(env: Env, holder: Address, asset: Address) -> Result<i128, Error> {
+        distribution::claimable(&env, &asset, &holder)
+    }
 
-        let mut result = Vec::new(&env);
+    /// Get income already claimed by `holder` in `asset`
+    pub fn claimed_distribution(env: Env, holder: Address, asset: Address) -> i128 {
+        distribution::claimed(&env, &asset, &holder)
+    }
 
-        for i in 0..owners_list.len() {
-            let owner = owners_list.get(i).unwrap();
-            let balance = storage::get_balance(&env, &owner);
+    /// Get total income in `asset` deposited but not yet claimed by holders
+    pub fn unclaimed_distribution_total(env: Env, asset: Address) -> i128 {
+        distribution::total_deposited(&env, &asset) - distribution::total_claimed(&env, &asset)
+    }
 
-            // Only include owners with non-zero balance
-            if balance > 0 {
-                let percentage = Self::get_ownership_percentage(env.clone(), owner.clone());
-                result.push_back(OwnershipInfo {
-                    owner,
-                    balance,
-                    percentage,
-                });
-            }
-        }
+    /// Get a recorded distribution
+    pub fn get_distribution(env: Env, distribution_id: u64) -> Option<Distribution> {
+        distribution::get_distribution(&env, distribution_id)
+    }
 
-        result
+    /// Get the property manager allowed to deposit income
+    pub fn distribution_manager(env: Env) -> Address {
+        distribution::get_manager(&env)
     }
 
-    /// Get token metadata
-    pub fn metadata(env: Env) -> TokenMetadata {
-        env.storage()
-            .instance()
-            .get(&Symbol::new(&env, "metadata"))
-            .unwrap()
+    // ========== COMPLIANCE ==========
+
+    /// Configure receiver checks for mint, transfer and transfer_from (compliance officer role)
+    /// Receivers pass if allowlisted, or if they hold the required ZKVerifier credentials
+    pub fn set_compliance(
+        env: Env,
+        caller: Address,
+        verifier: Option<Address>,
+        require_kyc: bool,
+        require_accredited: bool,
+        use_allowlist: bool,
+    ) -> Result<(), Error> {
+        access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        let config = ComplianceConfig {
+            verifier: verifier.clone(),
+            require_kyc,
+            require_accredited,
+            use_allowlist,
+        };
+        compliance::set_config(&env, &config)?;
+
+        events::compliance_updated(&env, verifier, require_kyc, require_accredited, use_allowlist);
+
+        Ok(())
     }
 
-    /// Get total supply
-    pub fn total_supply(env: Env) -> i128 {
-        let metadata: TokenMetadata = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, "metadata"))
-            .unwrap();
-        metadata.total_supply
+    /// Remove all compliance checks (compliance officer role)
+    pub fn clear_compliance(env: Env, caller: Address) -> Result<(), Error> {
+        access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        compliance::clear_config(&env);
+
+        events::compliance_updated(&env, None, false, false, false);
+
+        Ok(())
     }
 
-    /// Get total minted tokens
-    fn total_minted(env: Env) -> i128 {
-        env.storage()
-            .persistent()
-            .get(&DataKey::TotalMinted)
-            .unwrap_or(0)
+    /// Add or remove an account from the allowlist (compliance officer role)
+    pub fn set_allowlisted(env: Env, caller: Address, account: Address, allowed: bool) -> Result<(), Error> {
+        access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        compliance::set_allowlisted(&env, &account, allowed);
+
+        events::allowlist_updated(&env, account, allowed);
+
+        Ok(())
     }
 
-    /// Add owner to tracking list if not already present
-    /// SECURITY: Bounded to MAX_OWNERS to prevent unbounded storage growth
-    fn add_owner_to_list(env: &Env, owner: &Address) -> Result<(), Error> {
-        let mut owners_list: Vec<Address> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::OwnersList)
-            .unwrap_or(Vec::new(env));
-
-        // Check if owner already exists in list
-        let mut exists = false;
-        for i in 0..owners_list.len() {
-            if owners_list.get(i).unwrap() == *owner {
-                exists = true;
-                break;
-            }
-        }
+    /// Limit any single holder to `cap_bps` of the total supply, 0 to disable (compliance officer role)
+    pub fn set_holder_cap(env: Env, caller: Address, cap_bps: i128) -> Result<(), Error> {
+        access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
 
-        // Add if new owner (with bounds check)
-        if !exists {
-            // SECURITY: Enforce maximum owners limit
-            if owners_list.len() >= MAX_OWNERS {
-                return Err(Error::InvalidAmount); // Reusing error code, consider adding MaxOwnersReached
-            }
-            owners_list.push_back(owner.clone());
-            env.storage().persistent().set(&DataKey::OwnersList, &owners_list);
-        }
+        compliance::set_holder_cap(&env, cap_bps)?;
+
+        events::holder_cap_set(&env, cap_bps);
 
         Ok(())
     }
 
-    /// Get contract admin
-    pub fn admin(env: Env) -> Address {
-        storage::get_admin(&env)
+    /// Exempt an account from the holder cap, e.g. escrow or marketplace (compliance officer role)
+    pub fn set_cap_exempt(env: Env, caller: Address, account: Address, exempt: bool) -> Result<(), Error> {
+        access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        compliance::set_cap_exempt(&env, &account, exempt);
+
+        events::cap_exemption_updated(&env, account, exempt);
+
+        Ok(())
     }
 
-    // ========== SEP-41 STANDARD FUNCTIONS ==========
+    /// Get the holder cap in basis points (0 = no cap)
+    pub fn holder_cap(env: Env) -> i128 {
+        compliance::get_holder_cap(&env)
+    }
 
-    /// Get token symbol (SEP-41)
-    pub fn symbol(env: Env) -> String {
-        let metadata: TokenMetadata = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, "metadata"))
-            .unwrap();
-        metadata.symbol
+    pub fn is_cap_exempt(env: Env, account: Address) -> bool {
+        compliance::is_cap_exempt(&env, &account)
     }
 
-    /// Get token name (SEP-41)
-    pub fn name(env: Env) -> String {
-        let metadata: TokenMetadata = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, "metadata"))
-            .unwrap();
-        metadata.name
+    /// Get current compliance rules, if any
+    pub fn compliance_config(env: Env) -> Option<ComplianceConfig> {
+        compliance::get_config(&env)
     }
 
-    /// Get token decimals (SEP-41)
-    pub fn decimals(env: Env) -> u32 {
-        let metadata: TokenMetadata = env
-            .storage()
-            .instance()
-            .get(&Symbol::new(&env, "metadata"))
-            .unwrap();
-        metadata.decimals
+    pub fn is_allowlisted(env: Env, account: Address) -> bool {
+        compliance::is_allowlisted(&env, &account)
     }
 
-    /// Get balance (SEP-41 alias)
-    pub fn balance(env: Env, id: Address) -> i128 {
-        Self::balance_of(env, id)
+    /// Check whether `account` may currently receive tokens
+    pub fn can_receive(env: Env, account: Address) -> bool {
+        compliance::check_receiver(&env, &account).is_ok()
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::{testutils::Address as _, Env, String};
+    // ========== PERMITS ==========
 
-    #[test]
-    fn test_initialize() {
-        let env = Env::default();
-        env.mock_all_auths(); // Mock authorization for testing
+    /// Set an allowance from the owner's off-chain signature (anyone can submit)
+    /// `signature` is the owner account's ed25519 signature of `permit_message`
+    pub fn permit(env: Env, permit: Permit, signature: BytesN<64>) -> Result<(), Error> {
+        if permit.amount < 0 {
+            return Err(Error::InvalidAmount);
+        }
 
-        let contract_id = env.register_contract(None, PropertyTokenContract);
-        let client = PropertyTokenContractClient::new(&env, &contract_id);
+        permits::consume(&env, &permit, &signature)?;
 
-        let admin = Address::generate(&env);
-        let property_id = Symbol::new(&env, "PROP001");
-        let name = String::from_str(&env, "Property Token");
-        let symbol = String::from_str(&env, "PROP");
-        let total_supply = 1_000_000_0000000; // 1M with 7 decimals
+        storage::set_allowance(
+            &env,
+            &permit.owner,
+            &permit.spender,
+            permit.amount,
+            permit.expiration_ledger,
+        );
 
-        client.initialize(&admin, &property_id, &name, &symbol, &total_supply);
+        events::approve(&env, permit.owner, permit.spender, permit.amount, permit.expiration_ledger);
 
-        let metadata = client.metadata();
-        assert_eq!(metadata.property_id, property_id);
-        assert_eq!(metadata.total_supply, total_supply);
+        Ok(())
     }
 
-    #[test]
-    fn test_mint_and_balance() {
-        let env = Env::default();
-        env.mock_all_auths(); // Mock authorization for testing
+    /// Nonce the next permit of `owner` must carry
+    pub fn nonce(env: Env, owner: Address) -> u64 {
+        permits::nonce(&env, &owner)
+    }
 
-        let contract_id = env.register_contract(None, PropertyTokenContract);
-        let client = PropertyTokenContractClient::new(&env, &contract_id);
+    /// Bytes an owner signs to authorize `permit`
+    pub fn permit_message(env: Env, permit: Permit) -> Bytes {
+        permits::message(&env, &permit)
+    }
 
-        let admin = Address::generate(&env);
-        let user = Address::generate(&env);
-        let property_id = Symbol::new(&env, "PROP001");
-        let name = String::from_str(&env, "Property Token");
-        let symbol = String::from_str(&env, "PROP");
-        let total_supply = 1_000_000_0000000;
+    // ========== REDEMPTION ==========
 
-        client.initialize(&admin, &property_id, &name, &symbol, &total_supply);
+    /// Deposit sale proceeds and let holders redeem tokens pro rata (admin only)
+    /// After `deadline`, unclaimed proceeds go to `unclaimed_recipient`
+    pub fn open_redemption(
+        env: Env,
+        asset: Address,
+        proceeds: i128,
+        deadline: u64,
+        unclaimed_recipient: Address,
+    ) -> Result<(), Error> {
+        let admin = access::require_admin(&env);
+
+        let supply = checkpoints::current_supply(&env);
+        let redemption = redemption::open(
+            &env,
+            &admin,
+            &asset,
+            proceeds,
+            supply,
+            deadline,
+            &unclaimed_recipient,
+        )?;
 
-        let mint_amount = 100_000_0000000; // 100k tokens (10%)
-        client.mint(&user, &mint_amount, &1000); // 1000 basis points = 10%
+        events::redemption_opened(&env, asset, proceeds, redemption.supply, deadline);
 
-        assert_eq!(client.balance_of(&user), mint_amount);
+        Ok(())
     }
 
-    #[test]
-    fn test_transfer() {
-        let env = Env::default();
-        env.mock_all_auths(); // Mock authorization for testing
+    /// Burn `amount` tokens of `holder` for their share of the sale proceeds
+    pub fn redeem(env: Env, holder: Address, amount: i128) -> Result<i128, Error> {
+        holder.require_auth();
 
-        let contract_id = env.register_contract(None, PropertyTokenContract);
-        let client = PropertyTokenContractClient::new(&env, &contract_id);
+        controls::check_accounts(&env, &[&holder])?;
+        vesting::check_unlocked(&env, &holder, amount)?;
 
-        let admin = Address::generate(&env);
-        let user1 = Address::generate(&env);
-        let user2 = Address::generate(&env);
-        let property_id = Symbol::new(&env, "PROP001");
-        let name = String::from_str(&env, "Property Token");
-        let symbol = String::from_str(&env, "PROP");
-        let total_supply = 1_000_000_0000000;
+        Self::burn_balance(&env, &holder, amount)?;
+        let payout = redemption::pay(&env, &holder, amount)?;
 
-        client.initialize(&admin, &property_id, &name, &symbol, &total_supply);
+        events::burn(&env, holder.clone(), amount);
+        events::redeemed(&env, holder, amount, payout);
+
+        Ok(payout)
+    }
+
+    /// Send unclaimed proceeds to the designated recipient once the deadline passed
+    /// Anyone can call this; the recipient is fixed when redemption opens
+    pub fn close_redemption(env: Env) -> Result<i128, Error> {
+        let (redemption, unclaimed) = redemption::close(&env)?;
+
+        events::redemption_closed(&env, redemption.unclaimed_recipient, unclaimed);
+
+        Ok(unclaimed)
+    }
+
+    /// Get redemption terms and progress
+    pub fn get_redemption(env: Env) -> Option<Redemption> {
+        redemption::get(&env)
+    }
+
+    /// Get the payout for redeeming `amount` tokens
+    pub fn redemption_quote(env: Env, amount: i128) -> Result<i128, Error> {
+        let redemption = redemption::get(&env).ok_or(Error::RedemptionNotActive)?;
+        redemption::quote(&redemption, amount)
+    }
+
+    // ========== ISSUER CONTROLS ==========
+
+    /// Block all balance changes of `account` (compliance officer role)
+    pub fn freeze(env: Env, caller: Address, account: Address) -> Result<(), Error> {
+        access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        controls::set_frozen(&env, &account, true);
+
+        events::account_frozen(&env, caller, account);
+
+        Ok(())
+    }
+
+    /// Lift a freeze on `account` (compliance officer role)
+    pub fn unfreeze(env: Env, caller: Address, account: Address) -> Result<(), Error> {
+        access::require_role(&env, Role::ComplianceOfficer, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        controls::set_frozen(&env, &account, false);
+
+        events::account_unfrozen(&env, caller, account);
+
+        Ok(())
+    }
+
+    /// Halt all transfers, mints and burns (pauser role)
+    pub fn pause(env: Env, caller: Address) -> Result<(), Error> {
+        access::require_role(&env, Role::Pauser, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        controls::set_paused(&env, true);
+
+        events::paused(&env, caller);
+
+        Ok(())
+    }
+
+    /// Resume transfers, mints and burns (pauser role)
+    pub fn unpause(env: Env, caller: Address) -> Result<(), Error> {
+        access::require_role(&env, Role::Pauser, &caller).map_err(|_| Error::NotAuthorized)?;
+
+        controls::set_paused(&env, false);
+
+        events::unpaused(&env, caller);
+
+        Ok(())
+    }
+
+    /// Move tokens out of a lost, compromised or sanctioned wallet (admin only)
+    /// Ignores freeze and pause; tokens locked under vesting can't be moved
+    /// until released or revoked, and the receiver must still pass compliance checks
+    pub fn forced_transfer(
+        env: Env,
+        from: Address,
+        to: Address,
+        amount: i128,
+        reason: String,
+        document_hash: BytesN<32>,
+    ) -> Result<u64, Error> {
+        access::require_admin(&env);
+
+        // SECURITY: The legal reason and supporting document are mandatory for the audit trail
+        if reason.is_empty() {
+            return Err(Error::InvalidReason);
+        }
+        if document_hash.to_array() == [0u8; 32] {
+            return Err(Error::InvalidDocumentHash);
+        }
+
+        vesting::check_unlocked(&env, &from, amount)?;
+        Self::move_balance(&env, &from, &to, amount)?;
+
+        let record_id = controls::record_forced_transfer(&env, &from, &to, amount, &reason, &document_hash);
+
+        events::transfer(&env, from.clone(), to.clone(), amount);
+        events::forced_transfer(&env, record_id, from, to, amount, reason, document_hash.to_array());
+
+        Ok(record_id)
+    }
+
+    pub fn is_frozen(env: Env, account: Address) -> bool {
+        controls::is_frozen(&env, &account)
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        controls::is_paused(&env)
+    }
+
+    /// Get the audit record of a forced transfer
+    pub fn get_forced_transfer(env: Env, record_id: u64) -> Option<ForcedTransfer> {
+        controls::get_forced_transfer(&env, record_id)
+    }
+
+    // ========== ACCESS CONTROL ==========
+
+    /// Grant `role` to `account` (admin only)
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::grant_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Revoke `role` from `account` (admin only)
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::revoke_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Check whether `account` holds `role` (the admin holds every role)
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        access::has_role(&env, role, &account)
+    }
+
+    /// Nominate a new admin; takes effect once they call `accept_admin` (admin only)
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        access::propose_admin(&env, &new_admin);
+        Ok(())
+    }
+
+    /// Accept a pending admin nomination (pending admin only)
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        access::accept_admin(&env).map_err(|_| Error::NotAuthorized)?;
+        Ok(())
+    }
+
+    /// Get the nominated admin awaiting acceptance
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        access::pending_admin(&env)
+    }
+
+    // ========== UPGRADES ==========
+
+    /// Replace the contract code, keeping storage (admin only)
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        access::upgrade(&env, &new_wasm_hash);
+        Ok(())
+    }
+
+    /// Index and checkpoint up to `limit` holders of the legacy owner list
+    /// from `cursor` (admin only)
+    /// Returns the cursor to continue from, or None once the schema version is recorded
+    pub fn migrate(env: Env, cursor: u32, limit: u32) -> Result<Option<u32>, Error> {
+        access::require_admin(&env);
+
+        if limit == 0 || limit > migration::MAX_MIGRATION_BATCH {
+            return Err(Error::InvalidAmount);
+        }
+
+        if storage::get_schema_version(&env) < migration::SCHEMA_VERSION {
+            migration::migrate_supply(&env);
+        }
+
+        if let Some(next) = migration::migrate_owners(&env, cursor, limit) {
+            return Ok(Some(next));
+        }
+
+        migration::finish(&env);
+        Ok(None)
+    }
+
+    /// Storage layout version of this contract
+    pub fn schema_version(env: Env) -> u32 {
+        storage::get_schema_version(&env)
+    }
+
+    // ========== VIEW FUNCTIONS ==========
+
+    /// Get balance of an address
+    pub fn balance_of(env: Env, owner: Address) -> i128 {
+        storage::get_balance(&env, &owner)
+    }
+
+    /// Get ownership percentage for an address (in basis points: 10000 = 100%)
+    pub fn get_ownership_percentage(env: Env, owner: Address) -> i128 {
+        let balance = storage::get_balance(&env, &owner);
+        let metadata: TokenMetadata = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, "metadata"))
+            .unwrap();
+
+        if metadata.total_supply == 0 {
+            return 0;
+        }
+
+        // Calculate percentage in basis points with checked arithmetic
+        balance.checked_mul(10000)
+            .and_then(|v| v.checked_div(metadata.total_supply))
+            .unwrap_or(0)
+    }
+
+    /// List holders with their balances, `limit` at a time starting at `cursor`
+    /// Pass the returned `next_cursor` to fetch the following page; holders
+    /// leaving between calls may reorder the set, so read pages in one ledger
+    pub fn list_owners(env: Env, cursor: u32, limit: u32) -> OwnersPage {
+        let total_supply = Self::total_supply(env.clone());
+        let count = owners::count(&env);
+        let end = cursor.saturating_add(limit.min(MAX_OWNERS_PAGE)).min(count);
+
+        let mut owners = Vec::new(&env);
+        for index in cursor..end {
+            let owner = owners::owner_at(&env, index).unwrap();
+            let balance = storage::get_balance(&env, &owner);
+            let percentage = if total_supply > 0 {
+                balance.checked_mul(10000)
+                    .and_then(|v| v.checked_div(total_supply))
+                    .unwrap_or(0)
+            } else {
+                0
+            };
+
+            owners.push_back(OwnershipInfo {
+                owner,
+                balance,
+                percentage,
+            });
+        }
+
+        let next_cursor = if end < count { Some(end) } else { None };
+
+        OwnersPage { owners, next_cursor }
+    }
+
+    /// Get the number of holders with a non-zero balance
+    pub fn holder_count(env: Env) -> u32 {
+        owners::count(&env)
+    }
+
+    /// Get token metadata
+    pub fn metadata(env: Env) -> TokenMetadata {
+        env.storage()
+            .instance()
+            .get(&Symbol::new(&env, "metadata"))
+            .unwrap()
+    }
+
+    /// Get total supply
+    pub fn total_supply(env: Env) -> i128 {
+        let metadata: TokenMetadata = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(&env, "metadata"))
+            .unwrap();
+        metadata.total_supply
+    }
+
+    /// Get total minted tokens
+    fn total_minted(env: Env) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TotalMinted)
+            .unwrap_or(0)
+    }
+
+    /// Get contract admin
+    pub fn admin(env: Env) -> Address {
+        storage::get_admin(&env)
+    }
+
+    // ========== INTERNAL BALANCE HELPERS ==========
+
+    /// Mint `amount` new tokens to `to`
+    fn mint_tokens(env: &Env, minter: &Address, to: &Address, amount: i128) -> Result<(), Error> {
+        Self::mint_batch(env, minter, &Vec::from_array(env, [(to.clone(), amount)]))
+    }
+
+    /// Mint new tokens to every recipient, validating the whole batch first
+    fn mint_batch(env: &Env, minter: &Address, recipients: &Vec<(Address, i128)>) -> Result<(), Error> {
+        // Receivers must not be frozen, and the token not paused
+        for (to, _) in recipients.iter() {
+            controls::check_accounts(env, &[&to])?;
+        }
+
+        let (credits, total) = Self::collect_batch(env, recipients)?;
+
+        // SECURITY: New tokens would dilute the fixed redemption payout
+        if redemption::is_opened(env) {
+            return Err(Error::RedemptionAlreadyOpened);
+        }
+
+        // Check if minting would exceed total supply
+        let metadata: TokenMetadata = env
+            .storage()
+            .instance()
+            .get(&Symbol::new(env, "metadata"))
+            .unwrap();
+
+        let current_supply = Self::total_minted(env.clone());
+        let new_total = current_supply.checked_add(total)
+            .ok_or(Error::InvalidAmount)?;
+
+        if new_total > metadata.total_supply {
+            return Err(Error::MintExceedsSupply);
+        }
+
+        // Receivers must satisfy compliance rules
+        let mut new_balances: Map<Address, i128> = Map::new(env);
+        for (to, credit) in credits.iter() {
+            compliance::check_receiver(env, &to)?;
+
+            let new_balance = storage::get_balance(env, &to).checked_add(credit)
+                .ok_or(Error::InvalidAmount)?;
+            compliance::check_holder_cap(env, &to, new_balance, metadata.total_supply)?;
+            new_balances.set(to, new_balance);
+        }
+
+        // Mint tokens
+        for (to, new_balance) in new_balances.iter() {
+            Self::write_balance(env, &to, new_balance)?;
+        }
+
+        // Update total minted
+        env.storage().persistent().set(&DataKey::TotalMinted, &new_total);
+        checkpoints::write_supply_change(env, total).ok_or(Error::InvalidAmount)?;
+
+        // Emit one mint event per recipient
+        for (to, amount) in recipients.iter() {
+            events::mint(env, minter.clone(), to, amount);
+        }
+
+        Ok(())
+    }
+
+    /// Check batch size and amounts, and sum the credits of each recipient
+    fn collect_batch(
+        env: &Env,
+        recipients: &Vec<(Address, i128)>,
+    ) -> Result<(Map<Address, i128>, i128), Error> {
+        if recipients.is_empty() || recipients.len() > MAX_BATCH_SIZE {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut credits: Map<Address, i128> = Map::new(env);
+        let mut total: i128 = 0;
+        for (to, amount) in recipients.iter() {
+            if amount <= 0 {
+                return Err(Error::InvalidAmount);
+            }
+
+            total = total.checked_add(amount).ok_or(Error::InvalidAmount)?;
+            let credit = credits.get(to.clone()).unwrap_or(0).checked_add(amount)
+                .ok_or(Error::InvalidAmount)?;
+            credits.set(to, credit);
+        }
+
+        Ok((credits, total))
+    }
+
+    /// Record a vesting schedule and emit its event
+    fn add_vesting_schedule(
+        env: &Env,
+        holder: &Address,
+        amount: i128,
+        cliff_seconds: u64,
+        vesting_seconds: u64,
+        revocable: bool,
+    ) -> Result<u32, Error> {
+        let (index, schedule) =
+            vesting::add_schedule(env, holder, amount, cliff_seconds, vesting_seconds, revocable)?;
+
+        events::vesting_created(
+            env,
+            holder.clone(),
+            index,
+            amount,
+            schedule.cliff_time,
+            schedule.end_time,
+            revocable,
+        );
+
+        Ok(index)
+    }
+
+    /// Persist a new balance and notify balance-dependent modules
+    fn write_balance(env: &Env, holder: &Address, new_balance: i128) -> Result<(), Error> {
+        let old_balance = storage::get_balance(env, holder);
+        let delta = new_balance.checked_sub(old_balance)
+            .ok_or(Error::InvalidAmount)?;
+
+        distribution::on_balance_change(env, holder, delta)?;
+        storage::set_balance(env, holder, new_balance);
+        checkpoints::write_balance(env, holder, new_balance);
+        owners::on_balance_change(env, holder, old_balance, new_balance);
+
+        Ok(())
+    }
+
+    /// Move `amount` from `from` to `to` with checked arithmetic
+    fn move_balance(env: &Env, from: &Address, to: &Address, amount: i128) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Check balance
+        let from_balance = storage::get_balance(env, from);
+        if from_balance < amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        // Receiver must satisfy compliance rules
+        compliance::check_receiver(env, to)?;
+
+        // Update balances with checked arithmetic
+        let new_from_balance = from_balance.checked_sub(amount)
+            .ok_or(Error::InvalidAmount)?;
+        Self::write_balance(env, from, new_from_balance)?;
+
+        let to_balance = storage::get_balance(env, to);
+        let new_to_balance = to_balance.checked_add(amount)
+            .ok_or(Error::InvalidAmount)?;
+        compliance::check_holder_cap(env, to, new_to_balance, Self::total_supply(env.clone()))?;
+        Self::write_balance(env, to, new_to_balance)
+    }
+
+    /// Remove `amount` from `from` without crediting anyone
+    fn burn_balance(env: &Env, from: &Address, amount: i128) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let balance = storage::get_balance(env, from);
+        if balance < amount {
+            return Err(Error::InsufficientBalance);
+        }
+
+        // Use checked arithmetic to prevent underflow
+        let new_balance = balance.checked_sub(amount)
+            .ok_or(Error::InvalidAmount)?;
+        Self::write_balance(env, from, new_balance)?;
+
+        // Burned tokens leave the supply for good
+        let new_minted = Self::total_minted(env.clone()).checked_sub(amount)
+            .ok_or(Error::InvalidAmount)?;
+        env.storage().persistent().set(&DataKey::TotalMinted, &new_minted);
+
+        let mut metadata = Self::metadata(env.clone());
+        metadata.total_supply = metadata.total_supply.checked_sub(amount)
+            .ok_or(Error::InvalidAmount)?;
+        env.storage()
+            .instance()
+            .set(&Symbol::new(env, "metadata"), &metadata);
+
+        checkpoints::write_supply_change(env, -amount).ok_or(Error::InvalidAmount)?;
+
+        Ok(())
+    }
+
+    /// Consume `amount` of the allowance `spender` holds over `from`
+    /// Expired allowances are treated as zero (SEP-41)
+    fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let allowance = storage::get_allowance_value(env, from, spender);
+        let available = storage::get_allowance(env, from, spender);
+        if available < amount {
+            return Err(Error::InsufficientAllowance);
+        }
+
+        let new_allowance = available.checked_sub(amount)
+            .ok_or(Error::InvalidAmount)?;
+        storage::set_allowance(env, from, spender, new_allowance, allowance.expiration_ledger);
+
+        Ok(())
+    }
+}
+
+// ========== SEP-41 STANDARD FUNCTIONS ==========
+
+#[contractimpl]
+impl token::TokenInterface for PropertyTokenContract {
+    /// Get allowance of spender for owner (expired allowances return 0)
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        storage::get_allowance(&env, &from, &spender)
+    }
+
+    /// Approve spender to transfer tokens on behalf of owner until `expiration_ledger`
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+
+        if amount < 0 {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        // A non-zero allowance must not already be expired
+        if amount > 0 && expiration_ledger < env.ledger().sequence() {
+            panic_with_error!(&env, Error::InvalidAmount);
+        }
+
+        storage::set_allowance(&env, &from, &spender, amount, expiration_ledger);
+
+        events::approve(&env, from, spender, amount, expiration_ledger);
+    }
+
+    /// Get balance of an address
+    fn balance(env: Env, id: Address) -> i128 {
+        storage::get_balance(&env, &id)
+    }
+
+    /// Transfer tokens from one address to another
+    fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+
+        controls::check_accounts(&env, &[&from, &to])
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        vesting::check_unlocked(&env, &from, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        Self::move_balance(&env, &from, &to, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+
+        events::transfer(&env, from, to, amount);
+    }
+
+    /// Transfer tokens on behalf of another address (requires allowance)
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+
+        controls::check_accounts(&env, &[&spender, &from, &to])
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        vesting::check_unlocked(&env, &from, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        Self::spend_allowance(&env, &from, &spender, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        Self::move_balance(&env, &from, &to, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+
+        events::transfer(&env, from, to, amount);
+    }
+
+    /// Burn tokens from an address
+    fn burn(env: Env, from: Address, amount: i128) {
+        from.require_auth();
+
+        controls::check_accounts(&env, &[&from])
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        vesting::check_unlocked(&env, &from, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        Self::burn_balance(&env, &from, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+
+        events::burn(&env, from, amount);
+    }
+
+    /// Burn tokens on behalf of another address (requires allowance)
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+
+        controls::check_accounts(&env, &[&spender, &from])
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        vesting::check_unlocked(&env, &from, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        Self::spend_allowance(&env, &from, &spender, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+        Self::burn_balance(&env, &from, amount)
+            .unwrap_or_else(|err| panic_with_error!(&env, err));
+
+        events::burn(&env, from, amount);
+    }
+
+    /// Get token decimals
+    fn decimals(env: Env) -> u32 {
+        PropertyTokenContract::metadata(env).decimals
+    }
+
+    /// Get token name
+    fn name(env: Env) -> String {
+        PropertyTokenContract::metadata(env).name
+    }
+
+    /// Get token symbol
+    fn symbol(env: Env) -> String {
+        PropertyTokenContract::metadata(env).symbol
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::{
+        symbol_short,
+        testutils::{Address as _, Events, Ledger},
+        vec, Env, IntoVal, String,
+    };
+
+    fn setup<'a>(env: &Env, admin: &Address) -> PropertyTokenContractClient<'a> {
+        let contract_id = env.register(PropertyTokenContract, ());
+        let client = PropertyTokenContractClient::new(env, &contract_id);
+
+        let property_id = Symbol::new(env, "PROP001");
+        let name = String::from_str(env, "Property Token");
+        let symbol = String::from_str(env, "PROP");
+        let total_supply = 1_000_000_0000000;
+
+        client.initialize(admin, &property_id, &name, &symbol, &total_supply);
+        client
+    }
+
+    #[test]
+    fn test_initialize() {
+        let env = Env::default();
+        env.mock_all_auths(); // Mock authorization for testing
+
+        let contract_id = env.register(PropertyTokenContract, ());
+        let client = PropertyTokenContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let property_id = Symbol::new(&env, "PROP001");
+        let name = String::from_str(&env, "Property Token");
+        let symbol = String::from_str(&env, "PROP");
+        let total_supply = 1_000_000_0000000; // 1M with 7 decimals
+
+        client.initialize(&admin, &property_id, &name, &symbol, &total_supply);
+
+        let metadata = client.metadata();
+        assert_eq!(metadata.property_id, property_id);
+        assert_eq!(metadata.total_supply, total_supply);
+    }
+
+    #[test]
+    fn test_mint_and_balance() {
+        let env = Env::default();
+        env.mock_all_auths(); // Mock authorization for testing
+
+        let admin = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        // initialize mints the whole supply to the admin
+        assert_eq!(client.balance_of(&admin), 1_000_000_0000000);
+        assert_eq!(client.balance(&admin), client.total_supply());
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #14)")]
+    fn test_mint_exceeds_supply() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        client.mint(&admin, &user, &1, &1);
+    }
+
+    #[test]
+    fn test_transfer() {
+        let env = Env::default();
+        env.mock_all_auths(); // Mock authorization for testing
+
+        let admin = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let mint_amount = 100_000_0000000;
+        client.transfer(&admin, &user1, &mint_amount);
+
+        let transfer_amount = 25_000_0000000; // 25k tokens
+        client.transfer(&user1, &user2, &transfer_amount);
+
+        assert_eq!(client.balance_of(&user1), mint_amount - transfer_amount);
+        assert_eq!(client.balance_of(&user2), transfer_amount);
+    }
+
+    #[test]
+    fn test_approve_and_transfer_from() {
+        let env = Env::default();
+        env.mock_all_auths(); // Mock authorization for testing
+
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let spender = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let mint_amount = 100_000_0000000;
+        client.transfer(&admin, &owner, &mint_amount);
+
+        let allowance_amount = 50_000_0000000;
+        let expiration_ledger = env.ledger().sequence() + 1000;
+        client.approve(&owner, &spender, &allowance_amount, &expiration_ledger);
+
+        assert_eq!(client.allowance(&owner, &spender), allowance_amount);
+
+        let transfer_amount = 25_000_0000000;
+        client.transfer_from(&spender, &owner, &recipient, &transfer_amount);
+
+        assert_eq!(client.balance_of(&owner), mint_amount - transfer_amount);
+        assert_eq!(client.balance_of(&recipient), transfer_amount);
+        assert_eq!(
+            client.allowance(&owner, &spender),
+            allowance_amount - transfer_amount
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #11)")]
+    fn test_transfer_insufficient_balance() {
+        let env = Env::default();
+        env.mock_all_auths(); // Mock authorization for testing
+
+        let admin = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+        let client = setup(&env, &admin);
 
         let mint_amount = 100_000_0000000;
-        client.mint(&user1, &mint_amount, &1000);
+        client.transfer(&admin, &user1, &mint_amount);
+
+        // Try to transfer more than balance
+        let transfer_amount = 200_000_0000000;
+        client.transfer(&user1, &user2, &transfer_amount);
+    }
+
+    #[test]
+    fn test_ownership_percentage() {
+        let env = Env::default();
+        env.mock_all_auths(); // Mock authorization for testing
+
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let mint_amount = 100_000_0000000; // 10% of total
+        client.transfer(&admin, &user, &mint_amount);
+
+        let percentage = client.get_ownership_percentage(&user);
+        assert_eq!(percentage, 1000); // 1000 basis points = 10%
+    }
+
+    #[test]
+    fn test_rental_distribution() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let manager = Address::generate(&env);
+        let holder1 = Address::generate(&env);
+        let holder2 = Address::generate(&env);
+        let property = setup(&env, &admin);
+
+        let usdc_admin = Address::generate(&env);
+        let usdc = env.register_stellar_asset_contract_v2(usdc_admin).address();
+        token::StellarAssetClient::new(&env, &usdc).mint(&manager, &3_000_0000000);
+        let usdc_client = token::TokenClient::new(&env, &usdc);
+
+        // 60% / 40% split of the whole supply
+        property.transfer(&admin, &holder1, &600_000_0000000);
+        property.transfer(&admin, &holder2, &400_000_0000000);
+
+        property.set_distribution_manager(&manager);
+        let first = property.deposit_distribution(&usdc, &1_000_0000000, &202501);
+        assert_eq!(first, 0);
+        assert_eq!(usdc_client.balance(&property.address), 1_000_0000000);
+
+        // Transfers after the deposit don't move income already earned
+        property.transfer(&holder1, &holder2, &300_000_0000000);
+        assert_eq!(property.claimable_distribution(&holder1, &usdc), 600_0000000);
+        assert_eq!(property.claimable_distribution(&holder2, &usdc), 400_0000000);
+
+        // Second period is split 30% / 70%
+        property.deposit_distribution(&usdc, &2_000_0000000, &202502);
+        assert_eq!(property.claimable_distribution(&holder1, &usdc), 1_200_0000000);
+        assert_eq!(property.claimable_distribution(&holder2, &usdc), 1_800_0000000);
+
+        assert_eq!(property.claim_distribution(&holder1, &usdc), 1_200_0000000);
+        assert_eq!(usdc_client.balance(&holder1), 1_200_0000000);
+        assert_eq!(property.claimable_distribution(&holder1, &usdc), 0);
+        assert_eq!(property.claimed_distribution(&holder1, &usdc), 1_200_0000000);
+        assert_eq!(property.claim_distribution(&holder1, &usdc), 0);
+        assert_eq!(property.unclaimed_distribution_total(&usdc), 1_800_0000000);
+
+        let record = property.get_distribution(&1).unwrap();
+        assert_eq!(record.amount, 2_000_0000000);
+        assert_eq!(record.period, 202502);
+        assert_eq!(record.supply, 1_000_000_0000000);
+    }
+
+    #[test]
+    fn test_distribution_requires_manager() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let manager = Address::generate(&env);
+        let property = setup(&env, &admin);
+
+        let usdc = env.register_stellar_asset_contract_v2(admin.clone()).address();
+        token::StellarAssetClient::new(&env, &usdc).mint(&manager, &1_000_0000000);
+
+        assert_eq!(property.distribution_manager(), admin);
+        property.set_distribution_manager(&manager);
+        property.deposit_distribution(&usdc, &1_000_0000000, &1);
+
+        assert_eq!(env.auths()[0].0, manager);
+        assert_eq!(property.claimable_distribution(&admin, &usdc), 1_000_0000000);
+    }
+
+    #[test]
+    fn test_balance_checkpoints() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+        env.ledger().set_sequence_number(100);
+        let property = setup(&env, &admin);
+
+        env.ledger().set_sequence_number(110);
+        property.transfer(&admin, &user1, &500_0000000);
+        let first_snapshot = property.snapshot();
+
+        // Two changes in one ledger keep only the end-of-ledger value
+        env.ledger().set_sequence_number(120);
+        property.transfer(&user1, &user2, &100_0000000);
+        property.transfer(&user1, &user2, &100_0000000);
+
+        env.ledger().set_sequence_number(130);
+        property.burn(&user2, &50_0000000);
+        let second_snapshot = property.snapshot();
+
+        assert_eq!(property.snapshot_ledger(&first_snapshot), Some(110));
+        assert_eq!(property.snapshot_ledger(&second_snapshot), Some(130));
+        assert_eq!(property.snapshot_ledger(&99), None);
+
+        assert_eq!(property.balance_at(&user1, &99), 0);
+        assert_eq!(property.balance_at(&user1, &110), 500_0000000);
+        assert_eq!(property.balance_at(&user1, &119), 500_0000000);
+        assert_eq!(property.balance_at(&user1, &120), 300_0000000);
+        assert_eq!(property.balance_at(&user2, &125), 200_0000000);
+        assert_eq!(property.balance_at(&user2, &130), 150_0000000);
+        assert_eq!(property.balance_at(&admin, &100), 1_000_000_0000000);
+
+        assert_eq!(property.total_supply_at(&99), 0);
+        assert_eq!(property.total_supply_at(&129), 1_000_000_0000000);
+        assert_eq!(property.total_supply_at(&130), 1_000_000_0000000 - 50_0000000);
+    }
+
+    #[test]
+    fn test_sep41_token_client() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let spender = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let property = setup(&env, &admin);
+
+        // Drive the contract purely through the standard SEP-41 client
+        let token = token::TokenClient::new(&env, &property.address);
+        assert_eq!(token.decimals(), 7);
+        assert_eq!(token.name(), String::from_str(&env, "Property Token"));
+        assert_eq!(token.symbol(), String::from_str(&env, "PROP"));
+
+        token.transfer(&admin, &owner, &1_000_0000000);
+        assert_eq!(token.balance(&owner), 1_000_0000000);
+
+        let expiration_ledger = env.ledger().sequence() + 100;
+        token.approve(&owner, &spender, &600_0000000, &expiration_ledger);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    property.address.clone(),
+                    (symbol_short!("approve"), owner.clone(), spender.clone()).into_val(&env),
+                    (600_0000000_i128, expiration_ledger).into_val(&env),
+                ),
+            ]
+        );
+
+        token.transfer_from(&spender, &owner, &recipient, &200_0000000);
+        token.burn_from(&spender, &owner, &100_0000000);
+        token.burn(&recipient, &50_0000000);
+
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    property.address.clone(),
+                    (symbol_short!("burn"), recipient.clone()).into_val(&env),
+                    50_0000000_i128.into_val(&env),
+                ),
+            ]
+        );
+
+        assert_eq!(token.balance(&owner), 700_0000000);
+        assert_eq!(token.balance(&recipient), 150_0000000);
+        assert_eq!(token.allowance(&owner, &spender), 300_0000000);
+
+        // Allowance lapses once the expiration ledger has passed
+        env.ledger().set_sequence_number(expiration_ledger + 1);
+        assert_eq!(token.allowance(&owner, &spender), 0);
+        assert_eq!(
+            token.try_transfer_from(&spender, &owner, &recipient, &1),
+            Err(Ok(Error::InsufficientAllowance.into()))
+        );
+
+        // Zero allowance may be set with a past expiration, non-zero may not
+        token.approve(&owner, &spender, &0, &0);
+        assert!(token.try_approve(&owner, &spender, &1, &0).is_err());
+    }
+
+    #[test]
+    fn test_compliance_allowlist() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let approved = Address::generate(&env);
+        let outsider = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        client.set_compliance(&admin, &None, &false, &false, &true);
+        client.set_allowlisted(&admin, &admin, &true);
+        client.set_allowlisted(&admin, &approved, &true);
+
+        client.transfer(&admin, &approved, &1_000_0000000);
+        assert_eq!(client.balance(&approved), 1_000_0000000);
+
+        assert!(!client.can_receive(&outsider));
+        assert_eq!(
+            client.try_transfer(&approved, &outsider, &1_0000000),
+            Err(Ok(Error::ReceiverNotAllowlisted.into()))
+        );
+
+        client.approve(&approved, &outsider, &1_0000000, &100);
+        assert_eq!(
+            client.try_transfer_from(&outsider, &approved, &outsider, &1_0000000),
+            Err(Ok(Error::ReceiverNotAllowlisted.into()))
+        );
+
+        // Removing the account blocks further receipts
+        client.set_allowlisted(&admin, &approved, &false);
+        assert_eq!(
+            client.try_transfer(&admin, &approved, &1_0000000),
+            Err(Ok(Error::ReceiverNotAllowlisted.into()))
+        );
+
+        client.clear_compliance(&admin);
+        client.transfer(&approved, &outsider, &1_0000000);
+        assert_eq!(client.balance(&outsider), 1_0000000);
+    }
+
+    #[test]
+    fn test_compliance_zk_credentials() {
+        use soroban_sdk::BytesN;
+        use zk_verifier::{CredentialType as ZkCredential, Groth16Proof, ZKVerifier, ZKVerifierClient};
+
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let investor = Address::generate(&env);
+        let exempt = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let verifier_id = env.register(ZKVerifier, ());
+        let verifier = ZKVerifierClient::new(&env, &verifier_id);
+        verifier.initialize(&admin);
+
+        let proof = Groth16Proof {
+            a: (BytesN::from_array(&env, &[1; 32]), BytesN::from_array(&env, &[2; 32])),
+            b: (
+                (BytesN::from_array(&env, &[3; 32]), BytesN::from_array(&env, &[4; 32])),
+                (BytesN::from_array(&env, &[5; 32]), BytesN::from_array(&env, &[6; 32])),
+            ),
+            c: (BytesN::from_array(&env, &[7; 32]), BytesN::from_array(&env, &[8; 32])),
+        };
+
+        // Credential checks need a verifier
+        assert_eq!(
+            client.try_set_compliance(&admin, &None, &true, &false, &true),
+            Err(Ok(Error::VerifierNotSet))
+        );
+
+        client.set_compliance(&admin, &Some(verifier_id.clone()), &true, &true, &true);
+        client.set_allowlisted(&admin, &exempt, &true);
+
+        assert_eq!(
+            client.try_transfer(&admin, &investor, &1_0000000),
+            Err(Ok(Error::KycCredentialRequired.into()))
+        );
+
+        verifier.issue_credential(&investor, &ZkCredential::Kyc, &proof, &BytesN::from_array(&env, &[20; 32]), &0);
+        assert_eq!(
+            client.try_transfer(&admin, &investor, &1_0000000),
+            Err(Ok(Error::AccreditationRequired.into()))
+        );
+
+        verifier.issue_credential(&investor, &ZkCredential::Accredited, &proof, &BytesN::from_array(&env, &[21; 32]), &0);
+        client.transfer(&admin, &investor, &1_0000000);
+        assert_eq!(client.balance(&investor), 1_0000000);
+
+        // Allowlisted accounts skip the credential check
+        client.transfer(&admin, &exempt, &1_0000000);
+        assert_eq!(client.balance(&exempt), 1_0000000);
+
+        // Revoked credentials stop new receipts
+        verifier.revoke_credential(&investor, &ZkCredential::Kyc);
+        assert!(!client.can_receive(&investor));
+    }
+
+    #[test]
+    fn test_freeze_blocks_balance_changes() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+        let client = setup(&env, &admin);
 
-        let transfer_amount = 25_000_0000000; // 25k tokens
-        client.transfer(&user1, &user2, &transfer_amount);
+        client.transfer(&admin, &user1, &100_0000000);
+        client.freeze(&admin, &user1);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    client.address.clone(),
+                    (symbol_short!("freeze"), user1.clone()).into_val(&env),
+                    admin.into_val(&env),
+                ),
+            ]
+        );
+        assert!(client.is_frozen(&user1));
 
-        assert_eq!(client.balance_of(&user1), mint_amount - transfer_amount);
-        assert_eq!(client.balance_of(&user2), transfer_amount);
+        // Frozen accounts can neither send, receive, burn nor spend allowances
+        assert_eq!(
+            client.try_transfer(&user1, &user2, &1_0000000),
+            Err(Ok(Error::AccountFrozen.into()))
+        );
+        assert_eq!(
+            client.try_transfer(&admin, &user1, &1_0000000),
+            Err(Ok(Error::AccountFrozen.into()))
+        );
+        assert_eq!(client.try_burn(&user1, &1_0000000), Err(Ok(Error::AccountFrozen.into())));
+
+        client.approve(&user1, &user2, &10_0000000, &100);
+        assert_eq!(
+            client.try_transfer_from(&user2, &user1, &user2, &1_0000000),
+            Err(Ok(Error::AccountFrozen.into()))
+        );
+
+        client.unfreeze(&admin, &user1);
+        client.transfer(&user1, &user2, &1_0000000);
+        assert_eq!(client.balance(&user2), 1_0000000);
     }
 
     #[test]
-    fn test_approve_and_transfer_from() {
+    fn test_pause_blocks_balance_changes() {
         let env = Env::default();
-        env.mock_all_auths(); // Mock authorization for testing
+        env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, PropertyTokenContract);
-        let client = PropertyTokenContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        client.pause(&admin);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    client.address.clone(),
+                    (symbol_short!("pause"),).into_val(&env),
+                    admin.into_val(&env),
+                ),
+            ]
+        );
+        assert!(client.is_paused());
+
+        assert_eq!(
+            client.try_transfer(&admin, &user, &1_0000000),
+            Err(Ok(Error::ContractPaused.into()))
+        );
+        assert_eq!(client.try_burn(&admin, &1_0000000), Err(Ok(Error::ContractPaused.into())));
+        assert_eq!(client.try_mint(&admin, &user, &1, &1), Err(Ok(Error::ContractPaused)));
+
+        client.unpause(&admin);
+        client.transfer(&admin, &user, &1_0000000);
+        assert_eq!(client.balance(&user), 1_0000000);
+    }
+
+    #[test]
+    fn test_forced_transfer() {
+        use soroban_sdk::BytesN;
+
+        let env = Env::default();
+        env.mock_all_auths();
 
         let admin = Address::generate(&env);
-        let owner = Address::generate(&env);
-        let spender = Address::generate(&env);
-        let recipient = Address::generate(&env);
-        let property_id = Symbol::new(&env, "PROP001");
-        let name = String::from_str(&env, "Property Token");
-        let symbol = String::from_str(&env, "PROP");
-        let total_supply = 1_000_000_0000000;
+        let lost_wallet = Address::generate(&env);
+        let recovery_wallet = Address::generate(&env);
+        let client = setup(&env, &admin);
 
-        client.initialize(&admin, &property_id, &name, &symbol, &total_supply);
+        client.transfer(&admin, &lost_wallet, &50_0000000);
+        client.freeze(&admin, &lost_wallet);
+        client.pause(&admin);
 
-        let mint_amount = 100_000_0000000;
-        client.mint(&owner, &mint_amount, &1000);
+        let reason = String::from_str(&env, "Court order 2025-CV-0142");
+        let document_hash = BytesN::from_array(&env, &[7u8; 32]);
 
-        let allowance_amount = 50_000_0000000;
-        client.approve(&owner, &spender, &allowance_amount);
+        assert_eq!(
+            client.try_forced_transfer(&lost_wallet, &recovery_wallet, &50_0000000, &String::from_str(&env, ""), &document_hash),
+            Err(Ok(Error::InvalidReason))
+        );
+        assert_eq!(
+            client.try_forced_transfer(&lost_wallet, &recovery_wallet, &50_0000000, &reason, &BytesN::from_array(&env, &[0u8; 32])),
+            Err(Ok(Error::InvalidDocumentHash))
+        );
 
-        assert_eq!(client.allowance(&owner, &spender), allowance_amount);
+        let record_id = client.forced_transfer(&lost_wallet, &recovery_wallet, &50_0000000, &reason, &document_hash);
+        assert_eq!(client.balance(&lost_wallet), 0);
+        assert_eq!(client.balance(&recovery_wallet), 50_0000000);
 
-        let transfer_amount = 25_000_0000000;
-        client.transfer_from(&spender, &owner, &recipient, &transfer_amount);
+        let record = client.get_forced_transfer(&record_id).unwrap();
+        assert_eq!(record.from, lost_wallet);
+        assert_eq!(record.to, recovery_wallet);
+        assert_eq!(record.reason, reason);
+        assert_eq!(record.document_hash, document_hash);
 
-        assert_eq!(client.balance_of(&owner), mint_amount - transfer_amount);
-        assert_eq!(client.balance_of(&recipient), transfer_amount);
+        // Vesting locks still hold
+        client.unpause(&admin);
+        client.grant_vesting(&recovery_wallet, &10_0000000, &1000, &1000, &false);
         assert_eq!(
-            client.allowance(&owner, &spender),
-            allowance_amount - transfer_amount
+            client.try_forced_transfer(&recovery_wallet, &lost_wallet, &60_0000000, &reason, &document_hash),
+            Err(Ok(Error::TokensLocked))
         );
+        client.forced_transfer(&recovery_wallet, &lost_wallet, &50_0000000, &reason, &document_hash);
+        assert_eq!(client.balance(&recovery_wallet), 10_0000000);
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #11)")]
-    fn test_transfer_insufficient_balance() {
+    fn test_vesting_schedule() {
         let env = Env::default();
-        env.mock_all_auths(); // Mock authorization for testing
+        env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, PropertyTokenContract);
-        let client = PropertyTokenContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let sponsor = Address::generate(&env);
+        let buyer = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let year = 365 * 24 * 3600;
+        env.ledger().set_timestamp(1_000);
+
+        // 12 month lockup, then linear vesting over 24 months
+        let index = client.grant_vesting(&sponsor, &120_0000000, &year, &(2 * year), &false);
+        assert_eq!(index, 0);
+        assert_eq!(client.balance(&sponsor), 120_0000000);
+        assert_eq!(client.locked_balance(&sponsor), 120_0000000);
+        assert_eq!(client.releasable_balance(&sponsor), 0);
+
+        assert_eq!(
+            client.try_transfer(&sponsor, &buyer, &1),
+            Err(Ok(Error::TokensLocked.into()))
+        );
+
+        // Half way through the linear period
+        env.ledger().set_timestamp(1_000 + 2 * year);
+        assert_eq!(client.locked_balance(&sponsor), 60_0000000);
+        assert_eq!(client.releasable_balance(&sponsor), 60_0000000);
+
+        client.transfer(&sponsor, &buyer, &60_0000000);
+        assert_eq!(
+            client.try_burn(&sponsor, &1),
+            Err(Ok(Error::TokensLocked.into()))
+        );
+
+        // Non-revocable schedules can't be cancelled
+        assert_eq!(
+            client.try_revoke_vesting(&sponsor, &index),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        env.ledger().set_timestamp(1_000 + 3 * year);
+        assert_eq!(client.locked_balance(&sponsor), 0);
+        client.transfer(&sponsor, &buyer, &60_0000000);
+        assert_eq!(client.balance(&buyer), 120_0000000);
+    }
+
+    #[test]
+    fn test_revoke_vesting() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let developer = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        env.ledger().set_timestamp(0);
+        let index = client.grant_vesting(&developer, &100_0000000, &100, &1_000, &true);
+
+        // 25% vested at revocation; the rest goes back to the admin
+        env.ledger().set_timestamp(350);
+        let admin_before = client.balance(&admin);
+        assert_eq!(client.revoke_vesting(&developer, &index), 75_0000000);
+        assert_eq!(client.balance(&developer), 25_0000000);
+        assert_eq!(client.balance(&admin), admin_before + 75_0000000);
+        assert_eq!(client.locked_balance(&developer), 0);
+        assert!(client.vesting_schedules(&developer).get(0).unwrap().revoked);
+    }
+
+    #[test]
+    fn test_holder_cap() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let investor = Address::generate(&env);
+        let fund = Address::generate(&env);
+        let escrow = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        // 10% of the 1M supply per holder
+        client.set_holder_cap(&admin, &1000);
+        assert_eq!(client.try_set_holder_cap(&admin, &10001), Err(Ok(Error::InvalidPercentage)));
+
+        client.transfer(&admin, &investor, &100_000_0000000);
+        assert_eq!(client.get_ownership_percentage(&investor), 1000);
+        assert_eq!(
+            client.try_transfer(&admin, &investor, &1),
+            Err(Ok(Error::HolderCapExceeded.into()))
+        );
+
+        client.approve(&admin, &fund, &200_000_0000000, &100);
+        client.transfer_from(&fund, &admin, &fund, &100_000_0000000);
+        assert_eq!(
+            client.try_transfer_from(&fund, &admin, &fund, &1),
+            Err(Ok(Error::HolderCapExceeded.into()))
+        );
+
+        // Exempt accounts (e.g. escrow) may exceed the cap
+        client.set_cap_exempt(&admin, &escrow, &true);
+        client.transfer(&admin, &escrow, &300_000_0000000);
+        assert_eq!(client.balance(&escrow), 300_000_0000000);
+
+        client.set_holder_cap(&admin, &0);
+        client.transfer(&admin, &investor, &1);
+    }
+
+    #[test]
+    fn test_owner_index_pagination() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let client = setup(&env, &admin);
+        assert_eq!(client.holder_count(), 1);
+
+        let mut holders = Vec::new(&env);
+        for _ in 0..5 {
+            let holder = Address::generate(&env);
+            client.transfer(&admin, &holder, &10_0000000);
+            holders.push_back(holder);
+        }
+        assert_eq!(client.holder_count(), 6);
+
+        let page = client.list_owners(&0, &4);
+        assert_eq!(page.owners.len(), 4);
+        assert_eq!(page.owners.get(0).unwrap().owner, admin);
+        assert_eq!(page.next_cursor, Some(4));
+
+        let page = client.list_owners(&4, &4);
+        assert_eq!(page.owners.len(), 2);
+        assert_eq!(page.next_cursor, None);
+
+        // Emptied accounts leave the index; the last holder takes their slot
+        let first = holders.get(0).unwrap();
+        let last = holders.get(4).unwrap();
+        client.transfer(&first, &admin, &10_0000000);
+        assert_eq!(client.holder_count(), 5);
+
+        let page = client.list_owners(&0, &10);
+        assert_eq!(page.owners.len(), 5);
+        assert_eq!(page.owners.get(1).unwrap().owner, last);
+        for info in page.owners.iter() {
+            assert!(info.owner != first);
+            assert!(info.balance > 0);
+        }
+
+        // Re-entering holders are appended again
+        client.transfer(&admin, &first, &1);
+        assert_eq!(client.holder_count(), 6);
+        assert_eq!(client.list_owners(&5, &1).owners.get(0).unwrap().owner, first);
+    }
+
+    #[test]
+    fn test_batch_transfer() {
+        let env = Env::default();
+        env.mock_all_auths();
 
         let admin = Address::generate(&env);
         let user1 = Address::generate(&env);
         let user2 = Address::generate(&env);
-        let property_id = Symbol::new(&env, "PROP001");
-        let name = String::from_str(&env, "Property Token");
-        let symbol = String::from_str(&env, "PROP");
-        let total_supply = 1_000_000_0000000;
+        let client = setup(&env, &admin);
 
-        client.initialize(&admin, &property_id, &name, &symbol, &total_supply);
+        let batch = vec![
+            &env,
+            (user1.clone(), 10_0000000),
+            (user2.clone(), 20_0000000),
+            (user1.clone(), 5_0000000),
+        ];
+        client.batch_transfer(&admin, &batch);
 
-        let mint_amount = 100_000_0000000;
-        client.mint(&user1, &mint_amount, &1000);
+        // One transfer event per entry, in order
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    client.address.clone(),
+                    (symbol_short!("transfer"), admin.clone(), user1.clone()).into_val(&env),
+                    10_0000000_i128.into_val(&env),
+                ),
+                (
+                    client.address.clone(),
+                    (symbol_short!("transfer"), admin.clone(), user2.clone()).into_val(&env),
+                    20_0000000_i128.into_val(&env),
+                ),
+                (
+                    client.address.clone(),
+                    (symbol_short!("transfer"), admin.clone(), user1.clone()).into_val(&env),
+                    5_0000000_i128.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.balance(&user1), 15_0000000);
+        assert_eq!(client.balance(&user2), 20_0000000);
+        assert_eq!(client.balance(&admin), 1_000_000_0000000 - 35_0000000);
+        assert_eq!(client.holder_count(), 3);
+
+        // Entries sent back to the sender net out
+        client.batch_transfer(&user1, &vec![&env, (user1.clone(), 5_0000000), (user2.clone(), 10_0000000)]);
+        assert_eq!(client.balance(&user1), 5_0000000);
+        assert_eq!(client.balance(&user2), 30_0000000);
+
+        client.batch_transfer(&user1, &vec![&env, (user2.clone(), 5_0000000)]);
+        assert_eq!(client.balance(&user1), 0);
+        assert_eq!(client.holder_count(), 2);
+    }
 
-        // Try to transfer more than balance
-        let transfer_amount = 200_000_0000000;
-        client.transfer(&user1, &user2, &transfer_amount);
+    #[test]
+    fn test_batch_transfer_is_atomic() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        client.transfer(&admin, &user1, &30_0000000);
+
+        // The batch total exceeds the balance even though each entry fits
+        assert_eq!(
+            client.try_batch_transfer(&user1, &vec![&env, (user2.clone(), 20_0000000), (admin.clone(), 20_0000000)]),
+            Err(Ok(Error::InsufficientBalance))
+        );
+
+        // A single frozen recipient rejects the whole batch
+        client.freeze(&admin, &user2);
+        assert_eq!(
+            client.try_batch_transfer(&admin, &vec![&env, (user1.clone(), 1_0000000), (user2.clone(), 1_0000000)]),
+            Err(Ok(Error::AccountFrozen))
+        );
+        assert_eq!(
+            client.try_batch_transfer(&admin, &vec![&env, (user1.clone(), 1_0000000), (user2.clone(), 0)]),
+            Err(Ok(Error::AccountFrozen))
+        );
+        client.unfreeze(&admin, &user2);
+
+        assert_eq!(
+            client.try_batch_transfer(&admin, &vec![&env, (user1.clone(), 1_0000000), (user2.clone(), 0)]),
+            Err(Ok(Error::InvalidAmount))
+        );
+        assert_eq!(client.try_batch_transfer(&admin, &Vec::new(&env)), Err(Ok(Error::InvalidAmount)));
+
+        assert_eq!(client.balance(&user1), 30_0000000);
+        assert_eq!(client.balance(&user2), 0);
+        assert_eq!(client.holder_count(), 2);
     }
 
     #[test]
-    fn test_ownership_percentage() {
+    fn test_batch_mint() {
         let env = Env::default();
-        env.mock_all_auths(); // Mock authorization for testing
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let user1 = Address::generate(&env);
+        let user2 = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        // The whole supply is minted at initialization, so nothing is left
+        let batch = vec![&env, (user1.clone(), 1_0000000), (user2.clone(), 1_0000000)];
+        assert_eq!(client.try_batch_mint(&admin, &batch), Err(Ok(Error::MintExceedsSupply)));
+        assert_eq!(client.balance(&user1), 0);
+        assert_eq!(client.holder_count(), 1);
+
+        // Burning retires supply rather than freeing room to mint
+        client.burn(&admin, &3_0000000);
+        let batch = vec![&env, (user1.clone(), 1_0000000)];
+        assert_eq!(client.try_batch_mint(&admin, &batch), Err(Ok(Error::MintExceedsSupply)));
+        assert_eq!(client.try_mint(&admin, &user1, &1_0000000, &1), Err(Ok(Error::MintExceedsSupply)));
+
+        // The whole batch is validated before the supply cap
+        let batch = vec![&env, (user1.clone(), 1_0000000), (user2.clone(), 0)];
+        assert_eq!(client.try_batch_mint(&admin, &batch), Err(Ok(Error::InvalidAmount)));
+        let batch = vec![&env, (user1.clone(), i128::MAX), (user2.clone(), 1)];
+        assert_eq!(client.try_batch_mint(&admin, &batch), Err(Ok(Error::InvalidAmount)));
+        assert_eq!(client.try_batch_mint(&admin, &vec![&env]), Err(Ok(Error::InvalidAmount)));
+
+        let outsider = Address::generate(&env);
+        assert_eq!(
+            client.try_batch_mint(&outsider, &vec![&env, (user1.clone(), 1_0000000)]),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        assert_eq!(client.balance(&user1), 0);
+        assert_eq!(client.balance(&user2), 0);
+        assert_eq!(client.balance(&admin), 1_000_000_0000000 - 3_0000000);
+        assert_eq!(client.total_supply(), 1_000_000_0000000 - 3_0000000);
+    }
+
+    #[test]
+    fn test_burn_reduces_supply() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let holder = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        client.transfer(&admin, &holder, &500_000_0000000);
+        client.burn(&admin, &500_000_0000000);
+
+        assert_eq!(client.total_supply(), 500_000_0000000);
+        assert_eq!(client.total_supply_at(&env.ledger().sequence()), 500_000_0000000);
+        assert_eq!(client.get_ownership_percentage(&holder), 10000);
+
+        // Burned tokens can't be minted again
+        assert_eq!(client.try_mint(&admin, &holder, &1, &1), Err(Ok(Error::MintExceedsSupply)));
+    }
+
+    #[test]
+    fn test_redemption() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let holder1 = Address::generate(&env);
+        let holder2 = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        let property = setup(&env, &admin);
+
+        let usdc = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+        token::StellarAssetClient::new(&env, &usdc).mint(&admin, &2_000_000_0000000);
+        let usdc_client = token::TokenClient::new(&env, &usdc);
+
+        // 60% / 30% / 10% (admin)
+        property.transfer(&admin, &holder1, &600_000_0000000);
+        property.transfer(&admin, &holder2, &300_000_0000000);
+
+        env.ledger().set_timestamp(1_000);
+        assert_eq!(
+            property.try_redeem(&holder1, &1_0000000),
+            Err(Ok(Error::RedemptionNotActive))
+        );
+
+        // Sale proceeds of 2 USDC per token
+        property.open_redemption(&usdc, &2_000_000_0000000, &2_000, &treasury);
+        assert_eq!(usdc_client.balance(&property.address), 2_000_000_0000000);
+        assert_eq!(property.redemption_quote(&1_0000000), 2_0000000);
+        assert_eq!(
+            property.try_open_redemption(&usdc, &1, &3_000, &treasury),
+            Err(Ok(Error::RedemptionAlreadyOpened))
+        );
+        assert_eq!(property.try_mint(&admin, &holder1, &1, &1), Err(Ok(Error::RedemptionAlreadyOpened)));
+
+        assert_eq!(property.redeem(&holder1, &600_000_0000000), 1_200_000_0000000);
+        assert_eq!(property.redeem(&holder2, &150_000_0000000), 300_000_0000000);
+        assert_eq!(property.balance(&holder1), 0);
+        assert_eq!(usdc_client.balance(&holder1), 1_200_000_0000000);
+        assert_eq!(property.total_supply(), 250_000_0000000);
+
+        assert_eq!(property.try_close_redemption(), Err(Ok(Error::RedemptionNotEnded)));
+
+        // After the deadline the rest goes to the treasury
+        env.ledger().set_timestamp(2_000);
+        assert_eq!(
+            property.try_redeem(&holder2, &1_0000000),
+            Err(Ok(Error::RedemptionNotActive))
+        );
+        assert_eq!(property.close_redemption(), 500_000_0000000);
+        assert_eq!(usdc_client.balance(&treasury), 500_000_0000000);
+        assert_eq!(usdc_client.balance(&property.address), 0);
+
+        let redemption = property.get_redemption().unwrap();
+        assert!(redemption.closed);
+        assert_eq!(redemption.redeemed_tokens, 750_000_0000000);
+        assert_eq!(property.try_close_redemption(), Err(Ok(Error::RedemptionNotActive)));
+    }
+
+    #[test]
+    fn test_roles_grant_and_revoke() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let pauser = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        assert_eq!(client.try_pause(&pauser), Err(Ok(Error::NotAuthorized)));
+
+        client.grant_role(&Role::Pauser, &pauser);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    client.address.clone(),
+                    (symbol_short!("role_grnt"), Role::Pauser as u32, pauser.clone()).into_val(&env),
+                    admin.into_val(&env),
+                ),
+            ]
+        );
+        assert!(client.has_role(&Role::Pauser, &pauser));
+        assert!(!client.has_role(&Role::Minter, &pauser));
+        assert!(client.has_role(&Role::Minter, &admin));
+
+        client.pause(&pauser);
+        client.unpause(&pauser);
+
+        // Roles are scoped: a pauser cannot mint
+        assert_eq!(
+            client.try_mint(&pauser, &pauser, &1, &1),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        client.revoke_role(&Role::Pauser, &pauser);
+        assert!(!client.has_role(&Role::Pauser, &pauser));
+        assert_eq!(client.try_pause(&pauser), Err(Ok(Error::NotAuthorized)));
+    }
+
+    #[test]
+    fn test_two_step_admin_handover() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let new_admin = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        // Nothing to accept yet
+        assert_eq!(client.try_accept_admin(), Err(Ok(Error::NotAuthorized)));
+
+        client.propose_admin(&new_admin);
+        assert_eq!(client.pending_admin(), Some(new_admin.clone()));
+
+        // The proposal alone does not move any rights
+        assert!(!client.has_role(&Role::Pauser, &new_admin));
+
+        client.accept_admin();
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    client.address.clone(),
+                    (symbol_short!("adm_xfer"), admin.clone()).into_val(&env),
+                    new_admin.into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.pending_admin(), None);
+        assert!(client.has_role(&Role::Pauser, &new_admin));
+
+        // The previous admin no longer holds implicit roles
+        assert!(!client.has_role(&Role::Pauser, &admin));
+        assert_eq!(client.try_pause(&admin), Err(Ok(Error::NotAuthorized)));
+    }
+
+    #[test]
+    fn test_upgrade_and_migrate_owner_list() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_sequence_number(100);
 
-        let contract_id = env.register_contract(None, PropertyTokenContract);
+        let admin = Address::generate(&env);
+        let spender = Address::generate(&env);
+        let contract_id = env.register(PropertyTokenContract, ());
         let client = PropertyTokenContractClient::new(&env, &contract_id);
+        let supply = 1_000_000_0000000;
+
+        // Version 1 state: holders recorded only in the owner list, one
+        // already emptied, no checkpoints and an allowance without expiration
+        let mut legacy = Vec::new(&env);
+        legacy.push_back(admin.clone());
+        for _ in 0..4 {
+            legacy.push_back(Address::generate(&env));
+        }
+        env.as_contract(&contract_id, || {
+            storage::set_admin(&env, &admin);
+            env.storage().instance().set(
+                &Symbol::new(&env, "metadata"),
+                &TokenMetadata {
+                    property_id: Symbol::new(&env, "PROP001"),
+                    name: String::from_str(&env, "Property Token"),
+                    symbol: String::from_str(&env, "PROP"),
+                    decimals: 7,
+                    total_supply: supply,
+                },
+            );
+            for i in 1..4 {
+                storage::set_balance(&env, &legacy.get(i).unwrap(), 10_0000000);
+            }
+            storage::set_balance(&env, &admin, supply - 30_0000000);
+            storage::set_persistent(&env, &DataKey::TotalMinted, &supply);
+            storage::set_persistent(&env, &DataKey::OwnersList, &legacy);
+            storage::set_persistent(&env, &DataKey::Allowance(admin.clone(), spender.clone()), &5_0000000i128);
+            storage::set_initialized(&env);
+        });
+        assert_eq!(client.schema_version(), 1);
+        assert_eq!(client.holder_count(), 0);
+        assert_eq!(client.total_supply_at(&100), 0);
+
+        let wasm_hash = stellar_property_access::testutils::upload_upgrade_wasm(&env);
+        client.upgrade(&wasm_hash);
+        env.register_at(&contract_id, PropertyTokenContract, ());
+
+        // Old allowances never expire
+        env.ledger().set_sequence_number(110);
+        assert_eq!(client.allowance(&admin, &spender), 5_0000000);
+
+        assert_eq!(client.try_migrate(&0, &101), Err(Ok(Error::InvalidAmount)));
+        assert_eq!(client.migrate(&0, &2), Some(2));
+        assert_eq!(client.holder_count(), 2);
+        assert_eq!(client.total_supply_at(&110), supply);
+        assert_eq!(client.balance_at(&admin, &110), supply - 30_0000000);
+        assert_eq!(client.schema_version(), 1);
+
+        assert_eq!(client.migrate(&2, &100), None);
+        assert_eq!(client.holder_count(), 4);
+        assert_eq!(client.balance_at(&legacy.get(3).unwrap(), &110), 10_0000000);
+        assert_eq!(client.schema_version(), migration::SCHEMA_VERSION);
+        env.as_contract(&contract_id, || {
+            assert!(!storage::has_persistent(&env, &DataKey::OwnersList));
+        });
+
+        // Migrated holders keep trading and the index stays in sync
+        env.ledger().set_sequence_number(120);
+        let holder = legacy.get(1).unwrap();
+        client.transfer(&holder, &admin, &10_0000000);
+        assert_eq!(client.holder_count(), 3);
+        assert_eq!(client.balance(&admin), supply - 20_0000000);
+        assert_eq!(client.balance_at(&holder, &110), 10_0000000);
+        client.transfer_from(&spender, &admin, &holder, &5_0000000);
+        assert_eq!(client.allowance(&admin, &spender), 0);
+
+        // Distributions and burns work from the seeded supply
+        let usdc = env.register_stellar_asset_contract_v2(admin.clone()).address();
+        token::StellarAssetClient::new(&env, &usdc).mint(&admin, &1_000_0000000);
+        client.deposit_distribution(&usdc, &1_000_0000000, &1);
+        assert_eq!(client.claimable_distribution(&holder, &usdc), 50000);
+        client.burn(&holder, &5_0000000);
+        assert_eq!(client.total_supply_at(&120), supply - 5_0000000);
+    }
+
+    // Stellar account address and signing key derived from `seed`
+    fn signer(env: &Env, seed: u8) -> (ed25519_dalek::SigningKey, Address) {
+        use soroban_sdk::xdr::FromXdr;
+
+        let key = ed25519_dalek::SigningKey::from_bytes(&[seed; 32]);
+        let mut xdr = soroban_sdk::Bytes::from_array(env, &[0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0]);
+        xdr.extend_from_array(&key.verifying_key().to_bytes());
+        (key, Address::from_xdr(env, &xdr).unwrap())
+    }
+
+    fn sign_permit(
+        client: &PropertyTokenContractClient,
+        key: &ed25519_dalek::SigningKey,
+        permit: &Permit,
+    ) -> BytesN<64> {
+        use ed25519_dalek::Signer;
+
+        let message = client.permit_message(permit);
+        let mut buf = [0u8; 512];
+        let buf = &mut buf[..message.len() as usize];
+        message.copy_into_slice(buf);
+        BytesN::from_array(&client.env, &key.sign(buf).to_bytes())
+    }
+
+    #[test]
+    fn test_permit() {
+        let env = Env::default();
+        env.mock_all_auths();
 
         let admin = Address::generate(&env);
-        let user = Address::generate(&env);
-        let property_id = Symbol::new(&env, "PROP001");
-        let name = String::from_str(&env, "Property Token");
-        let symbol = String::from_str(&env, "PROP");
-        let total_supply = 1_000_000_0000000;
+        let spender = Address::generate(&env);
+        let recipient = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let (key, owner) = signer(&env, 7);
+        client.transfer(&admin, &owner, &100_0000000);
+        assert_eq!(client.nonce(&owner), 0);
+
+        let permit = Permit {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount: 40_0000000,
+            nonce: 0,
+            expiration_ledger: env.ledger().sequence() + 100,
+        };
+        let signature = sign_permit(&client, &key, &permit);
 
-        client.initialize(&admin, &property_id, &name, &symbol, &total_supply);
+        client.permit(&permit, &signature);
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    client.address.clone(),
+                    (symbol_short!("approve"), owner.clone(), spender.clone()).into_val(&env),
+                    (40_0000000i128, permit.expiration_ledger).into_val(&env),
+                ),
+            ]
+        );
+        assert_eq!(client.allowance(&owner, &spender), 40_0000000);
+        assert_eq!(client.nonce(&owner), 1);
 
-        let mint_amount = 100_000_0000000; // 10% of total
-        client.mint(&user, &mint_amount, &1000);
+        client.transfer_from(&spender, &owner, &recipient, &40_0000000);
+        assert_eq!(client.balance(&recipient), 40_0000000);
 
-        let percentage = client.get_ownership_percentage(&user);
-        assert_eq!(percentage, 1000); // 1000 basis points = 10%
+        // Replaying the same signed permit fails on the nonce
+        assert_eq!(client.try_permit(&permit, &signature), Err(Ok(Error::InvalidNonce)));
+        assert_eq!(client.allowance(&owner, &spender), 0);
+    }
+
+    #[test]
+    fn test_permit_expired() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let spender = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let (key, owner) = signer(&env, 8);
+        let permit = Permit {
+            owner: owner.clone(),
+            spender,
+            amount: 10_0000000,
+            nonce: 0,
+            expiration_ledger: env.ledger().sequence() + 10,
+        };
+        let signature = sign_permit(&client, &key, &permit);
+
+        env.ledger().with_mut(|li| li.sequence_number += 11);
+        assert_eq!(client.try_permit(&permit, &signature), Err(Ok(Error::PermitExpired)));
+        assert_eq!(client.nonce(&owner), 0);
+    }
+
+    #[test]
+    fn test_permit_wrong_signer() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let admin = Address::generate(&env);
+        let spender = Address::generate(&env);
+        let client = setup(&env, &admin);
+
+        let (_, owner) = signer(&env, 9);
+        let (other_key, _) = signer(&env, 10);
+        let permit = Permit {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount: 10_0000000,
+            nonce: 0,
+            expiration_ledger: env.ledger().sequence() + 10,
+        };
+
+        // A signature by another key does not verify against the owner's account
+        let forged = sign_permit(&client, &other_key, &permit);
+        assert_eq!(
+            client.try_permit(&permit, &forged),
+            Err(Err(soroban_sdk::InvokeError::Abort))
+        );
+        assert_eq!(client.nonce(&owner), 0);
+        assert_eq!(client.allowance(&owner, &spender), 0);
+
+        // Contract addresses have no signing key
+        let contract_owner = Permit { owner: admin.clone(), ..permit };
+        let signature = sign_permit(&client, &other_key, &contract_owner);
+        assert_eq!(
+            client.try_permit(&contract_owner, &signature),
+            Err(Ok(Error::InvalidSignature))
+        );
     }
 }
@@ -0,0 +1,60 @@
+// Storage layout versions
+//
+//   1 - initial layout, holders kept in the `OwnersList` vector
+//   2 - holders kept in the indexed set (see owners.rs), balances and supply
+//       checkpointed (see checkpoints.rs), allowances carry an expiration
+//
+// `migrate` first records the minted total as the current supply, then walks
+// the legacy list in batches, indexes every address that still holds tokens,
+// records its balance as a checkpoint and deletes the list once it reaches
+// the end. Until then `list_owners` and `holder_count` only see holders that
+// transacted after the upgrade, and `balance_at` reads 0 for the others.
+// Version 1 allowances can't be listed; they are read as never expiring and
+// rewritten on their next use.
+
+use soroban_sdk::{Address, Env, Vec};
+use stellar_property_events as events;
+use stellar_property_storage::{self as storage, DataKey};
+
+use crate::{checkpoints, owners};
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+// SECURITY: Maximum legacy holders indexed per call, keeps each call within budget
+pub const MAX_MIGRATION_BATCH: u32 = 100;
+
+/// Record the minted total as the current supply
+pub fn migrate_supply(env: &Env) {
+    let minted: i128 = storage::get_persistent(env, &DataKey::TotalMinted).unwrap_or(0);
+    checkpoints::seed_supply(env, minted);
+}
+
+/// Index legacy holders `[start, start + limit)`, returning where to continue
+pub fn migrate_owners(env: &Env, start: u32, limit: u32) -> Option<u32> {
+    let legacy: Vec<Address> = storage::get_persistent(env, &DataKey::OwnersList)?;
+    let end = start.saturating_add(limit).min(legacy.len());
+
+    for i in start..end {
+        let holder = legacy.get(i).unwrap();
+        let balance = storage::get_balance(env, &holder);
+        // Adding an already indexed holder is a no-op
+        owners::on_balance_change(env, &holder, 0, balance);
+        checkpoints::seed_balance(env, &holder, balance);
+    }
+
+    if end < legacy.len() {
+        return Some(end);
+    }
+
+    storage::remove_persistent(env, &DataKey::OwnersList);
+    None
+}
+
+/// Record that storage now follows the current layout
+pub fn finish(env: &Env) {
+    let from_version = storage::get_schema_version(env);
+    if from_version != SCHEMA_VERSION {
+        storage::set_schema_version(env, SCHEMA_VERSION);
+        events::schema_migrated(env, from_version, SCHEMA_VERSION);
+    }
+}
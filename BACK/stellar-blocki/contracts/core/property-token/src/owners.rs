@@ -0,0 +1,57 @@
+// Holder index
+//
+// Holders with a non-zero balance are kept in an indexed set: `OwnerAt(i)`
+// maps positions to addresses and `OwnerIndex(addr)` maps back. Adding and
+// removing are O(1) (removal swaps the last holder into the freed slot), so
+// transfer cost does not grow with the number of holders.
+
+use soroban_sdk::{Address, Env};
+use stellar_property_storage::{self as storage, DataKey};
+
+/// Number of holders with a non-zero balance
+pub fn count(env: &Env) -> u32 {
+    storage::get_persistent(env, &DataKey::OwnerCount).unwrap_or(0)
+}
+
+pub fn owner_at(env: &Env, index: u32) -> Option<Address> {
+    storage::get_persistent(env, &DataKey::OwnerAt(index))
+}
+
+fn add(env: &Env, holder: &Address) {
+    if storage::has_persistent(env, &DataKey::OwnerIndex(holder.clone())) {
+        return;
+    }
+
+    let index = count(env);
+    storage::set_persistent(env, &DataKey::OwnerAt(index), holder);
+    storage::set_persistent(env, &DataKey::OwnerIndex(holder.clone()), &index);
+    storage::set_persistent(env, &DataKey::OwnerCount, &(index + 1));
+}
+
+fn remove(env: &Env, holder: &Address) {
+    let index: u32 = match storage::get_persistent(env, &DataKey::OwnerIndex(holder.clone())) {
+        Some(index) => index,
+        None => return,
+    };
+
+    // Move the last holder into the freed slot
+    let last_index = count(env) - 1;
+    if index != last_index {
+        let last: Address = owner_at(env, last_index).unwrap();
+        storage::set_persistent(env, &DataKey::OwnerAt(index), &last);
+        storage::set_persistent(env, &DataKey::OwnerIndex(last), &index);
+    }
+
+    storage::remove_persistent(env, &DataKey::OwnerAt(last_index));
+    storage::remove_persistent(env, &DataKey::OwnerIndex(holder.clone()));
+    storage::set_persistent(env, &DataKey::OwnerCount, &last_index);
+}
+
+/// Keep the index in sync with a balance change of `holder`
+pub fn on_balance_change(env: &Env, holder: &Address, old_balance: i128, new_balance: i128) {
+    if old_balance <= 0 && new_balance > 0 {
+        add(env, holder);
+    } else if old_balance > 0 && new_balance <= 0 {
+        remove(env, holder);
+    }
+}
@@ -0,0 +1,69 @@
+// Signed allowances (permits)
+//
+// An owner signs a `Permit` off-chain with the ed25519 key of their Stellar
+// account and anyone (typically a relayer) submits it. The signed message is
+// the XDR of `("permit", token contract, permit)`, so a permit is bound to
+// one token. Each owner has a nonce that must match and is bumped on use,
+// which makes every permit single-use.
+
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol};
+use stellar_property_errors::TokenError as Error;
+use stellar_property_storage as storage;
+
+// XDR of an `ScVal::Address` holding an ed25519 account, up to the key:
+// SCV_ADDRESS, SC_ADDRESS_TYPE_ACCOUNT, PUBLIC_KEY_TYPE_ED25519
+const ACCOUNT_XDR_PREFIX: [u8; 12] = [0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0];
+
+#[contracttype]
+#[derive(Clone)]
+enum PermitKey {
+    Nonce(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Permit {
+    pub owner: Address,
+    pub spender: Address,
+    pub amount: i128,
+    pub nonce: u64,
+    pub expiration_ledger: u32, // Last ledger the permit and the allowance are valid
+}
+
+pub fn nonce(env: &Env, owner: &Address) -> u64 {
+    storage::get_persistent(env, &PermitKey::Nonce(owner.clone())).unwrap_or(0)
+}
+
+/// Bytes the owner signs for `permit`
+pub fn message(env: &Env, permit: &Permit) -> Bytes {
+    (Symbol::new(env, "permit"), env.current_contract_address(), permit.clone()).to_xdr(env)
+}
+
+// ed25519 public key of a Stellar account address; contracts can't sign permits
+fn account_key(env: &Env, owner: &Address) -> Result<BytesN<32>, Error> {
+    let xdr = owner.clone().to_xdr(env);
+    if xdr.len() != 44 || xdr.slice(0..12) != Bytes::from_array(env, &ACCOUNT_XDR_PREFIX) {
+        return Err(Error::InvalidSignature);
+    }
+
+    BytesN::try_from(xdr.slice(12..44)).map_err(|_| Error::InvalidSignature)
+}
+
+/// Check `permit` and consume its nonce
+/// A signature by any key other than the owner's traps in the host
+pub fn consume(env: &Env, permit: &Permit, signature: &BytesN<64>) -> Result<(), Error> {
+    if env.ledger().sequence() > permit.expiration_ledger {
+        return Err(Error::PermitExpired);
+    }
+
+    let current = nonce(env, &permit.owner);
+    if permit.nonce != current {
+        return Err(Error::InvalidNonce);
+    }
+
+    let public_key = account_key(env, &permit.owner)?;
+    env.crypto().ed25519_verify(&public_key, &message(env, permit), signature);
+
+    storage::set_persistent(env, &PermitKey::Nonce(permit.owner.clone()), &(current + 1));
+    Ok(())
+}
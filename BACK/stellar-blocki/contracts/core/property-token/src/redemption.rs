@@ -0,0 +1,143 @@
+// Redemption of tokens for property sale proceeds
+//
+// The admin deposits the sale proceeds once and fixes the circulating supply
+// they are split over. Each redeemed token is burned and paid
+// `total_proceeds / supply` of the settlement asset. After the deadline the
+// remainder goes to the designated recipient and redemption closes.
+
+use soroban_sdk::{contracttype, token, Address, Env};
+use stellar_property_errors::TokenError as Error;
+use stellar_property_storage as storage;
+
+#[contracttype]
+#[derive(Clone)]
+enum RedemptionKey {
+    Redemption,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Redemption {
+    pub asset: Address,
+    pub total_proceeds: i128,
+    pub supply: i128,            // Circulating supply when redemption opened
+    pub redeemed_tokens: i128,
+    pub paid_out: i128,
+    pub opened_at: u64,
+    pub deadline: u64,
+    pub unclaimed_recipient: Address,
+    pub closed: bool,
+}
+
+pub fn get(env: &Env) -> Option<Redemption> {
+    storage::get_persistent(env, &RedemptionKey::Redemption)
+}
+
+fn set(env: &Env, redemption: &Redemption) {
+    storage::set_persistent(env, &RedemptionKey::Redemption, redemption);
+}
+
+/// Whether redemption has been opened (no further minting afterwards)
+pub fn is_opened(env: &Env) -> bool {
+    storage::has_persistent(env, &RedemptionKey::Redemption)
+}
+
+/// Payout for redeeming `amount` tokens
+pub fn quote(redemption: &Redemption, amount: i128) -> Result<i128, Error> {
+    amount
+        .checked_mul(redemption.total_proceeds)
+        .and_then(|v| v.checked_div(redemption.supply))
+        .ok_or(Error::InvalidAmount)
+}
+
+/// Pull `proceeds` of `asset` from `admin` and open redemption
+pub fn open(
+    env: &Env,
+    admin: &Address,
+    asset: &Address,
+    proceeds: i128,
+    supply: i128,
+    deadline: u64,
+    unclaimed_recipient: &Address,
+) -> Result<Redemption, Error> {
+    if is_opened(env) {
+        return Err(Error::RedemptionAlreadyOpened);
+    }
+
+    if proceeds <= 0 || supply <= 0 || deadline <= env.ledger().timestamp() {
+        return Err(Error::InvalidAmount);
+    }
+
+    let redemption = Redemption {
+        asset: asset.clone(),
+        total_proceeds: proceeds,
+        supply,
+        redeemed_tokens: 0,
+        paid_out: 0,
+        opened_at: env.ledger().timestamp(),
+        deadline,
+        unclaimed_recipient: unclaimed_recipient.clone(),
+        closed: false,
+    };
+    set(env, &redemption);
+
+    let asset_client = token::TokenClient::new(env, asset);
+    asset_client.transfer(admin, &env.current_contract_address(), &proceeds);
+
+    Ok(redemption)
+}
+
+/// Record the redemption of `amount` tokens and pay `holder`
+/// The caller burns the tokens
+pub fn pay(env: &Env, holder: &Address, amount: i128) -> Result<i128, Error> {
+    let mut redemption = get(env).ok_or(Error::RedemptionNotActive)?;
+
+    if redemption.closed || env.ledger().timestamp() >= redemption.deadline {
+        return Err(Error::RedemptionNotActive);
+    }
+
+    let payout = quote(&redemption, amount)?;
+
+    redemption.redeemed_tokens = redemption.redeemed_tokens
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    redemption.paid_out = redemption.paid_out
+        .checked_add(payout)
+        .ok_or(Error::InvalidAmount)?;
+    set(env, &redemption);
+
+    if payout > 0 {
+        let asset_client = token::TokenClient::new(env, &redemption.asset);
+        asset_client.transfer(&env.current_contract_address(), holder, &payout);
+    }
+
+    Ok(payout)
+}
+
+/// Send unclaimed proceeds to the designated recipient after the deadline
+pub fn close(env: &Env) -> Result<(Redemption, i128), Error> {
+    let mut redemption = get(env).ok_or(Error::RedemptionNotActive)?;
+
+    if redemption.closed {
+        return Err(Error::RedemptionNotActive);
+    }
+
+    if env.ledger().timestamp() < redemption.deadline {
+        return Err(Error::RedemptionNotEnded);
+    }
+
+    let unclaimed = redemption.total_proceeds - redemption.paid_out;
+    redemption.closed = true;
+    set(env, &redemption);
+
+    if unclaimed > 0 {
+        let asset_client = token::TokenClient::new(env, &redemption.asset);
+        asset_client.transfer(
+            &env.current_contract_address(),
+            &redemption.unclaimed_recipient,
+            &unclaimed,
+        );
+    }
+
+    Ok((redemption, unclaimed))
+}
@@ -0,0 +1,149 @@
+// Vesting and lockup schedules
+//
+// Vesting tokens sit in the holder's balance from issuance (so they vote and
+// earn distributions) but only the vested part can be transferred or burned.
+// Nothing vests before the cliff; afterwards the amount vests linearly until
+// `end_time`:
+//
+//   vested = total * (now - cliff_time) / (end_time - cliff_time)
+
+use soroban_sdk::{contracttype, Address, Env, Vec};
+use stellar_property_errors::TokenError as Error;
+use stellar_property_storage as storage;
+
+// SECURITY: Maximum schedules per holder, bounds the work done on every transfer
+const MAX_SCHEDULES_PER_HOLDER: u32 = 10;
+
+#[contracttype]
+#[derive(Clone)]
+enum VestingKey {
+    Schedules(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VestingSchedule {
+    pub total: i128,
+    pub start_time: u64,
+    pub cliff_time: u64, // Fully locked until this timestamp
+    pub end_time: u64,   // Fully vested from this timestamp
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+pub fn get_schedules(env: &Env, holder: &Address) -> Vec<VestingSchedule> {
+    storage::get_persistent(env, &VestingKey::Schedules(holder.clone())).unwrap_or(Vec::new(env))
+}
+
+fn set_schedules(env: &Env, holder: &Address, schedules: &Vec<VestingSchedule>) {
+    storage::set_persistent(env, &VestingKey::Schedules(holder.clone()), schedules);
+}
+
+/// Amount of a schedule vested at the current ledger time
+pub fn vested(env: &Env, schedule: &VestingSchedule) -> i128 {
+    let now = env.ledger().timestamp();
+
+    if schedule.revoked || now >= schedule.end_time {
+        return schedule.total;
+    }
+
+    if now < schedule.cliff_time {
+        return 0;
+    }
+
+    let elapsed = (now - schedule.cliff_time) as i128;
+    let duration = (schedule.end_time - schedule.cliff_time) as i128;
+
+    // total * elapsed fits comfortably in i128 for any 7-decimal supply
+    schedule.total * elapsed / duration
+}
+
+/// Tokens of `holder` that are still unvested
+pub fn locked(env: &Env, holder: &Address) -> i128 {
+    let mut locked = 0;
+    for schedule in get_schedules(env, holder).iter() {
+        locked += schedule.total - vested(env, &schedule);
+    }
+    locked
+}
+
+/// Register a new schedule for `holder` and return its index
+pub fn add_schedule(
+    env: &Env,
+    holder: &Address,
+    total: i128,
+    cliff_seconds: u64,
+    vesting_seconds: u64,
+    revocable: bool,
+) -> Result<(u32, VestingSchedule), Error> {
+    if total <= 0 {
+        return Err(Error::InvalidAmount);
+    }
+
+    let start_time = env.ledger().timestamp();
+    let cliff_time = start_time.checked_add(cliff_seconds).ok_or(Error::InvalidAmount)?;
+    let end_time = cliff_time.checked_add(vesting_seconds).ok_or(Error::InvalidAmount)?;
+
+    let mut schedules = get_schedules(env, holder);
+
+    // SECURITY: Enforce maximum schedules per holder
+    if schedules.len() >= MAX_SCHEDULES_PER_HOLDER {
+        return Err(Error::InvalidAmount);
+    }
+
+    let schedule = VestingSchedule {
+        total,
+        start_time,
+        cliff_time,
+        end_time,
+        revocable,
+        revoked: false,
+    };
+    let index = schedules.len();
+    schedules.push_back(schedule.clone());
+    set_schedules(env, holder, &schedules);
+
+    Ok((index, schedule))
+}
+
+/// Stop a revocable schedule and return the unvested amount
+pub fn revoke(env: &Env, holder: &Address, index: u32) -> Result<i128, Error> {
+    let mut schedules = get_schedules(env, holder);
+    let mut schedule = schedules.get(index).ok_or(Error::TokenNotFound)?;
+
+    if !schedule.revocable || schedule.revoked {
+        return Err(Error::NotAuthorized);
+    }
+
+    let vested_amount = vested(env, &schedule);
+    let unvested = schedule.total - vested_amount;
+
+    // Keep what has vested, release the rest of the lock
+    schedule.total = vested_amount;
+    schedule.revoked = true;
+    schedules.set(index, schedule);
+    set_schedules(env, holder, &schedules);
+
+    Ok(unvested)
+}
+
+/// Reject spending `amount` if it would dip into `holder`'s locked tokens
+pub fn check_unlocked(env: &Env, holder: &Address, amount: i128) -> Result<(), Error> {
+    let locked = locked(env, holder);
+    if locked == 0 {
+        return Ok(());
+    }
+
+    // Overdrafts are reported as InsufficientBalance by the balance helpers
+    let balance = storage::get_balance(env, holder);
+    if amount > balance {
+        return Ok(());
+    }
+
+    let available = balance.checked_sub(locked).ok_or(Error::InvalidAmount)?;
+    if amount > available {
+        return Err(Error::TokensLocked);
+    }
+
+    Ok(())
+}
@@ -1,14 +1,20 @@
 #![no_std]
 
 use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, String, Symbol, Vec};
+use stellar_property_access::{self as access, Role};
 use stellar_property_errors::Error;
 use stellar_property_events as events;
 use stellar_property_storage::{self as storage, DataKey};
 
-// SECURITY: Maximum ownership history records per property to prevent unbounded storage
-const MAX_OWNERSHIP_HISTORY: u32 = 5000;
-// SECURITY: Maximum number of concurrent owners per property
-const MAX_CONCURRENT_OWNERS: u32 = 100;
+// SECURITY: Maximum owners set per update, keeps each call within budget
+const MAX_OWNERS_PER_UPDATE: u32 = 100;
+// SECURITY: Maximum owners or history records returned per page
+const MAX_OWNERS_PAGE: u32 = 100;
+
+mod migration;
+mod ownership;
+
+pub use migration::PropertyMetadataV1;
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -20,6 +26,7 @@ pub struct PropertyMetadata {
     pub verified: bool,
     pub verification_date: u64,
     pub token_contract: Address,
+    pub verified_by: Option<Address>,
 }
 
 #[contracttype]
@@ -52,6 +59,7 @@ impl OwnershipRegistryContract {
         admin.require_auth();
 
         storage::set_admin(&env, &admin);
+        storage::set_schema_version(&env, migration::SCHEMA_VERSION);
         storage::set_initialized(&env);
 
         Ok(())
@@ -67,8 +75,7 @@ impl OwnershipRegistryContract {
         legal_id: String,
         token_contract: Address,
     ) -> Result<(), Error> {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
+        access::require_admin(&env);
 
         // Check if property already exists
         if storage::has_persistent(&env, &DataKey::PropertyMetadata(property_id.clone())) {
@@ -88,23 +95,15 @@ impl OwnershipRegistryContract {
             verified: false, // Initially not verified
             verification_date: 0,
             token_contract: token_contract.clone(),
+            verified_by: None,
         };
 
         // Store property metadata
         storage::set_persistent(&env, &DataKey::PropertyMetadata(property_id.clone()), &metadata);
 
-        // Create initial ownership record
-        let initial_record = OwnershipRecord {
-            timestamp: env.ledger().timestamp(),
-            owner: owner.clone(),
-            percentage: 10000, // 100% (in basis points)
-            tx_hash: BytesN::from_array(&env, &[0u8; 32]), // Placeholder
-        };
-
-        // Store ownership history (initially just owner with 100%)
-        let mut history = Vec::new(&env);
-        history.push_back(initial_record);
-        storage::set_persistent(&env, &DataKey::OwnershipHistory(property_id.clone()), &history);
+        // Initially just the owner with 100% (in basis points)
+        ownership::init(&env, &property_id);
+        ownership::set_share(&env, &property_id, &owner, 10000);
 
         // Emit event
         events::property_registered(&env, property_id, owner, valuation);
@@ -112,21 +111,18 @@ impl OwnershipRegistryContract {
         Ok(())
     }
 
-    /// Verify a property (only admin)
-    pub fn verify_property(env: Env, property_id: Symbol) -> Result<(), Error> {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
+    /// Verify a property (verifier role)
+    pub fn verify_property(env: Env, caller: Address, property_id: Symbol) -> Result<(), Error> {
+        access::require_role(&env, Role::Verifier, &caller)?;
 
         // Get property metadata
-        let mut metadata: PropertyMetadata = storage::get_persistent(
-            &env,
-            &DataKey::PropertyMetadata(property_id.clone()),
-        )
-        .ok_or(Error::PropertyNotFound)?;
+        let mut metadata = migration::read_metadata(&env, &property_id)
+            .ok_or(Error::PropertyNotFound)?;
 
         // Update verification status
         metadata.verified = true;
         metadata.verification_date = env.ledger().timestamp();
+        metadata.verified_by = Some(caller);
 
         // Store updated metadata
         storage::set_persistent(&env, &DataKey::PropertyMetadata(property_id), &metadata);
@@ -135,78 +131,59 @@ impl OwnershipRegistryContract {
     }
 
     /// Update ownership after a transfer (called by marketplace or admin)
+    /// Sets the share of each of `new_holders`; owners left out keep theirs
+    /// and a 0% share removes the owner. Shares may add up to less than 100%,
+    /// the rest being held in custody or lost to rounding
     pub fn update_ownership(
         env: Env,
+        caller: Address,
         property_id: Symbol,
         new_holders: Vec<OwnerInfo>,
     ) -> Result<(), Error> {
-        // Allow both admin and marketplace to update ownership
-        // In production, verify caller is authorized marketplace contract
-        // For now, anyone can call this (marketplace will be the primary caller)
+        // SECURITY: Only the admin or the authorized marketplace can update ownership
+        caller.require_auth();
+
+        let marketplace: Option<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::MarketplaceAddress);
+        if caller != storage::get_admin(&env) && Some(caller) != marketplace {
+            return Err(Error::NotAuthorized);
+        }
 
         // Verify property exists
         if !storage::has_persistent(&env, &DataKey::PropertyMetadata(property_id.clone())) {
             return Err(Error::PropertyNotFound);
         }
 
-        // SECURITY: Validate number of concurrent owners
-        if new_holders.len() > MAX_CONCURRENT_OWNERS {
+        // SECURITY: Validate number of owners per update
+        if new_holders.len() > MAX_OWNERS_PER_UPDATE {
             return Err(Error::InvalidOwnershipData);
         }
 
-        // Validate total percentage = 100% with checked arithmetic
-        let mut total_percentage: i128 = 0;
         for holder in new_holders.iter() {
-            total_percentage = total_percentage.checked_add(holder.percentage)
-                .ok_or(Error::InvalidPercentage)?;
+            if holder.percentage < 0 || holder.percentage > 10000 {
+                return Err(Error::InvalidPercentage);
+            }
+            ownership::set_share(&env, &property_id, &holder.owner, holder.percentage);
         }
 
-        if total_percentage != 10000 {
+        // Shares can't add up to more than 100%
+        if ownership::total_share(&env, &property_id) > 10000 {
             return Err(Error::InvalidPercentage);
         }
 
-        // Get existing history
-        let mut history: Vec<OwnershipRecord> = storage::get_persistent(
-            &env,
-            &DataKey::OwnershipHistory(property_id.clone()),
-        )
-        .unwrap_or(Vec::new(&env));
-
-        // Add new records
-        let current_time = env.ledger().timestamp();
-        for holder in new_holders.iter() {
-            // SECURITY: Enforce maximum history with FIFO eviction
-            if history.len() >= MAX_OWNERSHIP_HISTORY {
-                // Remove oldest record when limit reached
-                history.remove(0);
-            }
-
-            let record = OwnershipRecord {
-                timestamp: current_time,
-                owner: holder.owner.clone(),
-                percentage: holder.percentage,
-                tx_hash: BytesN::from_array(&env, &[0u8; 32]), // Placeholder
-            };
-            history.push_back(record);
-
-            // Emit event for each new owner
-            events::ownership_updated(&env, property_id.clone(), holder.owner.clone(), holder.percentage);
-        }
-
-        // Store updated history
-        storage::set_persistent(&env, &DataKey::OwnershipHistory(property_id), &history);
-
         Ok(())
     }
 
-    /// Record a legal document hash for a property
+    /// Record a legal document hash for a property (verifier role)
     pub fn record_legal_document(
         env: Env,
+        caller: Address,
         property_id: Symbol,
         document_hash: BytesN<32>,
     ) -> Result<(), Error> {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
+        access::require_role(&env, Role::Verifier, &caller)?;
 
         // Verify property exists
         if !storage::has_persistent(&env, &DataKey::PropertyMetadata(property_id.clone())) {
@@ -222,83 +199,121 @@ impl OwnershipRegistryContract {
         Ok(())
     }
 
-    // ========== VIEW FUNCTIONS ==========
+    /// Authorize the marketplace to update ownership after trades (admin only)
+    pub fn set_marketplace(env: Env, marketplace: Address) -> Result<(), Error> {
+        access::require_admin(&env);
 
-    /// Verify ownership of a property by an address
-    pub fn verify_ownership(env: Env, property_id: Symbol, user: Address) -> (bool, i128) {
-        // Get ownership history
-        let history: Option<Vec<OwnershipRecord>> =
-            storage::get_persistent(&env, &DataKey::OwnershipHistory(property_id));
+        env.storage()
+            .instance()
+            .set(&DataKey::MarketplaceAddress, &marketplace);
 
-        if history.is_none() {
-            return (false, 0);
-        }
+        Ok(())
+    }
 
-        let history = history.unwrap();
+    // ========== ACCESS CONTROL ==========
 
-        // Find most recent record for user
-        let mut percentage: i128 = 0;
-        let mut found = false;
+    /// Grant `role` to `account` (admin only)
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::grant_role(&env, role, &account);
+        Ok(())
+    }
 
-        for i in (0..history.len()).rev() {
-            let record = history.get(i).unwrap();
-            if record.owner == user {
-                percentage = record.percentage;
-                found = true;
-                break;
-            }
+    /// Revoke `role` from `account` (admin only)
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::revoke_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Check whether `account` holds `role` (the admin holds every role)
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        access::has_role(&env, role, &account)
+    }
+
+    /// Nominate a new admin; takes effect once they call `accept_admin` (admin only)
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        access::propose_admin(&env, &new_admin);
+        Ok(())
+    }
+
+    /// Accept a pending admin nomination (pending admin only)
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        access::accept_admin(&env)?;
+        Ok(())
+    }
+
+    /// Get the nominated admin awaiting acceptance
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        access::pending_admin(&env)
+    }
+
+    // ========== UPGRADES ==========
+
+    /// Replace the contract code, keeping storage (admin only)
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        access::upgrade(&env, &new_wasm_hash);
+        Ok(())
+    }
+
+    /// Convert the metadata and owners of `property_ids` to the current layout
+    /// and record the schema version (admin only)
+    /// Reads accept either layout, so properties can be converted in any order
+    pub fn migrate(env: Env, property_ids: Vec<Symbol>) -> Result<(), Error> {
+        access::require_admin(&env);
+
+        if property_ids.len() > migration::MAX_MIGRATION_BATCH {
+            return Err(Error::InvalidAmount);
         }
 
-        (found, percentage)
+        for property_id in property_ids.iter() {
+            migration::migrate_metadata(&env, &property_id);
+            ownership::index_legacy(&env, &property_id);
+        }
+
+        migration::finish(&env);
+        Ok(())
     }
 
-    /// Get current property owners with their percentages
-    pub fn get_property_owners(env: Env, property_id: Symbol) -> Vec<OwnerInfo> {
-        let history: Vec<OwnershipRecord> = storage::get_persistent(
-            &env,
-            &DataKey::OwnershipHistory(property_id),
-        )
-        .unwrap_or(Vec::new(&env));
-
-        // Get unique owners with their latest percentages
-        let mut owners = Vec::new(&env);
-        let mut seen_owners: Vec<Address> = Vec::new(&env);
-
-        // Iterate from most recent to oldest
-        for i in (0..history.len()).rev() {
-            let record = history.get(i).unwrap();
-
-            // Check if we've already seen this owner
-            let mut already_seen = false;
-            for j in 0..seen_owners.len() {
-                if seen_owners.get(j).unwrap() == record.owner {
-                    already_seen = true;
-                    break;
-                }
-            }
+    /// Storage layout version of this contract
+    pub fn schema_version(env: Env) -> u32 {
+        storage::get_schema_version(&env)
+    }
 
-            if !already_seen && record.percentage > 0 {
-                seen_owners.push_back(record.owner.clone());
-                owners.push_back(OwnerInfo {
-                    owner: record.owner,
-                    percentage: record.percentage,
-                });
-            }
+    // ========== VIEW FUNCTIONS ==========
+
+    /// Verify ownership of a property by an address
+    /// Returns whether the address ever held a share, and its current share
+    pub fn verify_ownership(env: Env, property_id: Symbol, user: Address) -> (bool, i128) {
+        match ownership::share(&env, &property_id, &user) {
+            Some(percentage) => (true, percentage),
+            None => (false, 0),
         }
+    }
 
-        owners
+    /// Get current property owners with their percentages, `limit` at a
+    /// time starting at `cursor`
+    pub fn get_property_owners(env: Env, property_id: Symbol, cursor: u32, limit: u32) -> Vec<OwnerInfo> {
+        ownership::owners(&env, &property_id, cursor, limit.min(MAX_OWNERS_PAGE))
     }
 
-    /// Get full ownership history for a property
-    pub fn get_property_history(env: Env, property_id: Symbol) -> Vec<OwnershipRecord> {
-        storage::get_persistent(&env, &DataKey::OwnershipHistory(property_id))
-            .unwrap_or(Vec::new(&env))
+    /// Get the number of owners with a non-zero share
+    pub fn owner_count(env: Env, property_id: Symbol) -> u32 {
+        ownership::count(&env, &property_id)
+    }
+
+    /// Get the ownership history of a property, oldest first, `limit`
+    /// records at a time starting at `cursor`
+    pub fn get_property_history(env: Env, property_id: Symbol, cursor: u32, limit: u32) -> Vec<OwnershipRecord> {
+        ownership::history(&env, &property_id, cursor, limit.min(MAX_OWNERS_PAGE))
+    }
+
+    /// Get the number of ownership records of a property
+    pub fn history_len(env: Env, property_id: Symbol) -> u32 {
+        ownership::history_len(&env, &property_id)
     }
 
     /// Get property metadata
     pub fn get_property_metadata(env: Env, property_id: Symbol) -> Result<PropertyMetadata, Error> {
-        storage::get_persistent(&env, &DataKey::PropertyMetadata(property_id))
-            .ok_or(Error::PropertyNotFound)
+        migration::read_metadata(&env, &property_id).ok_or(Error::PropertyNotFound)
     }
 
     /// Get legal document hash for a property
@@ -308,13 +323,19 @@ impl OwnershipRegistryContract {
 
     /// Check if property is verified
     pub fn is_verified(env: Env, property_id: Symbol) -> Result<bool, Error> {
-        let metadata: PropertyMetadata =
-            storage::get_persistent(&env, &DataKey::PropertyMetadata(property_id))
-                .ok_or(Error::PropertyNotFound)?;
+        let metadata = migration::read_metadata(&env, &property_id)
+            .ok_or(Error::PropertyNotFound)?;
 
         Ok(metadata.verified)
     }
 
+    /// Get the marketplace authorized to update ownership
+    pub fn marketplace(env: Env) -> Option<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::MarketplaceAddress)
+    }
+
     /// Get contract admin
     pub fn admin(env: Env) -> Address {
         storage::get_admin(&env)
@@ -329,7 +350,9 @@ mod test {
     #[test]
     fn test_initialize() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, OwnershipRegistryContract);
+        env.mock_all_auths();
+
+        let contract_id = env.register(OwnershipRegistryContract, ());
         let client = OwnershipRegistryContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -343,7 +366,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, OwnershipRegistryContract);
+        let contract_id = env.register(OwnershipRegistryContract, ());
         let client = OwnershipRegistryContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -370,7 +393,7 @@ mod test {
         let metadata = client.get_property_metadata(&property_id);
         assert_eq!(metadata.property_id, property_id);
         assert_eq!(metadata.valuation, valuation);
-        assert_eq!(metadata.verified, false);
+        assert!(!metadata.verified);
 
         // Check ownership
         let (is_owner, percentage) = client.verify_ownership(&property_id, &owner);
@@ -383,7 +406,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, OwnershipRegistryContract);
+        let contract_id = env.register(OwnershipRegistryContract, ());
         let client = OwnershipRegistryContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -403,13 +426,48 @@ mod test {
         );
 
         // Initially not verified
-        assert_eq!(client.is_verified(&property_id), false);
+        assert!(!client.is_verified(&property_id));
 
         // Verify property
-        client.verify_property(&property_id);
+        client.verify_property(&admin, &property_id);
 
         // Now verified
-        assert_eq!(client.is_verified(&property_id), true);
+        assert!(client.is_verified(&property_id));
+    }
+
+    #[test]
+    fn test_verifier_role() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(OwnershipRegistryContract, ());
+        let client = OwnershipRegistryContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let verifier = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let token_contract = Address::generate(&env);
+
+        client.initialize(&admin);
+
+        let property_id = Symbol::new(&env, "PROP001");
+        client.register_property(
+            &property_id,
+            &owner,
+            &String::from_str(&env, "Address"),
+            &5_000_000_0000000,
+            &String::from_str(&env, "LEGAL-001"),
+            &token_contract,
+        );
+
+        assert_eq!(
+            client.try_verify_property(&verifier, &property_id),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        client.grant_role(&Role::Verifier, &verifier);
+        client.verify_property(&verifier, &property_id);
+        assert!(client.is_verified(&property_id));
     }
 
     #[test]
@@ -417,7 +475,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, OwnershipRegistryContract);
+        let contract_id = env.register(OwnershipRegistryContract, ());
         let client = OwnershipRegistryContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -448,7 +506,7 @@ mod test {
             percentage: 2500,
         });
 
-        client.update_ownership(&property_id, &new_holders);
+        client.update_ownership(&admin, &property_id, &new_holders);
 
         // Verify new ownership
         let (is_owner1, percentage1) = client.verify_ownership(&property_id, &owner1);
@@ -458,6 +516,44 @@ mod test {
         let (is_owner2, percentage2) = client.verify_ownership(&property_id, &owner2);
         assert!(is_owner2);
         assert_eq!(percentage2, 2500);
+
+        // Only the admin or the authorized marketplace can update
+        let marketplace = Address::generate(&env);
+        let mut sold_out = Vec::new(&env);
+        sold_out.push_back(OwnerInfo {
+            owner: owner2.clone(),
+            percentage: 10000,
+        });
+        assert_eq!(
+            client.try_update_ownership(&marketplace, &property_id, &sold_out),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        client.set_marketplace(&marketplace);
+        assert_eq!(client.marketplace(), Some(marketplace.clone()));
+
+        // Owners left out of the update keep their share, so the total would exceed 100%
+        assert_eq!(
+            client.try_update_ownership(&marketplace, &property_id, &sold_out),
+            Err(Ok(Error::InvalidPercentage))
+        );
+        sold_out.push_front(OwnerInfo {
+            owner: owner1.clone(),
+            percentage: 0,
+        });
+        client.update_ownership(&marketplace, &property_id, &sold_out);
+
+        // A 0% share removes the owner
+        assert_eq!(client.verify_ownership(&property_id, &owner1), (true, 0));
+        let owners = client.get_property_owners(&property_id, &0, &10);
+        assert_eq!(owners.len(), 1);
+        assert_eq!(owners.get(0).unwrap().owner, owner2);
+
+        // Only changes are recorded
+        assert_eq!(client.history_len(&property_id), 5);
+        let history = client.get_property_history(&property_id, &3, &10);
+        assert_eq!((history.get(0).unwrap().owner, history.get(0).unwrap().percentage), (owner1, 0));
+        assert_eq!((history.get(1).unwrap().owner, history.get(1).unwrap().percentage), (owner2, 10000));
     }
 
     #[test]
@@ -465,7 +561,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, OwnershipRegistryContract);
+        let contract_id = env.register(OwnershipRegistryContract, ());
         let client = OwnershipRegistryContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -486,7 +582,7 @@ mod test {
 
         // Record document
         let doc_hash = BytesN::from_array(&env, &[1u8; 32]);
-        client.record_legal_document(&property_id, &doc_hash);
+        client.record_legal_document(&admin, &property_id, &doc_hash);
 
         // Verify document was recorded
         let stored_hash = client.get_document_hash(&property_id);
@@ -499,7 +595,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, OwnershipRegistryContract);
+        let contract_id = env.register(OwnershipRegistryContract, ());
         let client = OwnershipRegistryContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -530,10 +626,111 @@ mod test {
             percentage: 4000,
         });
 
-        client.update_ownership(&property_id, &new_holders);
+        client.update_ownership(&admin, &property_id, &new_holders);
 
         // Get all owners
-        let owners = client.get_property_owners(&property_id);
+        let owners = client.get_property_owners(&property_id, &0, &10);
         assert_eq!(owners.len(), 2);
+        assert_eq!(client.owner_count(&property_id), 2);
+        assert_eq!(client.get_property_owners(&property_id, &1, &10).len(), 1);
+    }
+
+    #[test]
+    fn test_upgrade_and_migrate_metadata() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(OwnershipRegistryContract, ());
+        let client = OwnershipRegistryContractClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let owner = Address::generate(&env);
+        let token_contract = Address::generate(&env);
+
+        client.initialize(&admin);
+
+        // Live property in the current layout
+        let live_id = Symbol::new(&env, "PROP001");
+        client.register_property(
+            &live_id,
+            &owner,
+            &String::from_str(&env, "Address"),
+            &5_000_000_0000000,
+            &String::from_str(&env, "LEGAL-001"),
+            &token_contract,
+        );
+
+        // A verified property left behind by a version 1 deployment
+        let old_id = Symbol::new(&env, "PROP002");
+        let old_metadata = PropertyMetadataV1 {
+            property_id: old_id.clone(),
+            address: String::from_str(&env, "Old address"),
+            valuation: 1_000_000_0000000,
+            legal_id: String::from_str(&env, "LEGAL-002"),
+            verified: true,
+            verification_date: 1000,
+            token_contract: token_contract.clone(),
+        };
+        // And its ownership history: owner sold 40% to buyer, who later left
+        let buyer = Address::generate(&env);
+        let record = |owner: &Address, percentage: i128| OwnershipRecord {
+            timestamp: 1000,
+            owner: owner.clone(),
+            percentage,
+            tx_hash: BytesN::from_array(&env, &[0u8; 32]),
+        };
+        let old_history = Vec::from_array(
+            &env,
+            [
+                record(&owner, 10000),
+                record(&owner, 6000),
+                record(&buyer, 4000),
+                record(&owner, 10000),
+                record(&buyer, 0),
+            ],
+        );
+        env.as_contract(&contract_id, || {
+            storage::set_persistent(&env, &DataKey::PropertyMetadata(old_id.clone()), &old_metadata);
+            storage::set_persistent(&env, &DataKey::OwnershipHistory(old_id.clone()), &old_history);
+            env.storage().instance().remove(&DataKey::SchemaVersion);
+        });
+        assert_eq!(client.schema_version(), 1);
+
+        let wasm_hash = stellar_property_access::testutils::upload_upgrade_wasm(&env);
+        client.upgrade(&wasm_hash);
+        env.register_at(&contract_id, OwnershipRegistryContract, ());
+
+        // Readable before it is rewritten
+        let converted = client.get_property_metadata(&old_id);
+        assert!(converted.verified);
+        assert_eq!(converted.verified_by, None);
+        assert_eq!(client.verify_ownership(&old_id, &owner), (true, 10000));
+        assert_eq!(client.verify_ownership(&old_id, &buyer), (true, 0));
+        assert_eq!(client.owner_count(&old_id), 1);
+
+        client.migrate(&Vec::from_array(&env, [old_id.clone()]));
+        assert_eq!(client.schema_version(), migration::SCHEMA_VERSION);
+        env.as_contract(&contract_id, || {
+            let stored: PropertyMetadata =
+                storage::get_persistent(&env, &DataKey::PropertyMetadata(old_id.clone())).unwrap();
+            assert_eq!(stored, converted);
+        });
+
+        // Owners are indexed and the old history starts the new one
+        let owners = client.get_property_owners(&old_id, &0, &10);
+        assert_eq!(owners, Vec::from_array(&env, [OwnerInfo { owner: owner.clone(), percentage: 10000 }]));
+        assert_eq!(client.verify_ownership(&old_id, &buyer), (true, 0));
+        let mut sale = Vec::new(&env);
+        sale.push_back(OwnerInfo { owner: owner.clone(), percentage: 7000 });
+        sale.push_back(OwnerInfo { owner: buyer.clone(), percentage: 3000 });
+        client.update_ownership(&admin, &old_id, &sale);
+        assert_eq!(client.owner_count(&old_id), 2);
+        assert_eq!(client.history_len(&old_id), 7);
+        assert_eq!(client.get_property_history(&old_id, &0, &5), old_history);
+        assert_eq!(client.get_property_history(&old_id, &6, &5).get(0).unwrap().percentage, 3000);
+
+        // New verifications record the verifier
+        client.verify_property(&admin, &live_id);
+        assert_eq!(client.get_property_metadata(&live_id).verified_by, Some(admin));
     }
 }
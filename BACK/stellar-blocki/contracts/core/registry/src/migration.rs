@@ -0,0 +1,84 @@
+// Storage layout versions
+//
+//   1 - initial layout
+//   2 - `PropertyMetadata` records `verified_by`
+//   3 - owners kept in an indexed set with a per-record history (see
+//       ownership.rs)
+//
+// Metadata and owners stored by an older version are read as they are, and
+// owners are indexed on the first update of their property. The registry
+// keeps no index of properties, so `migrate` converts the ids it is given.
+
+use soroban_sdk::{contracttype, Address, Env, Map, String, Symbol, TryFromVal, Val};
+use stellar_property_events as events;
+use stellar_property_storage::{self as storage, DataKey};
+
+use crate::PropertyMetadata;
+
+pub const SCHEMA_VERSION: u32 = 3;
+
+// SECURITY: Maximum properties converted per call, keeps each call within budget
+pub const MAX_MIGRATION_BATCH: u32 = 100;
+
+/// `PropertyMetadata` as stored by schema version 1
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PropertyMetadataV1 {
+    pub property_id: Symbol,
+    pub address: String,
+    pub valuation: i128,
+    pub legal_id: String,
+    pub verified: bool,
+    pub verification_date: u64,
+    pub token_contract: Address,
+}
+
+impl From<PropertyMetadataV1> for PropertyMetadata {
+    fn from(old: PropertyMetadataV1) -> Self {
+        // Version 1 did not record who verified a property
+        PropertyMetadata {
+            property_id: old.property_id,
+            address: old.address,
+            valuation: old.valuation,
+            legal_id: old.legal_id,
+            verified: old.verified,
+            verification_date: old.verification_date,
+            token_contract: old.token_contract,
+            verified_by: None,
+        }
+    }
+}
+
+// Returns the metadata and whether it is stored in the old layout
+fn load(env: &Env, property_id: &Symbol) -> Option<(PropertyMetadata, bool)> {
+    let raw: Map<Symbol, Val> =
+        storage::get_persistent(env, &DataKey::PropertyMetadata(property_id.clone()))?;
+    let val = raw.to_val();
+
+    if raw.contains_key(Symbol::new(env, "verified_by")) {
+        PropertyMetadata::try_from_val(env, &val).ok().map(|metadata| (metadata, false))
+    } else {
+        PropertyMetadataV1::try_from_val(env, &val).ok().map(|old| (old.into(), true))
+    }
+}
+
+/// Read property metadata written by any schema version
+pub fn read_metadata(env: &Env, property_id: &Symbol) -> Option<PropertyMetadata> {
+    load(env, property_id).map(|(metadata, _)| metadata)
+}
+
+/// Rewrite the metadata of a property if it still uses an old layout
+pub fn migrate_metadata(env: &Env, property_id: &Symbol) {
+    if let Some((metadata, true)) = load(env, property_id) {
+        storage::set_persistent(env, &DataKey::PropertyMetadata(property_id.clone()), &metadata);
+    }
+}
+
+/// Record that storage now follows the current layout
+pub fn finish(env: &Env) {
+    let from_version = storage::get_schema_version(env);
+    if from_version != SCHEMA_VERSION {
+        storage::set_schema_version(env, SCHEMA_VERSION);
+        events::schema_migrated(env, from_version, SCHEMA_VERSION);
+    }
+}
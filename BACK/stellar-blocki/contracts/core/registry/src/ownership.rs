@@ -0,0 +1,228 @@
+// Property owners and ownership history
+//
+// Each owner's current share is stored under its own key, and owners with a
+// non-zero share are kept in an indexed set per property (`OwnerAt(i)` maps
+// positions to addresses, `OwnerIndex(addr)` maps back, removal swaps the last
+// owner into the freed slot). Updates cost O(1) per owner however many owners
+// a property has. The history is an append-only ledger of share changes, one
+// entry per key.
+//
+// Version 2 kept both in the `OwnershipHistory` vector, with one record per
+// owner on every update. `index_legacy` replays it once per property; the
+// vector stays in place as the start of the history.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, Symbol, Vec};
+use stellar_property_events as events;
+use stellar_property_storage::{self as storage, DataKey};
+
+use crate::{OwnerInfo, OwnershipRecord};
+
+#[contracttype]
+#[derive(Clone)]
+enum OwnershipKey {
+    Indexed(Symbol),
+    Share(Symbol, Address),
+    TotalShare(Symbol),
+    OwnerCount(Symbol),
+    OwnerAt(Symbol, u32),
+    OwnerIndex(Symbol, Address),
+    RecordCount(Symbol),
+    Record(Symbol, u32),
+}
+
+fn legacy_history(env: &Env, property_id: &Symbol) -> Vec<OwnershipRecord> {
+    storage::get_persistent(env, &DataKey::OwnershipHistory(property_id.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+// Latest share of every owner in the version 2 history, newest first
+fn legacy_shares(env: &Env, property_id: &Symbol) -> Vec<OwnerInfo> {
+    let history = legacy_history(env, property_id);
+    let mut shares: Vec<OwnerInfo> = Vec::new(env);
+
+    for i in (0..history.len()).rev() {
+        let record = history.get(i).unwrap();
+        if shares.iter().any(|share| share.owner == record.owner) {
+            continue;
+        }
+        shares.push_back(OwnerInfo {
+            owner: record.owner,
+            percentage: record.percentage,
+        });
+    }
+
+    shares
+}
+
+fn is_indexed(env: &Env, property_id: &Symbol) -> bool {
+    storage::has_persistent(env, &OwnershipKey::Indexed(property_id.clone()))
+}
+
+/// Index the owners recorded by the version 2 history, once per property
+pub fn index_legacy(env: &Env, property_id: &Symbol) {
+    if is_indexed(env, property_id) {
+        return;
+    }
+
+    let mut total: i128 = 0;
+    for share in legacy_shares(env, property_id).iter() {
+        write_share(env, property_id, &share.owner, 0, share.percentage);
+        total += share.percentage;
+    }
+    storage::set_persistent(env, &OwnershipKey::TotalShare(property_id.clone()), &total);
+    storage::set_persistent(env, &OwnershipKey::Indexed(property_id.clone()), &true);
+}
+
+/// Start the owners of a newly registered property
+pub fn init(env: &Env, property_id: &Symbol) {
+    storage::set_persistent(env, &OwnershipKey::Indexed(property_id.clone()), &true);
+}
+
+/// Share of `owner`, or None if it never held one
+pub fn share(env: &Env, property_id: &Symbol, owner: &Address) -> Option<i128> {
+    if !is_indexed(env, property_id) {
+        return legacy_shares(env, property_id)
+            .iter()
+            .find(|share| share.owner == *owner)
+            .map(|share| share.percentage);
+    }
+
+    storage::get_persistent(env, &OwnershipKey::Share(property_id.clone(), owner.clone()))
+}
+
+/// Sum of all current shares
+pub fn total_share(env: &Env, property_id: &Symbol) -> i128 {
+    storage::get_persistent(env, &OwnershipKey::TotalShare(property_id.clone())).unwrap_or(0)
+}
+
+/// Set the share of `owner` and record the change
+pub fn set_share(env: &Env, property_id: &Symbol, owner: &Address, percentage: i128) {
+    index_legacy(env, property_id);
+
+    let old = share(env, property_id, owner).unwrap_or(0);
+    if old == percentage {
+        return;
+    }
+
+    write_share(env, property_id, owner, old, percentage);
+    let total = total_share(env, property_id) - old + percentage;
+    storage::set_persistent(env, &OwnershipKey::TotalShare(property_id.clone()), &total);
+
+    push_record(
+        env,
+        property_id,
+        &OwnershipRecord {
+            timestamp: env.ledger().timestamp(),
+            owner: owner.clone(),
+            percentage,
+            tx_hash: BytesN::from_array(env, &[0u8; 32]), // Placeholder
+        },
+    );
+    events::ownership_updated(env, property_id.clone(), owner.clone(), percentage);
+}
+
+fn write_share(env: &Env, property_id: &Symbol, owner: &Address, old: i128, new: i128) {
+    storage::set_persistent(env, &OwnershipKey::Share(property_id.clone(), owner.clone()), &new);
+    if old <= 0 && new > 0 {
+        add_owner(env, property_id, owner);
+    } else if old > 0 && new <= 0 {
+        remove_owner(env, property_id, owner);
+    }
+}
+
+/// Number of owners with a non-zero share
+pub fn count(env: &Env, property_id: &Symbol) -> u32 {
+    if !is_indexed(env, property_id) {
+        return current_legacy_owners(env, property_id).len();
+    }
+
+    storage::get_persistent(env, &OwnershipKey::OwnerCount(property_id.clone())).unwrap_or(0)
+}
+
+/// Owners with a non-zero share, `limit` at a time starting at `cursor`
+pub fn owners(env: &Env, property_id: &Symbol, cursor: u32, limit: u32) -> Vec<OwnerInfo> {
+    let end = cursor.saturating_add(limit).min(count(env, property_id));
+    if !is_indexed(env, property_id) {
+        return current_legacy_owners(env, property_id).slice(cursor.min(end)..end);
+    }
+
+    let mut page = Vec::new(env);
+    for index in cursor..end {
+        let owner: Address =
+            storage::get_persistent(env, &OwnershipKey::OwnerAt(property_id.clone(), index)).unwrap();
+        let percentage = share(env, property_id, &owner).unwrap_or(0);
+        page.push_back(OwnerInfo { owner, percentage });
+    }
+
+    page
+}
+
+fn current_legacy_owners(env: &Env, property_id: &Symbol) -> Vec<OwnerInfo> {
+    let mut owners = Vec::new(env);
+    for share in legacy_shares(env, property_id).iter() {
+        if share.percentage > 0 {
+            owners.push_back(share);
+        }
+    }
+    owners
+}
+
+fn add_owner(env: &Env, property_id: &Symbol, owner: &Address) {
+    let index = storage::get_persistent(env, &OwnershipKey::OwnerCount(property_id.clone())).unwrap_or(0u32);
+    storage::set_persistent(env, &OwnershipKey::OwnerAt(property_id.clone(), index), owner);
+    storage::set_persistent(env, &OwnershipKey::OwnerIndex(property_id.clone(), owner.clone()), &index);
+    storage::set_persistent(env, &OwnershipKey::OwnerCount(property_id.clone()), &(index + 1));
+}
+
+fn remove_owner(env: &Env, property_id: &Symbol, owner: &Address) {
+    let index_key = OwnershipKey::OwnerIndex(property_id.clone(), owner.clone());
+    let index: u32 = match storage::get_persistent(env, &index_key) {
+        Some(index) => index,
+        None => return,
+    };
+
+    // Move the last owner into the freed slot
+    let last_index = count(env, property_id) - 1;
+    if index != last_index {
+        let last: Address =
+            storage::get_persistent(env, &OwnershipKey::OwnerAt(property_id.clone(), last_index)).unwrap();
+        storage::set_persistent(env, &OwnershipKey::OwnerAt(property_id.clone(), index), &last);
+        storage::set_persistent(env, &OwnershipKey::OwnerIndex(property_id.clone(), last), &index);
+    }
+
+    storage::remove_persistent(env, &OwnershipKey::OwnerAt(property_id.clone(), last_index));
+    storage::remove_persistent(env, &index_key);
+    storage::set_persistent(env, &OwnershipKey::OwnerCount(property_id.clone()), &last_index);
+}
+
+fn record_count(env: &Env, property_id: &Symbol) -> u32 {
+    storage::get_persistent(env, &OwnershipKey::RecordCount(property_id.clone())).unwrap_or(0)
+}
+
+fn push_record(env: &Env, property_id: &Symbol, record: &OwnershipRecord) {
+    let index = record_count(env, property_id);
+    storage::set_persistent(env, &OwnershipKey::Record(property_id.clone(), index), record);
+    storage::set_persistent(env, &OwnershipKey::RecordCount(property_id.clone()), &(index + 1));
+}
+
+/// Number of share changes recorded for a property
+pub fn history_len(env: &Env, property_id: &Symbol) -> u32 {
+    legacy_history(env, property_id).len() + record_count(env, property_id)
+}
+
+/// Share changes `limit` at a time starting at `cursor`, oldest first
+pub fn history(env: &Env, property_id: &Symbol, cursor: u32, limit: u32) -> Vec<OwnershipRecord> {
+    let legacy = legacy_history(env, property_id);
+    let end = cursor.saturating_add(limit).min(legacy.len() + record_count(env, property_id));
+
+    let mut page = Vec::new(env);
+    for position in cursor..end {
+        let record = match position.checked_sub(legacy.len()) {
+            Some(index) => storage::get_persistent(env, &OwnershipKey::Record(property_id.clone(), index)).unwrap(),
+            None => legacy.get(position).unwrap(),
+        };
+        page.push_back(record);
+    }
+
+    page
+}
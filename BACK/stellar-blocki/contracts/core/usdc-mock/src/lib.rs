@@ -1,5 +1,5 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Symbol, Vec, Map};
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, String, Symbol};
 
 // Simple USDC Mock token for testnet
 // This implements a basic token contract for testing purposes only
@@ -12,6 +12,23 @@ pub struct Groth16Proof {
     pub c: (BytesN<32>, BytesN<32>),
 }
 
+// Credential kinds that can be bound to an address
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CredentialType {
+    Kyc,
+    Accredited,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Credential {
+    pub credential_type: CredentialType,
+    pub nullifier: BytesN<32>,
+    pub issued_at: u64,
+    pub expires_at: u64, // 0 = no expiry
+}
+
 #[contract]
 pub struct ZKVerifier;
 
@@ -148,6 +165,94 @@ impl ZKVerifier {
         true
     }
 
+    /// Bind a KYC or accredited credential to `subject` (admin only)
+    /// The admin attests the proof off-chain, see `verify_kyc_proof`
+    /// Returns false if the nullifier was already used
+    pub fn issue_credential(
+        env: Env,
+        subject: Address,
+        credential_type: CredentialType,
+        proof: Groth16Proof,
+        nullifier: BytesN<32>,
+        expires_at: u64,
+    ) -> bool {
+        let admin = Self::admin(env.clone());
+        admin.require_auth();
+
+        let nullifier_key = match credential_type {
+            CredentialType::Kyc => (Symbol::new(&env, "kyc_nullifier"), nullifier.clone()),
+            CredentialType::Accredited => (Symbol::new(&env, "accredited_nullifier"), nullifier.clone()),
+        };
+        if env.storage().persistent().has(&nullifier_key) {
+            return false;
+        }
+
+        let verified = Self::verify_groth16_simple(
+            env.clone(),
+            proof,
+            nullifier.clone(),
+            BytesN::from_array(&env, &[0; 32]),
+        );
+
+        if verified {
+            env.storage().persistent().set(&nullifier_key, &true);
+
+            let credential = Credential {
+                credential_type,
+                nullifier,
+                issued_at: env.ledger().timestamp(),
+                expires_at,
+            };
+            env.storage().persistent().set(
+                &(Symbol::new(&env, "credential"), subject.clone(), credential_type),
+                &credential,
+            );
+
+            env.events().publish(
+                (Symbol::new(&env, "credential_issued"), subject),
+                (credential_type, expires_at),
+            );
+        }
+
+        verified
+    }
+
+    /// Revoke a credential previously issued to `subject` (admin only)
+    pub fn revoke_credential(env: Env, subject: Address, credential_type: CredentialType) {
+        let admin = Self::admin(env.clone());
+        admin.require_auth();
+
+        env.storage().persistent().remove(
+            &(Symbol::new(&env, "credential"), subject.clone(), credential_type),
+        );
+
+        env.events().publish(
+            (Symbol::new(&env, "credential_revoked"), subject),
+            credential_type,
+        );
+    }
+
+    /// Get the credential of a given type bound to `subject`
+    pub fn get_credential(
+        env: Env,
+        subject: Address,
+        credential_type: CredentialType,
+    ) -> Option<Credential> {
+        env.storage()
+            .persistent()
+            .get(&(Symbol::new(&env, "credential"), subject, credential_type))
+    }
+
+    /// Check that `subject` holds an unexpired credential of the given type
+    pub fn has_credential(env: Env, subject: Address, credential_type: CredentialType) -> bool {
+        match Self::get_credential(env.clone(), subject, credential_type) {
+            Some(credential) => {
+                credential.expires_at == 0 || env.ledger().timestamp() < credential.expires_at
+            }
+            None => false,
+        }
+    }
+
     /// Check if nullifier has been used (any type)
     pub fn is_nullifier_used(env: Env, nullifier: BytesN<32>) -> bool {
         let kyc_key = (Symbol::new(&env, "kyc_nullifier"), nullifier.clone());
@@ -159,6 +264,19 @@ impl ZKVerifier {
             || env.storage().persistent().has(&ownership_key)
     }
 
+    /// Replace the contract code, keeping issued credentials (admin only)
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) {
+        let admin = Self::admin(env.clone());
+        admin.require_auth();
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+
+        env.events().publish(
+            (Symbol::new(&env, "upgrade"), admin),
+            new_wasm_hash,
+        );
+    }
+
     pub fn admin(env: Env) -> Address {
         env.storage()
             .instance()
@@ -170,12 +288,16 @@ impl ZKVerifier {
 #[cfg(test)]
 mod test {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Env};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        Env,
+    };
 
     #[test]
     fn test_initialize() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, ZKVerifier);
+        env.mock_all_auths();
+        let contract_id = env.register(ZKVerifier, ());
         let client = ZKVerifierClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -189,7 +311,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, ZKVerifier);
+        let contract_id = env.register(ZKVerifier, ());
         let client = ZKVerifierClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -222,7 +344,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, ZKVerifier);
+        let contract_id = env.register(ZKVerifier, ());
         let client = ZKVerifierClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -247,4 +369,72 @@ mod test {
         // Now it's used
         assert!(client.is_nullifier_used(&nullifier));
     }
+
+    #[test]
+    fn test_issue_and_expire_credential() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(ZKVerifier, ());
+        let client = ZKVerifierClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let investor = Address::generate(&env);
+        client.initialize(&admin);
+
+        let proof = Groth16Proof {
+            a: (BytesN::from_array(&env, &[1; 32]), BytesN::from_array(&env, &[2; 32])),
+            b: (
+                (BytesN::from_array(&env, &[3; 32]), BytesN::from_array(&env, &[4; 32])),
+                (BytesN::from_array(&env, &[5; 32]), BytesN::from_array(&env, &[6; 32])),
+            ),
+            c: (BytesN::from_array(&env, &[7; 32]), BytesN::from_array(&env, &[8; 32])),
+        };
+        let nullifier = BytesN::from_array(&env, &[11; 32]);
+
+        env.ledger().set_timestamp(1000);
+        assert!(!client.has_credential(&investor, &CredentialType::Kyc));
+        assert!(client.issue_credential(&investor, &CredentialType::Kyc, &proof, &nullifier, &2000));
+        assert!(client.has_credential(&investor, &CredentialType::Kyc));
+        assert!(!client.has_credential(&investor, &CredentialType::Accredited));
+
+        // Same nullifier can't back a second credential
+        let other = Address::generate(&env);
+        assert!(!client.issue_credential(&other, &CredentialType::Kyc, &proof, &nullifier, &0));
+
+        env.ledger().set_timestamp(2000);
+        assert!(!client.has_credential(&investor, &CredentialType::Kyc));
+    }
+
+    #[test]
+    fn test_upgrade_keeps_credentials() {
+        let env = Env::default();
+        env.mock_all_auths();
+
+        let contract_id = env.register(ZKVerifier, ());
+        let client = ZKVerifierClient::new(&env, &contract_id);
+
+        let admin = Address::generate(&env);
+        let investor = Address::generate(&env);
+        client.initialize(&admin);
+
+        let proof = Groth16Proof {
+            a: (BytesN::from_array(&env, &[1; 32]), BytesN::from_array(&env, &[2; 32])),
+            b: (
+                (BytesN::from_array(&env, &[3; 32]), BytesN::from_array(&env, &[4; 32])),
+                (BytesN::from_array(&env, &[5; 32]), BytesN::from_array(&env, &[6; 32])),
+            ),
+            c: (BytesN::from_array(&env, &[7; 32]), BytesN::from_array(&env, &[8; 32])),
+        };
+        let nullifier = BytesN::from_array(&env, &[12; 32]);
+        client.issue_credential(&investor, &CredentialType::Accredited, &proof, &nullifier, &0);
+
+        let wasm_hash = stellar_property_access::testutils::upload_upgrade_wasm(&env);
+        client.upgrade(&wasm_hash);
+        env.register_at(&contract_id, ZKVerifier, ());
+
+        assert_eq!(client.admin(), admin);
+        assert!(client.has_credential(&investor, &CredentialType::Accredited));
+        assert!(client.is_nullifier_used(&nullifier));
+    }
 }
@@ -0,0 +1,133 @@
+#![no_std]
+use soroban_sdk::{contracttype, Address, BytesN, Env};
+use stellar_property_errors::Error;
+use stellar_property_events as events;
+use stellar_property_storage as storage;
+
+// Shared role-based access control
+//
+// The admin implicitly holds every role and is the only account that can
+// grant or revoke them. Admin handover is two-step: the current admin
+// proposes a successor, who must accept before the change takes effect.
+// The admin is also the only account that can upgrade a contract's code.
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+    Minter = 1,
+    ComplianceOfficer = 2,
+    Pauser = 3,
+    Verifier = 4,
+    FeeManager = 5,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum AccessKey {
+    Role(Role, Address),
+    PendingAdmin,
+}
+
+/// Require authorization from the admin and return it
+pub fn require_admin(env: &Env) -> Address {
+    let admin = storage::get_admin(env);
+    admin.require_auth();
+    admin
+}
+
+pub fn has_role(env: &Env, role: Role, account: &Address) -> bool {
+    *account == storage::get_admin(env)
+        || storage::has_persistent(env, &AccessKey::Role(role, account.clone()))
+}
+
+/// Require authorization from `account` and that it holds `role`
+pub fn require_role(env: &Env, role: Role, account: &Address) -> Result<(), Error> {
+    account.require_auth();
+
+    if !has_role(env, role, account) {
+        return Err(Error::NotAuthorized);
+    }
+
+    Ok(())
+}
+
+/// Grant `role` to `account` (admin only)
+pub fn grant_role(env: &Env, role: Role, account: &Address) {
+    let admin = require_admin(env);
+
+    storage::set_persistent(env, &AccessKey::Role(role, account.clone()), &true);
+
+    events::role_granted(env, role as u32, account.clone(), admin);
+}
+
+/// Revoke `role` from `account` (admin only)
+pub fn revoke_role(env: &Env, role: Role, account: &Address) {
+    let admin = require_admin(env);
+
+    storage::remove_persistent(env, &AccessKey::Role(role, account.clone()));
+
+    events::role_revoked(env, role as u32, account.clone(), admin);
+}
+
+/// Nominate `new_admin`; takes effect once they call `accept_admin`
+pub fn propose_admin(env: &Env, new_admin: &Address) {
+    let admin = require_admin(env);
+
+    env.storage().instance().set(&AccessKey::PendingAdmin, new_admin);
+
+    events::admin_proposed(env, admin, new_admin.clone());
+}
+
+/// Complete a handover; must be authorized by the pending admin
+pub fn accept_admin(env: &Env) -> Result<Address, Error> {
+    let pending = pending_admin(env).ok_or(Error::NotAuthorized)?;
+    pending.require_auth();
+
+    let previous = storage::get_admin(env);
+    storage::set_admin(env, &pending);
+    env.storage().instance().remove(&AccessKey::PendingAdmin);
+
+    events::admin_transferred(env, previous, pending.clone());
+
+    Ok(pending)
+}
+
+pub fn pending_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&AccessKey::PendingAdmin)
+}
+
+/// Replace the contract code with `new_wasm_hash` (admin only)
+/// Storage is kept as is; layout changes are handled by the contract's `migrate`
+pub fn upgrade(env: &Env, new_wasm_hash: &BytesN<32>) {
+    let admin = require_admin(env);
+
+    env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+
+    events::contract_upgraded(env, admin, new_wasm_hash.to_array());
+}
+
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils {
+    use soroban_sdk::{Bytes, BytesN, Env};
+
+    // Smallest module the host accepts as a contract: the wasm header plus a
+    // `contractenvmetav0` section declaring protocol 22
+    const UPGRADE_WASM: [u8; 40] = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic, version
+        0x00, 0x1e, 0x11, // custom section, size, name length
+        b'c', b'o', b'n', b't', b'r', b'a', b'c', b't', b'e', b'n', b'v', b'm', b'e', b't', b'a',
+        b'v', b'0',
+        0x00, 0x00, 0x00, 0x00, // SC_ENV_META_KIND_INTERFACE_VERSION
+        0x00, 0x00, 0x00, 0x16, // protocol 22
+        0x00, 0x00, 0x00, 0x00, // pre-release 0
+    ];
+
+    /// Upload a placeholder wasm and return its hash, for upgrade tests
+    /// The placeholder exports nothing: after upgrading, re-register the
+    /// contract with `env.register_at` to run its Rust code as the new version
+    pub fn upload_upgrade_wasm(env: &Env) -> BytesN<32> {
+        env.deployer()
+            .upload_contract_wasm(Bytes::from_slice(env, &UPGRADE_WASM))
+    }
+}
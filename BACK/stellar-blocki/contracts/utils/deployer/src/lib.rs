@@ -1,6 +1,7 @@
 #![no_std]
 
 use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Symbol, Vec};
+use stellar_property_access::{self as access, Role};
 use stellar_property_errors::Error;
 use stellar_property_events as events;
 use stellar_property_storage::{self as storage, DataKey};
@@ -29,8 +30,7 @@ impl DeployerContract {
 
     /// Set the WASM hash for PropertyToken contract
     pub fn set_property_token_wasm(env: Env, wasm_hash: BytesN<32>) -> Result<(), Error> {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
+        access::require_admin(&env);
 
         env.storage()
             .instance()
@@ -45,8 +45,7 @@ impl DeployerContract {
         property_id: Symbol,
         salt: BytesN<32>,
     ) -> Result<Address, Error> {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
+        let admin = access::require_admin(&env);
 
         // Get WASM hash
         let wasm_hash: BytesN<32> = env
@@ -65,7 +64,7 @@ impl DeployerContract {
         let deployed_address = env
             .deployer()
             .with_current_contract(salt)
-            .deploy(wasm_hash);
+            .deploy_v2(wasm_hash, ());
 
         // Store deployed contract address
         storage::set_persistent(&env, &deploy_key, &deployed_address);
@@ -100,14 +99,13 @@ impl DeployerContract {
         wasm_hash: BytesN<32>,
         salt: BytesN<32>,
     ) -> Result<Address, Error> {
-        let admin = storage::get_admin(&env);
-        admin.require_auth();
+        let admin = access::require_admin(&env);
 
         // Deploy contract
         let deployed_address = env
             .deployer()
             .with_current_contract(salt)
-            .deploy(wasm_hash);
+            .deploy_v2(wasm_hash, ());
 
         // Add to deployed contracts list
         let mut deployed_contracts: Vec<Address> = env
@@ -133,6 +131,42 @@ impl DeployerContract {
         Ok(deployed_address)
     }
 
+    // ========== ACCESS CONTROL ==========
+
+    /// Grant `role` to `account` (admin only)
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::grant_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Revoke `role` from `account` (admin only)
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        access::revoke_role(&env, role, &account);
+        Ok(())
+    }
+
+    /// Check whether `account` holds `role` (the admin holds every role)
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        access::has_role(&env, role, &account)
+    }
+
+    /// Nominate a new admin; takes effect once they call `accept_admin` (admin only)
+    pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        access::propose_admin(&env, &new_admin);
+        Ok(())
+    }
+
+    /// Accept a pending admin nomination (pending admin only)
+    pub fn accept_admin(env: Env) -> Result<(), Error> {
+        access::accept_admin(&env)?;
+        Ok(())
+    }
+
+    /// Get the nominated admin awaiting acceptance
+    pub fn pending_admin(env: Env) -> Option<Address> {
+        access::pending_admin(&env)
+    }
+
     // ========== VIEW FUNCTIONS ==========
 
     /// Get all deployed contracts
@@ -181,7 +215,9 @@ mod test {
     #[test]
     fn test_initialize() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, DeployerContract);
+        env.mock_all_auths();
+
+        let contract_id = env.register(DeployerContract, ());
         let client = DeployerContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -195,7 +231,7 @@ mod test {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, DeployerContract);
+        let contract_id = env.register(DeployerContract, ());
         let client = DeployerContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -212,7 +248,9 @@ mod test {
     #[test]
     fn test_deploy_count() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, DeployerContract);
+        env.mock_all_auths();
+
+        let contract_id = env.register(DeployerContract, ());
         let client = DeployerContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
@@ -225,12 +263,12 @@ mod test {
     }
 
     #[test]
-    #[should_panic(expected = "Error(InvalidWasmHash)")]
+    #[should_panic(expected = "Error(Contract, #62)")]
     fn test_deploy_without_wasm_hash() {
         let env = Env::default();
         env.mock_all_auths();
 
-        let contract_id = env.register_contract(None, DeployerContract);
+        let contract_id = env.register(DeployerContract, ());
         let client = DeployerContractClient::new(&env, &contract_id);
 
         let admin = Address::generate(&env);
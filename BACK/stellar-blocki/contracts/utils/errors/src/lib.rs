@@ -32,6 +32,7 @@ pub enum Error {
     InvalidPrice = 35,
     InvalidListingAmount = 36,
     NotListingOwner = 37,
+    SettlementTokenMismatch = 38,
 
     // Escrow errors (41-50)
     EscrowNotFound = 41,
@@ -54,4 +55,189 @@ pub enum Error {
     InvalidWasmHash = 62,
     InvalidSalt = 63,
     ContractAlreadyDeployed = 64,
+
+    // Compliance errors (71-80)
+    ReceiverNotAllowlisted = 71,
+    KycCredentialRequired = 72,
+    AccreditationRequired = 73,
+    AccountFrozen = 74,
+    ContractPaused = 75,
+    TokensLocked = 76,
+    HolderCapExceeded = 77,
+
+    // Redemption errors (81-90)
+    RedemptionNotActive = 81,
+    RedemptionAlreadyOpened = 82,
+    RedemptionNotEnded = 83,
+
+    // Permit errors (91-100)
+    InvalidSignature = 91,
+    PermitExpired = 92,
+    InvalidNonce = 93,
+}
+
+/// Errors for the property token contract.
+/// Kept apart from `Error` for the same reason as `GovernanceError`; shared
+/// codes keep the same numbers as in `Error`.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum TokenError {
+    // General errors (1-10)
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotAuthorized = 3,
+    InvalidAmount = 4,
+    InvalidPercentage = 5,
+
+    // Token errors (11-20)
+    InsufficientBalance = 11,
+    InsufficientAllowance = 12,
+    TokenNotFound = 13,
+    MintExceedsSupply = 14,
+
+    // Registry errors (51-60)
+    InvalidDocumentHash = 55,
+
+    // Compliance errors (71-80)
+    ReceiverNotAllowlisted = 71,
+    KycCredentialRequired = 72,
+    AccreditationRequired = 73,
+    AccountFrozen = 74,
+    ContractPaused = 75,
+    TokensLocked = 76,
+    HolderCapExceeded = 77,
+    VerifierNotSet = 78,
+    InvalidReason = 79,
+
+    // Redemption errors (81-90)
+    RedemptionNotActive = 81,
+    RedemptionAlreadyOpened = 82,
+    RedemptionNotEnded = 83,
+
+    // Permit errors (91-100)
+    InvalidSignature = 91,
+    PermitExpired = 92,
+    InvalidNonce = 93,
+}
+
+/// Errors for the holder governance contract.
+/// Kept apart from `Error` because a contract spec caps an error enum at 50
+/// cases; shared codes keep the same numbers as in `Error`.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum GovernanceError {
+    // General errors (1-10)
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotAuthorized = 3,
+    InvalidAmount = 4,
+    InvalidPercentage = 5,
+
+    // Governance errors (101-110)
+    ProposalNotFound = 101,
+    BelowProposalThreshold = 102,
+    VotingNotStarted = 103,
+    VotingClosed = 104,
+    VotingNotEnded = 105,
+    AlreadyVoted = 106,
+    NoVotingPower = 107,
+    ProposalNotPassed = 108,
+    ProposalAlreadyDecided = 109,
+}
+
+/// Errors for the primary offering contract.
+/// Kept apart from `Error` for the same reason as `GovernanceError`; shared
+/// codes keep the same numbers as in `Error`.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum OfferingError {
+    // General errors (1-10)
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotAuthorized = 3,
+    InvalidAmount = 4,
+
+    // Offering errors (111-120)
+    OfferingClosed = 111,
+    OfferingNotEnded = 112,
+    HardCapExceeded = 113,
+    TicketBelowMinimum = 114,
+    TicketAboveMaximum = 115,
+    SoftCapNotReached = 116,
+    SoftCapReached = 117,
+    NothingToSettle = 118,
+    InvestorNotEligible = 119,
+}
+
+/// Errors for the marketplace contract.
+/// Kept apart from `Error` for the same reason as `GovernanceError`; shared
+/// codes keep the same numbers as in `Error`. Compliance errors are raised by
+/// property tokens when a delivery is refused.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum MarketplaceError {
+    // General errors (1-10)
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotAuthorized = 3,
+    InvalidAmount = 4,
+    InvalidPercentage = 5,
+
+    // Token errors (11-20)
+    InsufficientBalance = 11,
+    InsufficientAllowance = 12,
+
+    // Marketplace errors (31-40)
+    ListingNotFound = 31,
+    ListingAlreadyExists = 32,
+    ListingExpired = 33,
+    ListingCancelled = 34,
+    InvalidPrice = 35,
+    InvalidListingAmount = 36,
+    NotListingOwner = 37,
+    SettlementTokenMismatch = 38,
+
+    // Escrow errors (41-50)
+    EscrowNotFound = 41,
+    EscrowNotLocked = 43,
+    EscrowAlreadyReleased = 44,
+    EscrowTimeoutNotReached = 46,
+
+    // Compliance errors (71-80)
+    ReceiverNotAllowlisted = 71,
+    KycCredentialRequired = 72,
+    AccreditationRequired = 73,
+    AccountFrozen = 74,
+    ContractPaused = 75,
+    TokensLocked = 76,
+    HolderCapExceeded = 77,
+
+    // Order book, auction and registry sync errors (121-130)
+    OrderBookFull = 121,
+    RegistrySyncFailed = 122,
+    ListingNotExpired = 123,
+    SelfTrade = 124,
+    AuctionNotEnded = 125,
+}
+
+/// Errors for the oracle consumer contract.
+/// Kept apart from `Error` for the same reason as `GovernanceError`; shared
+/// codes keep the same numbers as in `Error`.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum OracleError {
+    // General errors (1-10)
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    NotAuthorized = 3,
+    InvalidAmount = 4,
+
+    // Oracle errors (131-140)
+    PriceNotFound = 131,
+    StalePrice = 132,
 }
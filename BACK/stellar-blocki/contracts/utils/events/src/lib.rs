@@ -1,31 +1,216 @@
 #![no_std]
-use soroban_sdk::{Address, Env, Symbol, symbol_short};
+use soroban_sdk::{Address, Env, String, Symbol, Vec, symbol_short};
 
-// Property Token Events
+// Access Control Events
+pub fn role_granted(env: &Env, role: u32, account: Address, admin: Address) {
+    let topics = (symbol_short!("role_grnt"), role, account);
+    env.events().publish(topics, admin);
+}
+
+pub fn role_revoked(env: &Env, role: u32, account: Address, admin: Address) {
+    let topics = (symbol_short!("role_rvk"), role, account);
+    env.events().publish(topics, admin);
+}
+
+pub fn admin_proposed(env: &Env, admin: Address, pending_admin: Address) {
+    let topics = (symbol_short!("adm_prop"), admin);
+    env.events().publish(topics, pending_admin);
+}
+
+pub fn admin_transferred(env: &Env, previous_admin: Address, new_admin: Address) {
+    let topics = (symbol_short!("adm_xfer"), previous_admin);
+    env.events().publish(topics, new_admin);
+}
+
+// Upgrade Events
+pub fn contract_upgraded(env: &Env, admin: Address, new_wasm_hash: [u8; 32]) {
+    let topics = (symbol_short!("upgrade"), admin);
+    env.events().publish(topics, new_wasm_hash);
+}
+
+pub fn schema_migrated(env: &Env, from_version: u32, to_version: u32) {
+    let topics = (symbol_short!("migrate"),);
+    env.events().publish(topics, (from_version, to_version));
+}
+
+// Property Token Events (SEP-41 topic/data layout)
 pub fn transfer(env: &Env, from: Address, to: Address, amount: i128) {
     let topics = (symbol_short!("transfer"), from, to);
     env.events().publish(topics, amount);
 }
 
-pub fn mint(env: &Env, to: Address, amount: i128) {
-    let topics = (symbol_short!("mint"), to);
+pub fn mint(env: &Env, admin: Address, to: Address, amount: i128) {
+    let topics = (symbol_short!("mint"), admin, to);
     env.events().publish(topics, amount);
 }
 
+pub fn approve(env: &Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+    let topics = (symbol_short!("approve"), from, spender);
+    env.events().publish(topics, (amount, expiration_ledger));
+}
+
 pub fn burn(env: &Env, from: Address, amount: i128) {
     let topics = (symbol_short!("burn"), from);
     env.events().publish(topics, amount);
 }
 
+pub fn snapshot_taken(env: &Env, snapshot_id: u64, ledger: u32) {
+    let topics = (symbol_short!("snapshot"), snapshot_id);
+    env.events().publish(topics, ledger);
+}
+
+// Distribution Events
+pub fn distribution_deposited(env: &Env, distribution_id: u64, asset: Address, amount: i128, period: u64) {
+    let topics = (symbol_short!("dist_dep"), distribution_id, asset);
+    env.events().publish(topics, (amount, period));
+}
+
+pub fn distribution_claimed(env: &Env, holder: Address, asset: Address, amount: i128) {
+    let topics = (symbol_short!("dist_clm"), holder, asset);
+    env.events().publish(topics, amount);
+}
+
+pub fn distribution_custodian_set(env: &Env, account: Address, custodian: bool) {
+    let topics = (symbol_short!("dist_cust"), account);
+    env.events().publish(topics, custodian);
+}
+
+pub fn distribution_manager_set(env: &Env, manager: Address) {
+    let topics = (symbol_short!("dist_mgr"),);
+    env.events().publish(topics, manager);
+}
+
+// Compliance Events
+pub fn compliance_updated(env: &Env, verifier: Option<Address>, require_kyc: bool, require_accredited: bool, use_allowlist: bool) {
+    let topics = (symbol_short!("comply"),);
+    env.events().publish(topics, (verifier, require_kyc, require_accredited, use_allowlist));
+}
+
+pub fn allowlist_updated(env: &Env, account: Address, allowed: bool) {
+    let topics = (symbol_short!("allowlst"), account);
+    env.events().publish(topics, allowed);
+}
+
+pub fn holder_cap_set(env: &Env, cap_bps: i128) {
+    let topics = (symbol_short!("hold_cap"),);
+    env.events().publish(topics, cap_bps);
+}
+
+pub fn cap_exemption_updated(env: &Env, account: Address, exempt: bool) {
+    let topics = (symbol_short!("cap_exmp"), account);
+    env.events().publish(topics, exempt);
+}
+
+// Redemption Events
+pub fn redemption_opened(env: &Env, asset: Address, proceeds: i128, supply: i128, deadline: u64) {
+    let topics = (symbol_short!("rdm_open"), asset);
+    env.events().publish(topics, (proceeds, supply, deadline));
+}
+
+pub fn redeemed(env: &Env, holder: Address, amount: i128, payout: i128) {
+    let topics = (symbol_short!("redeem"), holder);
+    env.events().publish(topics, (amount, payout));
+}
+
+pub fn redemption_closed(env: &Env, recipient: Address, unclaimed: i128) {
+    let topics = (symbol_short!("rdm_close"), recipient);
+    env.events().publish(topics, unclaimed);
+}
+
+// Issuer Control Events
+pub fn account_frozen(env: &Env, caller: Address, account: Address) {
+    let topics = (symbol_short!("freeze"), account);
+    env.events().publish(topics, caller);
+}
+
+pub fn account_unfrozen(env: &Env, caller: Address, account: Address) {
+    let topics = (symbol_short!("unfreeze"), account);
+    env.events().publish(topics, caller);
+}
+
+pub fn paused(env: &Env, caller: Address) {
+    let topics = (symbol_short!("pause"),);
+    env.events().publish(topics, caller);
+}
+
+pub fn unpaused(env: &Env, caller: Address) {
+    let topics = (symbol_short!("unpause"),);
+    env.events().publish(topics, caller);
+}
+
+pub fn forced_transfer(env: &Env, record_id: u64, from: Address, to: Address, amount: i128, reason: String, document_hash: [u8; 32]) {
+    let topics = (symbol_short!("forced"), from, to);
+    env.events().publish(topics, (record_id, amount, reason, document_hash));
+}
+
+// Vesting Events
+pub fn vesting_created(env: &Env, holder: Address, schedule_index: u32, amount: i128, cliff_time: u64, end_time: u64, revocable: bool) {
+    let topics = (symbol_short!("vest_new"), holder, schedule_index);
+    env.events().publish(topics, (amount, cliff_time, end_time, revocable));
+}
+
+pub fn vesting_revoked(env: &Env, holder: Address, schedule_index: u32, unvested: i128) {
+    let topics = (symbol_short!("vest_rev"), holder, schedule_index);
+    env.events().publish(topics, unvested);
+}
+
+// Governance Events
+pub fn proposal_created(env: &Env, proposal_id: u64, proposer: Address, snapshot_ledger: u32, end_time: u64) {
+    let topics = (symbol_short!("prop_new"), proposal_id, proposer);
+    env.events().publish(topics, (snapshot_ledger, end_time));
+}
+
+pub fn vote_cast(env: &Env, proposal_id: u64, voter: Address, support: u32, weight: i128) {
+    let topics = (symbol_short!("vote"), proposal_id, voter);
+    env.events().publish(topics, (support, weight));
+}
+
+pub fn proposal_decided(env: &Env, proposal_id: u64, passed: bool, votes_for: i128, votes_against: i128, turnout_bps: i128) {
+    let topics = (symbol_short!("prop_dec"), proposal_id);
+    env.events().publish(topics, (passed, votes_for, votes_against, turnout_bps));
+}
+
+pub fn proposal_executed(env: &Env, proposal_id: u64, executor: Address) {
+    let topics = (symbol_short!("prop_exe"), proposal_id);
+    env.events().publish(topics, executor);
+}
+
+// Primary Offering Events
+pub fn offering_opened(env: &Env, token: Address, issuer: Address, hard_cap: i128, price_per_token: i128, deadline: u64) {
+    let topics = (symbol_short!("sto_open"), token, issuer);
+    env.events().publish(topics, (hard_cap, price_per_token, deadline));
+}
+
+pub fn offering_invested(env: &Env, investor: Address, tokens: i128, paid: i128) {
+    let topics = (symbol_short!("sto_inv"), investor);
+    env.events().publish(topics, (tokens, paid));
+}
+
+pub fn offering_finalized(env: &Env, succeeded: bool, sold: i128, raised: i128) {
+    let topics = (symbol_short!("sto_fin"),);
+    env.events().publish(topics, (succeeded, sold, raised));
+}
+
+pub fn offering_claimed(env: &Env, investor: Address, tokens: i128) {
+    let topics = (symbol_short!("sto_clm"), investor);
+    env.events().publish(topics, tokens);
+}
+
+pub fn offering_refunded(env: &Env, investor: Address, amount: i128) {
+    let topics = (symbol_short!("sto_rfnd"), investor);
+    env.events().publish(topics, amount);
+}
+
 // Marketplace Events
 pub fn listing_created(env: &Env, listing_id: u64, seller: Address, token: Address, amount: i128, price: i128) {
     let topics = (symbol_short!("list_new"), listing_id, seller);
     env.events().publish(topics, (token, amount, price));
 }
 
-pub fn tokens_purchased(env: &Env, listing_id: u64, buyer: Address, seller: Address, amount: i128, price: i128) {
+#[allow(clippy::too_many_arguments)]
+pub fn tokens_purchased(env: &Env, listing_id: u64, buyer: Address, seller: Address, amount: i128, price: i128, buyer_fee: i128, seller_fee: i128) {
     let topics = (symbol_short!("purchase"), listing_id, buyer);
-    env.events().publish(topics, (seller, amount, price));
+    env.events().publish(topics, (seller, amount, price, buyer_fee, seller_fee));
 }
 
 pub fn listing_cancelled(env: &Env, listing_id: u64, seller: Address) {
@@ -33,6 +218,112 @@ pub fn listing_cancelled(env: &Env, listing_id: u64, seller: Address) {
     env.events().publish(topics, seller);
 }
 
+pub fn dutch_auction_listed(env: &Env, listing_id: u64, start_price: i128, floor_price: i128, start_time: u64, end_time: u64) {
+    let topics = (symbol_short!("dutch_new"), listing_id);
+    env.events().publish(topics, (start_price, floor_price, start_time, end_time));
+}
+
+pub fn listing_repriced(env: &Env, listing_id: u64, old_price: i128, new_price: i128) {
+    let topics = (symbol_short!("list_prc"), listing_id);
+    env.events().publish(topics, (old_price, new_price));
+}
+
+pub fn listing_amount_changed(env: &Env, listing_id: u64, seller: Address, change: i128, new_amount: i128) {
+    let topics = (symbol_short!("list_amt"), listing_id, seller);
+    env.events().publish(topics, (change, new_amount));
+}
+
+pub fn listing_expired(env: &Env, listing_id: u64, seller: Address, returned: i128) {
+    let topics = (symbol_short!("list_exp"), listing_id, seller);
+    env.events().publish(topics, returned);
+}
+
+pub fn listing_unbooked(env: &Env, listing_id: u64, seller: Address) {
+    let topics = (symbol_short!("list_off"), listing_id, seller);
+    env.events().publish(topics, ());
+}
+
+pub fn tokens_reserved(env: &Env, listing_id: u64, reservation_id: u64, buyer: Address, amount: i128, price: i128) {
+    let topics = (symbol_short!("reserve"), listing_id, buyer);
+    env.events().publish(topics, (reservation_id, amount, price));
+}
+
+pub fn reservation_confirmed(env: &Env, reservation_id: u64, confirmed_by: Address) {
+    let topics = (symbol_short!("rsv_conf"), reservation_id);
+    env.events().publish(topics, confirmed_by);
+}
+
+pub fn reservation_refunded(env: &Env, reservation_id: u64, buyer: Address, amount: i128) {
+    let topics = (symbol_short!("rsv_rfnd"), reservation_id, buyer);
+    env.events().publish(topics, amount);
+}
+
+pub fn bid_placed(env: &Env, bid_id: u64, buyer: Address, token: Address, amount: i128, price: i128) {
+    let topics = (symbol_short!("bid_new"), bid_id, buyer);
+    env.events().publish(topics, (token, amount, price));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn bid_filled(env: &Env, bid_id: u64, seller: Address, listing_id: Option<u64>, amount: i128, price: i128, buyer_fee: i128, seller_fee: i128) {
+    let topics = (symbol_short!("bid_fill"), bid_id, seller);
+    env.events().publish(topics, (listing_id, amount, price, buyer_fee, seller_fee));
+}
+
+pub fn bid_cancelled(env: &Env, bid_id: u64, buyer: Address, refunded: i128) {
+    let topics = (symbol_short!("bid_cncl"), bid_id, buyer);
+    env.events().publish(topics, refunded);
+}
+
+pub fn bid_unbooked(env: &Env, bid_id: u64, buyer: Address) {
+    let topics = (symbol_short!("bid_off"), bid_id, buyer);
+    env.events().publish(topics, ());
+}
+
+pub fn refund_claimed(env: &Env, account: Address, asset: Address, amount: i128) {
+    let topics = (symbol_short!("rfnd_clm"), account, asset);
+    env.events().publish(topics, amount);
+}
+
+pub fn auction_created(env: &Env, auction_id: u64, seller: Address, token: Address, amount: i128, reserve_price: i128, end_time: u64) {
+    let topics = (symbol_short!("auc_new"), auction_id, seller);
+    env.events().publish(topics, (token, amount, reserve_price, end_time));
+}
+
+pub fn auction_bid(env: &Env, auction_id: u64, bidder: Address, bid: i128) {
+    let topics = (symbol_short!("auc_bid"), auction_id, bidder);
+    env.events().publish(topics, bid);
+}
+
+pub fn auction_outbid(env: &Env, auction_id: u64, bidder: Address, refunded: i128) {
+    let topics = (symbol_short!("auc_rfnd"), auction_id, bidder);
+    env.events().publish(topics, refunded);
+}
+
+pub fn auction_extended(env: &Env, auction_id: u64, end_time: u64) {
+    let topics = (symbol_short!("auc_ext"), auction_id);
+    env.events().publish(topics, end_time);
+}
+
+pub fn auction_settled(env: &Env, auction_id: u64, winner: Option<Address>, price: i128, buyer_fee: i128, seller_fee: i128) {
+    let topics = (symbol_short!("auc_done"), auction_id);
+    env.events().publish(topics, (winner, price, buyer_fee, seller_fee));
+}
+
+pub fn fees_updated(env: &Env, recipient: Address, buyer_fee_bps: u32, seller_fee_bps: u32, min_fee: i128) {
+    let topics = (symbol_short!("fee_set"), recipient);
+    env.events().publish(topics, (buyer_fee_bps, seller_fee_bps, min_fee));
+}
+
+pub fn token_fees_updated(env: &Env, token: Address, fees: Option<(u32, u32, i128)>) {
+    let topics = (symbol_short!("fee_tok"), token);
+    env.events().publish(topics, fees);
+}
+
+pub fn registry_sync_failed(env: &Env, property_id: Symbol, accounts: Vec<Address>) {
+    let topics = (symbol_short!("reg_fail"), property_id);
+    env.events().publish(topics, accounts);
+}
+
 // Escrow Events
 pub fn funds_locked(env: &Env, escrow_id: u64, buyer: Address, amount: i128) {
     let topics = (symbol_short!("esc_lock"), escrow_id, buyer);
@@ -1,5 +1,5 @@
 #![no_std]
-use soroban_sdk::{contracttype, Address, Env, Symbol};
+use soroban_sdk::{contracttype, Address, Env, Symbol, TryFromVal, Val};
 
 // SECURITY: Production-grade TTL settings
 // Persistent storage should last ~1 year minimum (17M ledgers = ~330 days)
@@ -13,12 +13,16 @@ pub enum DataKey {
     // Instance storage (small, rarely changing)
     Admin,
     Initialized,
+    SchemaVersion,               // Storage layout version, see get_schema_version
 
     // Persistent storage (user balances, ownerships)
     Balance(Address),
     Allowance(Address, Address), // (owner, spender)
     TotalMinted,                 // Total tokens minted
-    OwnersList,                  // List of all token owners
+    OwnersList,                  // Legacy list of token owners, replaced by the owner index
+    OwnerCount,                  // Number of holders with a non-zero balance
+    OwnerAt(u32),                // Holder stored at an index
+    OwnerIndex(Address),         // Index of a holder
 
     // Property storage
     PropertyData(Symbol),
@@ -39,6 +43,14 @@ pub enum DataKey {
     DocumentHash(Symbol),
 }
 
+// Allowance entry with SEP-41 expiration semantics
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
 // Instance Storage Helpers (for admin, config - small data)
 pub fn set_admin(env: &Env, admin: &Address) {
     env.storage().instance().set(&DataKey::Admin, admin);
@@ -60,6 +72,16 @@ pub fn is_initialized(env: &Env) -> bool {
     env.storage().instance().has(&DataKey::Initialized)
 }
 
+// Schema version helpers
+// Contracts deployed before versioning have no entry and report version 1
+pub fn get_schema_version(env: &Env) -> u32 {
+    env.storage().instance().get(&DataKey::SchemaVersion).unwrap_or(1)
+}
+
+pub fn set_schema_version(env: &Env, version: u32) {
+    env.storage().instance().set(&DataKey::SchemaVersion, &version);
+}
+
 // Persistent Storage Helpers (for balances, ownership - bounded data)
 pub fn set_balance(env: &Env, address: &Address, amount: i128) {
     let key = DataKey::Balance(address.clone());
@@ -72,28 +94,58 @@ pub fn get_balance(env: &Env, address: &Address) -> i128 {
     env.storage().persistent().get(&key).unwrap_or(0)
 }
 
-pub fn set_allowance(env: &Env, owner: &Address, spender: &Address, amount: i128) {
+pub fn set_allowance(env: &Env, owner: &Address, spender: &Address, amount: i128, expiration_ledger: u32) {
     let key = DataKey::Allowance(owner.clone(), spender.clone());
-    env.storage().persistent().set(&key, &amount);
+    let value = AllowanceValue { amount, expiration_ledger };
+    env.storage().persistent().set(&key, &value);
     env.storage().persistent().extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTENSION);
 }
 
-pub fn get_allowance(env: &Env, owner: &Address, spender: &Address) -> i128 {
+// Returns the raw allowance entry (may be expired)
+// Entries written before expirations were added hold a bare amount that never expires
+pub fn get_allowance_value(env: &Env, owner: &Address, spender: &Address) -> AllowanceValue {
     let key = DataKey::Allowance(owner.clone(), spender.clone());
-    env.storage().persistent().get(&key).unwrap_or(0)
+    let raw: Val = match env.storage().persistent().get(&key) {
+        Some(raw) => raw,
+        None => {
+            return AllowanceValue {
+                amount: 0,
+                expiration_ledger: 0,
+            }
+        }
+    };
+
+    match i128::try_from_val(env, &raw) {
+        Ok(amount) => AllowanceValue {
+            amount,
+            expiration_ledger: u32::MAX,
+        },
+        Err(_) => AllowanceValue::try_from_val(env, &raw).unwrap(),
+    }
+}
+
+// Returns the spendable allowance; expired entries count as zero
+pub fn get_allowance(env: &Env, owner: &Address, spender: &Address) -> i128 {
+    let value = get_allowance_value(env, owner, spender);
+    if value.expiration_ledger < env.ledger().sequence() {
+        0
+    } else {
+        value.amount
+    }
 }
 
 // Counter helpers (for listings, escrows)
-pub fn get_and_increment_counter(env: &Env, key: DataKey) -> u64 {
+pub fn get_and_increment_counter(env: &Env, key: impl soroban_sdk::IntoVal<Env, soroban_sdk::Val>) -> u64 {
     let counter: u64 = env.storage().persistent().get(&key).unwrap_or(0);
     env.storage().persistent().set(&key, &(counter + 1));
     counter
 }
 
 // Generic persistent storage helpers
-pub fn set_persistent<T: soroban_sdk::IntoVal<Env, soroban_sdk::Val> + soroban_sdk::TryFromVal<Env, soroban_sdk::Val>>(
+// Keys are generic so contracts can use their own key enums with the same TTL policy
+pub fn set_persistent<T: soroban_sdk::IntoVal<Env, soroban_sdk::Val>>(
     env: &Env,
-    key: &DataKey,
+    key: &impl soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
     value: &T,
 ) {
     env.storage().persistent().set(key, value);
@@ -102,16 +154,16 @@ pub fn set_persistent<T: soroban_sdk::IntoVal<Env, soroban_sdk::Val> + soroban_s
 
 pub fn get_persistent<T: soroban_sdk::TryFromVal<Env, soroban_sdk::Val>>(
     env: &Env,
-    key: &DataKey,
+    key: &impl soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
 ) -> Option<T> {
     env.storage().persistent().get(key)
 }
 
-pub fn has_persistent(env: &Env, key: &DataKey) -> bool {
+pub fn has_persistent(env: &Env, key: &impl soroban_sdk::IntoVal<Env, soroban_sdk::Val>) -> bool {
     env.storage().persistent().has(key)
 }
 
-pub fn remove_persistent(env: &Env, key: &DataKey) {
+pub fn remove_persistent(env: &Env, key: &impl soroban_sdk::IntoVal<Env, soroban_sdk::Val>) {
     env.storage().persistent().remove(key);
 }
 